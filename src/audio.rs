@@ -0,0 +1,268 @@
+//! This module allows to promote the audio rendering threads to the realtime
+//! scheduling suitable for the platform in one call, deriving the parameters
+//! from the audio buffer the thread renders:
+//!
+//! * On macOS and iOS, the time constraint policy with the period of the
+//!   buffer, see `set_thread_time_constraint_policy`.
+//! * On Android, the policy of the Android audio framework, see
+//!   `promote_to_audio_thread`.
+//! * On Windows, the `"Pro Audio"` task of the Multimedia Class Scheduler
+//!   Service, see `MmcssTask`.
+//! * On the other platforms, the `SCHED_FIFO` policy.
+//!
+//! On Linux, the `SCHED_FIFO` policy isn't requested from `rtkit` yet, so only
+//! the processes with the `CAP_SYS_NICE` capability or a non-zero
+//! `RLIMIT_RTPRIO` limit can promote their threads, while the unprivileged
+//! desktop applications get `Error::OS(EPERM)`. Until then, this module can't
+//! replace the `audio_thread_priority` crate on Linux.
+//!
+//! TODO: on `EPERM`, fall back to `MakeThreadRealtime` of the
+//! `org.freedesktop.RealtimeKit1` D-Bus service.
+//!
+//! ```rust,no_run
+//! use thread_priority::audio::promote_current_thread_to_real_time;
+//!
+//! std::thread::spawn(|| {
+//!     let handle = promote_current_thread_to_real_time(512, 48_000).unwrap();
+//!     // Render the audio.
+//!     handle.demote().unwrap();
+//! });
+//! ```
+
+use std::time::Duration;
+
+use crate::Error;
+
+/// The buffer size assumed when the one of the audio device isn't known.
+const DEFAULT_FRAMES_PER_BUFFER: u32 = 512;
+
+/// The `SCHED_FIFO` priority of the audio threads, the one the audio servers
+/// like PipeWire and JACK ask `rtkit` for.
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos"
+    ))
+))]
+const AUDIO_THREAD_FIFO_PRIORITY: u32 = 10;
+
+/// Returns the time it takes to play the buffer.
+fn buffer_duration(frames_per_buffer: u32, sample_rate: u32) -> Result<Duration, Error> {
+    if sample_rate == 0 {
+        return Err(Error::Priority("The sample rate must be positive."));
+    }
+    let frames = match frames_per_buffer {
+        0 => DEFAULT_FRAMES_PER_BUFFER,
+        frames => frames,
+    };
+    Ok(Duration::from_nanos(
+        frames as u64 * 1_000_000_000 / sample_rate as u64,
+    ))
+}
+
+/// The realtime scheduling of an audio thread, which is demoted back to the
+/// previous scheduling when dropped, see
+/// [`promote_current_thread_to_real_time`].
+///
+/// The handle must be dropped on the thread it was created on.
+#[derive(Debug)]
+pub struct RealTimeAudioHandle {
+    #[cfg(unix)]
+    guard: Option<crate::PriorityGuard>,
+    #[cfg(windows)]
+    task: Option<crate::MmcssTask>,
+}
+
+impl RealTimeAudioHandle {
+    /// Demotes the thread to the scheduling it had before the promotion,
+    /// returning the result of doing so. Dropping the handle does the same,
+    /// but only logs the errors.
+    pub fn demote(mut self) -> Result<(), Error> {
+        self.demote_inner()
+    }
+
+    fn demote_inner(&mut self) -> Result<(), Error> {
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos"
+        ))]
+        if self.guard.is_some() {
+            crate::set_thread_standard_policy(crate::thread_native_id())?;
+        }
+        #[cfg(unix)]
+        if let Some(guard) = self.guard.take() {
+            guard.restore()?;
+        }
+        #[cfg(windows)]
+        if let Some(task) = self.task.take() {
+            task.leave()?;
+        }
+        Ok(())
+    }
+}
+
+impl crate::Promotion for RealTimeAudioHandle {
+    fn demote(self) -> Result<(), Error> {
+        RealTimeAudioHandle::demote(self)
+    }
+}
+
+impl Drop for RealTimeAudioHandle {
+    fn drop(&mut self) {
+        if let Err(e) = self.demote_inner() {
+            log::warn!(
+                "Couldn't demote the audio thread named {:?}: {:?}",
+                std::thread::current().name(),
+                e,
+            );
+        }
+    }
+}
+
+/// Promotes the current thread to the realtime scheduling for rendering the
+/// audio buffers of `frames_per_buffer` frames at the `sample_rate`, returning
+/// the handle demoting the thread back.
+///
+/// When the buffer size isn't known, `0` may be passed for
+/// `frames_per_buffer`, in which case 512 frames are assumed.
+///
+/// The period of the time constraint policy is the duration of the buffer,
+/// half of which the thread may compute for. When demoted, the thread is reset
+/// to the standard policy, see `set_thread_standard_policy`.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::audio::promote_current_thread_to_real_time;
+///
+/// let handle = promote_current_thread_to_real_time(256, 44_100).unwrap();
+/// // The thread is demoted when the handle is dropped.
+/// drop(handle);
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn promote_current_thread_to_real_time(
+    frames_per_buffer: u32,
+    sample_rate: u32,
+) -> Result<RealTimeAudioHandle, Error> {
+    let period = buffer_duration(frames_per_buffer, sample_rate)?;
+    let guard = crate::PriorityGuard::save()?;
+    crate::set_current_thread_time_constraint_policy(period, period / 2, period, true)?;
+    Ok(RealTimeAudioHandle { guard: Some(guard) })
+}
+
+/// Promotes the current thread to the realtime scheduling for rendering the
+/// audio buffers of `frames_per_buffer` frames at the `sample_rate`, returning
+/// the handle demoting the thread back.
+///
+/// When the buffer size isn't known, `0` may be passed for
+/// `frames_per_buffer`, in which case 512 frames are assumed.
+///
+/// On Android, the buffer only has to be valid, as the scheduling of the audio
+/// threads doesn't depend on it.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::audio::promote_current_thread_to_real_time;
+///
+/// let handle = promote_current_thread_to_real_time(256, 44_100).unwrap();
+/// // The thread is demoted when the handle is dropped.
+/// drop(handle);
+/// ```
+#[cfg(target_os = "android")]
+pub fn promote_current_thread_to_real_time(
+    frames_per_buffer: u32,
+    sample_rate: u32,
+) -> Result<RealTimeAudioHandle, Error> {
+    buffer_duration(frames_per_buffer, sample_rate)?;
+    let guard = crate::PriorityGuard::save()?;
+    crate::promote_to_audio_thread()?;
+    Ok(RealTimeAudioHandle { guard: Some(guard) })
+}
+
+/// Promotes the current thread to the realtime scheduling for rendering the
+/// audio buffers of `frames_per_buffer` frames at the `sample_rate`, returning
+/// the handle demoting the thread back.
+///
+/// When the buffer size isn't known, `0` may be passed for
+/// `frames_per_buffer`, in which case 512 frames are assumed.
+///
+/// The `SCHED_FIFO` policy requires the `CAP_SYS_NICE` capability or a
+/// non-zero `RLIMIT_RTPRIO` limit on Linux, see
+/// [`crate::can_set_realtime_priority`] and the [module](self) documentation.
+/// The buffer only has to be valid, as the policy doesn't depend on it.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::audio::promote_current_thread_to_real_time;
+///
+/// let handle = promote_current_thread_to_real_time(256, 44_100).unwrap();
+/// // The thread is demoted when the handle is dropped.
+/// drop(handle);
+/// ```
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos"
+    ))
+))]
+pub fn promote_current_thread_to_real_time(
+    frames_per_buffer: u32,
+    sample_rate: u32,
+) -> Result<RealTimeAudioHandle, Error> {
+    use crate::{RealtimeThreadSchedulePolicy, ThreadPriority, ThreadPriorityOsValue};
+
+    buffer_duration(frames_per_buffer, sample_rate)?;
+    let guard = crate::PriorityGuard::raise(
+        ThreadPriority::Os(ThreadPriorityOsValue(AUDIO_THREAD_FIFO_PRIORITY)),
+        crate::ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+    )?;
+    Ok(RealTimeAudioHandle { guard: Some(guard) })
+}
+
+/// Promotes the current thread to the realtime scheduling for rendering the
+/// audio buffers of `frames_per_buffer` frames at the `sample_rate`, returning
+/// the handle demoting the thread back.
+///
+/// When the buffer size isn't known, `0` may be passed for
+/// `frames_per_buffer`, in which case 512 frames are assumed.
+///
+/// The thread joins the `"Pro Audio"` task of the Multimedia Class Scheduler
+/// Service, which schedules it according to the system settings of the task.
+/// The buffer only has to be valid, as the task doesn't depend on it.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::audio::promote_current_thread_to_real_time;
+///
+/// let handle = promote_current_thread_to_real_time(256, 44_100).unwrap();
+/// // The thread is demoted when the handle is dropped.
+/// drop(handle);
+/// ```
+#[cfg(windows)]
+pub fn promote_current_thread_to_real_time(
+    frames_per_buffer: u32,
+    sample_rate: u32,
+) -> Result<RealTimeAudioHandle, Error> {
+    buffer_duration(frames_per_buffer, sample_rate)?;
+    Ok(RealTimeAudioHandle {
+        task: Some(crate::MmcssTask::join("Pro Audio")?),
+    })
+}
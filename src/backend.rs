@@ -0,0 +1,211 @@
+//! This module allows to replace the way the crate changes the scheduling of
+//! the threads, for example, to delegate it to a privileged helper process or
+//! to support an in-house operating system.
+//!
+//! The [`ThreadPriority::set_for_current`] method, the threads spawned by the
+//! [`crate::ThreadBuilder`] and the applied [`crate::plan::SchedulePlan`]s go
+//! through the backend registered with [`set_scheduling_backend`], or through
+//! the [`NativeBackend`] if there is none. The platform-specific functions of the crate always use the
+//! operating system directly.
+//!
+//! ```rust
+//! use thread_priority::backend::*;
+//! use thread_priority::*;
+//!
+//! struct LoggingBackend;
+//!
+//! impl SchedulingBackend for LoggingBackend {
+//!     fn thread_native_id(&self) -> ThreadId {
+//!         NativeBackend.thread_native_id()
+//!     }
+//!
+//!     fn set_thread_priority(&self, native: ThreadId, priority: ThreadPriority) -> Result<(), Error> {
+//!         println!("Setting {priority} for {native:?}");
+//!         NativeBackend.set_thread_priority(native, priority)
+//!     }
+//!
+//!     fn get_thread_priority(&self, native: ThreadId) -> Result<ThreadPriority, Error> {
+//!         NativeBackend.get_thread_priority(native)
+//!     }
+//!
+//!     # #[cfg(unix)]
+//!     fn set_thread_priority_and_policy(
+//!         &self,
+//!         native: ThreadId,
+//!         priority: ThreadPriority,
+//!         policy: ThreadSchedulePolicy,
+//!     ) -> Result<(), Error> {
+//!         NativeBackend.set_thread_priority_and_policy(native, priority, policy)
+//!     }
+//!
+//!     # #[cfg(unix)]
+//!     fn thread_schedule_policy(&self, native: ThreadId) -> Result<ThreadSchedulePolicy, Error> {
+//!         NativeBackend.thread_schedule_policy(native)
+//!     }
+//! }
+//!
+//! set_scheduling_backend(LoggingBackend);
+//! assert!(ThreadPriority::Min.set_for_current().is_ok());
+//! reset_scheduling_backend();
+//! ```
+
+use std::sync::{Arc, RwLock};
+
+#[cfg(unix)]
+use crate::ThreadSchedulePolicy;
+use crate::{Error, ThreadId, ThreadPriority};
+
+/// The scheduling operations the crate performs on the threads.
+pub trait SchedulingBackend: Send + Sync {
+    /// Returns the native id of the current thread, see
+    /// [`crate::thread_native_id`].
+    fn thread_native_id(&self) -> ThreadId;
+
+    /// Sets the priority of the thread keeping its policy.
+    fn set_thread_priority(&self, native: ThreadId, priority: ThreadPriority) -> Result<(), Error>;
+
+    /// Returns the priority of the thread, see [`crate::get_thread_priority`].
+    fn get_thread_priority(&self, native: ThreadId) -> Result<ThreadPriority, Error>;
+
+    /// Sets the priority and the policy of the thread, see
+    /// [`crate::set_thread_priority_and_policy`].
+    #[cfg(unix)]
+    fn set_thread_priority_and_policy(
+        &self,
+        native: ThreadId,
+        priority: ThreadPriority,
+        policy: ThreadSchedulePolicy,
+    ) -> Result<(), Error>;
+
+    /// Returns the policy of the thread, see
+    /// [`crate::thread_schedule_policy_param`].
+    #[cfg(unix)]
+    fn thread_schedule_policy(&self, native: ThreadId) -> Result<ThreadSchedulePolicy, Error>;
+
+    /// Tries the policies and the priorities in order until one of them is
+    /// set, see [`crate::set_thread_priority_with_fallbacks`].
+    #[cfg(unix)]
+    fn set_thread_priority_with_fallbacks(
+        &self,
+        native: ThreadId,
+        fallbacks: &[(ThreadSchedulePolicy, ThreadPriority)],
+    ) -> Result<(ThreadSchedulePolicy, ThreadPriority), Error> {
+        let mut result = Err(Error::Priority("No priority to set"));
+        for &(policy, priority) in fallbacks {
+            result = self
+                .set_thread_priority_and_policy(native, priority, policy)
+                .map(|_| (policy, priority));
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Confines the thread to the provided processors, see
+    /// `set_thread_affinity`. Unsupported unless implemented.
+    fn set_thread_affinity(&self, _native: ThreadId, _cores: &[usize]) -> Result<(), Error> {
+        Err(Error::Priority(
+            "The affinity isn't supported by the scheduling backend.",
+        ))
+    }
+
+    /// Returns the processors the thread is confined to, see
+    /// `get_thread_affinity`. Unsupported unless implemented.
+    fn get_thread_affinity(&self, _native: ThreadId) -> Result<Vec<usize>, Error> {
+        Err(Error::Priority(
+            "The affinity isn't supported by the scheduling backend.",
+        ))
+    }
+}
+
+/// The built-in backend using the functions of this crate for the platform.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NativeBackend;
+
+impl SchedulingBackend for NativeBackend {
+    fn thread_native_id(&self) -> ThreadId {
+        crate::thread_native_id()
+    }
+
+    #[cfg(unix)]
+    fn set_thread_priority(&self, native: ThreadId, priority: ThreadPriority) -> Result<(), Error> {
+        let (policy, _) = crate::thread_schedule_policy_param(native)?;
+        crate::set_thread_priority_and_policy(native, priority, policy)
+    }
+
+    #[cfg(not(unix))]
+    fn set_thread_priority(&self, native: ThreadId, priority: ThreadPriority) -> Result<(), Error> {
+        crate::set_thread_priority(native, priority)
+    }
+
+    fn get_thread_priority(&self, native: ThreadId) -> Result<ThreadPriority, Error> {
+        crate::get_thread_priority(native)
+    }
+
+    #[cfg(unix)]
+    fn set_thread_priority_and_policy(
+        &self,
+        native: ThreadId,
+        priority: ThreadPriority,
+        policy: ThreadSchedulePolicy,
+    ) -> Result<(), Error> {
+        crate::set_thread_priority_and_policy(native, priority, policy)
+    }
+
+    #[cfg(unix)]
+    fn thread_schedule_policy(&self, native: ThreadId) -> Result<ThreadSchedulePolicy, Error> {
+        crate::thread_schedule_policy_param(native).map(|(policy, _)| policy)
+    }
+
+    #[cfg(unix)]
+    fn set_thread_priority_with_fallbacks(
+        &self,
+        native: ThreadId,
+        fallbacks: &[(ThreadSchedulePolicy, ThreadPriority)],
+    ) -> Result<(ThreadSchedulePolicy, ThreadPriority), Error> {
+        crate::set_thread_priority_with_fallbacks(native, fallbacks)
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    fn set_thread_affinity(&self, native: ThreadId, cores: &[usize]) -> Result<(), Error> {
+        crate::set_thread_affinity(native, cores)
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    fn get_thread_affinity(&self, native: ThreadId) -> Result<Vec<usize>, Error> {
+        crate::get_thread_affinity(native)
+    }
+}
+
+static BACKEND: RwLock<Option<Arc<dyn SchedulingBackend>>> = RwLock::new(None);
+
+/// Registers the backend used by the crate for all the threads of the process
+/// from now on, replacing the previous one. The previous backend is dropped
+/// once the operations still using it are done.
+pub fn set_scheduling_backend<B: SchedulingBackend + 'static>(backend: B) {
+    *BACKEND.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(backend));
+}
+
+/// Returns to the [`NativeBackend`].
+pub fn reset_scheduling_backend() {
+    *BACKEND.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Returns the registered backend, or the [`NativeBackend`] if there is none.
+pub fn scheduling_backend() -> Arc<dyn SchedulingBackend> {
+    match &*BACKEND.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(backend) => Arc::clone(backend),
+        None => Arc::new(NativeBackend),
+    }
+}
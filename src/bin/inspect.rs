@@ -0,0 +1,150 @@
+//! Prints the threads of a process with their scheduling and, optionally,
+//! applies a profile to them.
+//!
+//! ```text
+//! thread-priority-inspect <pid> [<profile>]
+//! ```
+//!
+//! The profile is a text file with a rule per line in the form of
+//! `<thread name> <policy> <priority>`, where the thread name may be `*` to
+//! match all the threads, the policy is like `other` or `fifo` and the
+//! priority is like `min`, `max`, `57` or `os:10`. The empty lines and the
+//! lines starting with `#` are ignored. The first matching rule is applied.
+//!
+//! ```text
+//! # The audio threads get the realtime scheduling, the rest are lowered.
+//! audio fifo 80
+//! * other min
+//! ```
+
+#[cfg(target_os = "linux")]
+mod inspect {
+    use thread_priority::*;
+
+    /// A rule of the profile.
+    struct Rule {
+        name: String,
+        policy: ThreadSchedulePolicy,
+        priority: ThreadPriority,
+    }
+
+    impl Rule {
+        fn matches(&self, name: &str) -> bool {
+            self.name == "*" || self.name == name
+        }
+    }
+
+    fn parse_profile(profile: &str) -> Result<Vec<Rule>, String> {
+        let mut rules = Vec::new();
+        for (number, line) in profile.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |e: Error| format!("line {}: {}", number + 1, e);
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                (Some(name), Some(policy), Some(priority), None) => rules.push(Rule {
+                    name: name.to_owned(),
+                    policy: policy.parse().map_err(error)?,
+                    priority: priority.parse().map_err(error)?,
+                }),
+                _ => {
+                    return Err(format!(
+                        "line {}: expected `<thread name> <policy> <priority>`",
+                        number + 1
+                    ))
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    fn thread_name(process: Process, id: KernelThreadId) -> String {
+        std::fs::read_to_string(format!("/proc/{}/task/{}/comm", process.pid(), id.0))
+            .map(|name| name.trim_end().to_owned())
+            .unwrap_or_default()
+    }
+
+    fn print_threads(process: Process) -> Result<(), Error> {
+        println!(
+            "{:>8}  {:<16}  {:<8}  {:>8}  {:>4}  AFFINITY",
+            "TID", "NAME", "POLICY", "PRIORITY", "NICE"
+        );
+        for thread in process.threads()? {
+            let niceness = thread
+                .id
+                .niceness()
+                .map_or_else(|_| "?".to_owned(), |niceness| niceness.to_string());
+            let affinity = thread.id.affinity().map_or_else(
+                |_| "?".to_owned(),
+                |cores| {
+                    cores
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                },
+            );
+            println!(
+                "{:>8}  {:<16}  {:<8}  {:>8}  {:>4}  {}",
+                thread.id.0,
+                thread.name.unwrap_or_default(),
+                thread.priority.policy.to_string(),
+                thread.priority.raw,
+                niceness,
+                affinity,
+            );
+        }
+        Ok(())
+    }
+
+    fn apply_profile(process: Process, rules: &[Rule]) -> Result<(), Error> {
+        for id in process.thread_ids()? {
+            let name = thread_name(process, id);
+            if let Some(rule) = rules.iter().find(|rule| rule.matches(&name)) {
+                match id.set_priority_and_policy(rule.priority, rule.policy) {
+                    // The thread has exited.
+                    Ok(()) | Err(Error::OS(libc::ESRCH)) => {}
+                    Err(e) => eprintln!("{} ({}): {}", id.0, name, e),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn run(args: &[String]) -> Result<(), String> {
+        let (pid, profile) = match args {
+            [pid] => (pid, None),
+            [pid, profile] => (pid, Some(profile)),
+            _ => return Err("usage: thread-priority-inspect <pid> [<profile>]".to_owned()),
+        };
+        let pid = pid
+            .parse()
+            .map_err(|_| format!("invalid process id: {}", pid))?;
+        let process = Process::from_pid(pid);
+
+        if let Some(profile) = profile {
+            let profile = std::fs::read_to_string(profile)
+                .map_err(|e| format!("can't read the profile {}: {}", profile, e))?;
+            let rules = parse_profile(&profile)?;
+            apply_profile(process, &rules).map_err(|e| e.to_string())?;
+        }
+        print_threads(process).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = inspect::run(&args) {
+        eprintln!("thread-priority-inspect: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("thread-priority-inspect: only Linux is currently supported");
+    std::process::exit(1);
+}
@@ -0,0 +1,227 @@
+//! This module allows to configure the threads in layers, so that the large
+//! applications can set the defaults once and only override what differs.
+//!
+//! The settings are resolved with the following precedence, from the highest
+//! to the lowest:
+//!
+//! 1. The thread's own [`ThreadBuilder`].
+//! 2. The builder of the pool the thread belongs to, see
+//!    [`crate::pool::ThreadPool`].
+//! 3. The global defaults, see [`set_global_defaults`].
+//!
+//! A setting is taken from the highest layer which has it set. The resolved
+//! configuration remembers the layer each of the settings came from.
+//!
+//! ```rust
+//! use thread_priority::config::{resolve, ConfigLayer, Setting};
+//! use thread_priority::*;
+//!
+//! let pool = ThreadBuilder::default().priority(ThreadPriority::Min);
+//! let thread = ThreadBuilder::default().name("Worker");
+//!
+//! let resolved = resolve(Some(&pool), &thread);
+//! assert_eq!(resolved.origin(Setting::Name), Some(ConfigLayer::Thread));
+//! assert_eq!(resolved.origin(Setting::Priority), Some(ConfigLayer::Pool));
+//! assert_eq!(resolved.origin(Setting::StackSize), None);
+//! ```
+
+use std::sync::Mutex;
+
+use crate::ThreadBuilder;
+
+static GLOBAL_DEFAULTS: Mutex<Option<ThreadBuilder>> = Mutex::new(None);
+
+/// A layer of the configuration hierarchy.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ConfigLayer {
+    /// The global defaults, see [`set_global_defaults`].
+    Global,
+    /// The builder of the pool.
+    Pool,
+    /// The builder of the thread itself.
+    Thread,
+}
+
+/// A setting of the [`ThreadBuilder`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Setting {
+    /// See [`ThreadBuilder::name`].
+    Name,
+    /// See [`ThreadBuilder::stack_size`].
+    StackSize,
+    /// See [`ThreadBuilder::priority`].
+    Priority,
+    /// See [`ThreadBuilder::policy`].
+    #[cfg(unix)]
+    Policy,
+    /// See [`ThreadBuilder::fallback`].
+    #[cfg(unix)]
+    Fallbacks,
+    /// See [`ThreadBuilder::qos_class`].
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos"
+    ))]
+    QosClass,
+    /// See [`ThreadBuilder::boost`].
+    Boost,
+    /// See [`ThreadBuilder::restore_priority`].
+    RestorePriority,
+    /// See [`ThreadBuilder::demote_on_panic`].
+    #[cfg(any(unix, windows))]
+    DemoteOnPanic,
+    /// See [`ThreadBuilder::lock_memory`].
+    #[cfg(any(unix, windows))]
+    LockMemory,
+    /// See [`ThreadBuilder::affinity`]. On Windows, it includes the processor
+    /// group, see `ThreadBuilder::group_affinity`.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    Affinity,
+    /// See [`ThreadBuilder::winapi_priority`].
+    #[cfg(windows)]
+    WinAPIPriority,
+    /// See [`ThreadBuilder::ideal_processor`].
+    #[cfg(windows)]
+    IdealProcessor,
+    /// See [`ThreadBuilder::prefer_numa_node`].
+    #[cfg(windows)]
+    NumaNode,
+    /// See [`ThreadBuilder::mmcss_task`].
+    #[cfg(windows)]
+    MmcssTask,
+}
+
+/// The configuration of a thread resolved from all the layers.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ResolvedConfig {
+    builder: ThreadBuilder,
+    origins: Vec<(Setting, ConfigLayer)>,
+}
+
+impl ResolvedConfig {
+    /// Returns the builder with all the resolved settings, which is used to
+    /// spawn the thread.
+    pub fn builder(&self) -> &ThreadBuilder {
+        &self.builder
+    }
+
+    /// Returns the builder with all the resolved settings.
+    pub fn into_builder(self) -> ThreadBuilder {
+        self.builder
+    }
+
+    /// Returns the layer the setting came from, or `None` if it isn't set in
+    /// any of the layers.
+    pub fn origin(&self, setting: Setting) -> Option<ConfigLayer> {
+        self.origins
+            .iter()
+            .find(|&&(resolved, _)| resolved == setting)
+            .map(|&(_, layer)| layer)
+    }
+
+    /// Returns the settings which are set along with the layers they came
+    /// from.
+    pub fn origins(&self) -> &[(Setting, ConfigLayer)] {
+        &self.origins
+    }
+
+    /// Overrides the settings with the ones set in the builder of the layer.
+    fn apply(&mut self, layer: ConfigLayer, builder: &ThreadBuilder) {
+        macro_rules! apply {
+            ($setting:expr, $field:ident, $is_set:expr) => {
+                if $is_set(&builder.$field) {
+                    self.builder.$field = builder.$field.clone();
+                    self.origins.retain(|&(setting, _)| setting != $setting);
+                    self.origins.push(($setting, layer));
+                }
+            };
+        }
+
+        apply!(Setting::Name, name, Option::is_some);
+        apply!(Setting::StackSize, stack_size, Option::is_some);
+        apply!(Setting::Priority, priority, Option::is_some);
+        #[cfg(unix)]
+        apply!(Setting::Policy, policy, Option::is_some);
+        #[cfg(unix)]
+        apply!(Setting::Fallbacks, fallbacks, |v: &Vec<_>| !v.is_empty());
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos"
+        ))]
+        apply!(Setting::QosClass, qos_class, Option::is_some);
+        apply!(Setting::Boost, boost, Option::is_some);
+        apply!(Setting::RestorePriority, restore_priority, |&v: &bool| v);
+        #[cfg(any(unix, windows))]
+        apply!(Setting::DemoteOnPanic, demote_on_panic, |&v: &bool| v);
+        #[cfg(any(unix, windows))]
+        apply!(Setting::LockMemory, lock_memory, |&v: &bool| v);
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "freebsd",
+            windows
+        ))]
+        apply!(Setting::Affinity, affinity, Option::is_some);
+        // The group is only meaningful along with the processors.
+        #[cfg(windows)]
+        if builder.affinity.is_some() {
+            self.builder.affinity_group = builder.affinity_group;
+        }
+        #[cfg(windows)]
+        apply!(Setting::WinAPIPriority, winapi_priority, Option::is_some);
+        #[cfg(windows)]
+        apply!(Setting::IdealProcessor, ideal_processor, Option::is_some);
+        #[cfg(windows)]
+        apply!(Setting::NumaNode, numa_node, Option::is_some);
+        #[cfg(windows)]
+        apply!(Setting::MmcssTask, mmcss_task, Option::is_some);
+    }
+}
+
+/// Sets the global defaults, which apply to the threads spawned by the pools
+/// and to the configurations resolved by [`resolve`]. Passing
+/// `ThreadBuilder::default()` clears them.
+///
+/// The threads already running aren't affected.
+pub fn set_global_defaults(builder: ThreadBuilder) {
+    *GLOBAL_DEFAULTS.lock().unwrap_or_else(|e| e.into_inner()) = Some(builder);
+}
+
+/// Returns the global defaults, which are `ThreadBuilder::default()` unless
+/// set by [`set_global_defaults`].
+pub fn global_defaults() -> ThreadBuilder {
+    GLOBAL_DEFAULTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Resolves the configuration of a thread from the global defaults, the
+/// builder of its pool, if any, and its own builder.
+pub fn resolve(pool: Option<&ThreadBuilder>, thread: &ThreadBuilder) -> ResolvedConfig {
+    let mut resolved = ResolvedConfig::default();
+    resolved.apply(ConfigLayer::Global, &global_defaults());
+    if let Some(pool) = pool {
+        resolved.apply(ConfigLayer::Pool, pool);
+    }
+    resolved.apply(ConfigLayer::Thread, thread);
+    resolved
+}
+
+/// Returns the base builder with the settings set in the overrides replaced.
+pub(crate) fn merge(base: &ThreadBuilder, overrides: &ThreadBuilder) -> ThreadBuilder {
+    let mut resolved = ResolvedConfig::default();
+    resolved.apply(ConfigLayer::Thread, base);
+    resolved.apply(ConfigLayer::Thread, overrides);
+    resolved.into_builder()
+}
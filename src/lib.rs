@@ -149,29 +149,34 @@
     target_os = "linux",
     target_os = "macos",
     target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
     target_os = "dragonfly",
     target_os = "freebsd",
     target_os = "openbsd",
     target_os = "vxworks",
     target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris",
     target_os = "android",
-    target_arch = "wasm32",
 ))]
 pub mod unix;
-#[cfg(any(target_os = "linux", target_os = "android"))]
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[cfg(any(
     target_os = "linux",
     target_os = "macos",
     target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
     target_os = "dragonfly",
     target_os = "freebsd",
     target_os = "openbsd",
     target_os = "vxworks",
     target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris",
     target_os = "android",
-    target_arch = "wasm32",
 ))]
 pub use unix::*;
 
@@ -180,6 +185,22 @@ pub mod windows;
 #[cfg(windows)]
 pub use windows::*;
 
+#[cfg(not(any(unix, windows)))]
+pub mod unsupported;
+#[cfg(not(any(unix, windows)))]
+pub use unsupported::*;
+
+#[cfg(any(unix, windows))]
+pub mod audio;
+pub mod backend;
+pub mod config;
+pub mod managed;
+pub mod plan;
+pub mod pool;
+pub mod testing;
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+pub mod topology;
+
 /// A error type
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Error {
@@ -193,9 +214,18 @@ pub enum Error {
     /// later should be used with target OS' API for understanding the value.
     /// On Linux there is an integer containing an error code from errno.
     /// For Windows it contains a number used in Windows for the same purpose.
+    /// The failed `HRESULT`s are converted into the Win32 error codes they wrap,
+    /// while the ones of the other facilities are kept as they are.
     OS(i32),
     /// FFI failure.
     Ffi(&'static str),
+    /// Indicates that some of the requested CPUs can't be used by the process,
+    /// for example, due to a container restricting the cores available. Contains
+    /// the CPUs the process is allowed to use.
+    CpusNotAllowed(Vec<usize>),
+    /// Indicates that the platform doesn't support the thread scheduling at
+    /// all, for example, `wasm32-unknown-unknown`.
+    UnsupportedPlatform,
 }
 
 impl std::fmt::Display for Error {
@@ -207,12 +237,195 @@ impl std::fmt::Display for Error {
             }
             Error::OS(i) => write!(f, "the operating system returned error code {}", i),
             Error::Ffi(s) => write!(f, "FFI error: {}", s),
+            Error::CpusNotAllowed(cpus) => {
+                write!(f, "CPUs must be within the allowed ones: {:?}", cpus)
+            }
+            Error::UnsupportedPlatform => {
+                write!(f, "the platform doesn't support the thread scheduling")
+            }
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+/// A platform-independent classification of the [`Error`]s.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ErrorKind {
+    /// The operation requires privileges the process doesn't have, for example,
+    /// `CAP_SYS_NICE` on Linux.
+    PermissionDenied,
+    /// The operation was provided with the values the operating system rejects.
+    InvalidParameter,
+    /// The operation isn't supported by the platform or the kernel.
+    UnsupportedOnPlatform,
+    /// The thread the operation was performed on doesn't exist anymore.
+    ThreadGone,
+    /// Any other failure.
+    Other,
+}
+
+impl Error {
+    /// Classifies the error, so that the failures can be handled without
+    /// matching the raw error codes of each platform.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// assert_eq!(
+    ///     Error::PriorityNotInRange(0..=0).kind(),
+    ///     ErrorKind::InvalidParameter
+    /// );
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Priority(_) | Error::PriorityNotInRange(_) | Error::CpusNotAllowed(_) => {
+                ErrorKind::InvalidParameter
+            }
+            Error::OS(code) => os_error_kind(*code),
+            Error::Ffi(_) => ErrorKind::Other,
+            Error::UnsupportedPlatform => ErrorKind::UnsupportedOnPlatform,
+        }
+    }
+
+    /// Returns the raw error code of the operating system, if the error has one.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            Error::OS(code) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn os_error_kind(code: i32) -> ErrorKind {
+    match code {
+        libc::EPERM | libc::EACCES => ErrorKind::PermissionDenied,
+        libc::EINVAL | libc::ERANGE => ErrorKind::InvalidParameter,
+        libc::ENOSYS | libc::EOPNOTSUPP => ErrorKind::UnsupportedOnPlatform,
+        libc::ESRCH => ErrorKind::ThreadGone,
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(windows)]
+fn os_error_kind(code: i32) -> ErrorKind {
+    use winapi::shared::winerror;
+
+    match code as u32 {
+        winerror::ERROR_ACCESS_DENIED | winerror::ERROR_PRIVILEGE_NOT_HELD => {
+            ErrorKind::PermissionDenied
+        }
+        winerror::ERROR_INVALID_PARAMETER | winerror::ERROR_INVALID_HANDLE => {
+            ErrorKind::InvalidParameter
+        }
+        winerror::ERROR_NOT_SUPPORTED | winerror::ERROR_CALL_NOT_IMPLEMENTED => {
+            ErrorKind::UnsupportedOnPlatform
+        }
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_error_kind(_code: i32) -> ErrorKind {
+    ErrorKind::Other
+}
+
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::OS(code) => std::io::Error::from_raw_os_error(code),
+            Error::Priority(_) | Error::PriorityNotInRange(_) | Error::CpusNotAllowed(_) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, error)
+            }
+            Error::Ffi(_) => std::io::Error::new(std::io::ErrorKind::Other, error),
+            Error::UnsupportedPlatform => {
+                std::io::Error::new(std::io::ErrorKind::Unsupported, error)
+            }
+        }
+    }
+}
+
+/// The scheduling features of the platform available to the process, see
+/// [`capabilities`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Capabilities {
+    /// The priorities of the threads can be changed.
+    pub priority: bool,
+    /// The threads can be switched to a realtime priority, see
+    /// [`can_set_realtime_priority`].
+    pub realtime_priority: bool,
+    /// The scheduling policy of the threads can be chosen.
+    pub policy: bool,
+    /// The threads can be confined to a set of processors.
+    pub affinity: bool,
+}
+
+/// Reports the scheduling features available to the process, so that the
+/// platforms without the thread scheduling, like `wasm32-unknown-unknown`,
+/// can be handled without trying the operations first. All the features are
+/// reported as unavailable on such platforms.
+///
+/// The backend registered with [`backend::set_scheduling_backend`] isn't
+/// taken into account.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// if capabilities().priority {
+///     assert!(set_current_thread_priority(ThreadPriority::Min).is_ok());
+/// }
+/// ```
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        priority: cfg!(any(unix, windows)),
+        realtime_priority: can_set_realtime_priority().unwrap_or(false),
+        policy: cfg!(unix),
+        affinity: cfg!(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "freebsd",
+            windows
+        )),
+    }
+}
+
+/// A promotion of the current thread's scheduling, which restores the
+/// scheduling captured at the promotion time when demoted or dropped.
+///
+/// The promotions are bound to the thread which made them, like
+/// [`PriorityGuard`], so that the long-lived threads don't keep the elevated
+/// scheduling by accident once the work needing it is done.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let promotion = PriorityGuard::save().unwrap();
+/// // The work changing the scheduling.
+/// assert!(demote(promotion).is_ok());
+/// ```
+pub trait Promotion {
+    /// Restores the scheduling captured at the promotion time, returning the
+    /// result of doing so. Dropping the promotion does the same, but only
+    /// logs the errors.
+    fn demote(self) -> Result<(), Error>;
+}
+
+impl Promotion for PriorityGuard {
+    fn demote(self) -> Result<(), Error> {
+        self.restore()
+    }
+}
+
+/// Restores the scheduling captured by the promotion, see [`Promotion`].
+pub fn demote<P: Promotion>(promotion: P) -> Result<(), Error> {
+    promotion.demote()
+}
+
 /// Platform-independent thread priority value.
 /// Should be in `[0; 100)` range. The higher the number is - the higher
 /// the priority.
@@ -243,6 +456,11 @@ impl std::error::Error for Error {}
 /// assert_eq!(raw_value, 0);
 /// ```
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "u8", into = "u8")
+)]
 pub struct ThreadPriorityValue(u8);
 impl ThreadPriorityValue {
     /// The maximum value for a thread priority.
@@ -278,10 +496,45 @@ impl std::convert::Into<u8> for ThreadPriorityValue {
 
 /// Platform-specific thread priority value.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThreadPriorityOsValue(u32);
 
+/// A priority given as a fraction of the range of priorities allowed for the
+/// scheduling policy, where `0.0` stands for the lowest priority and `1.0` for
+/// the highest one. The fraction is stored with the precision of `0.01%`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThreadPriorityFraction(u16);
+
+impl ThreadPriorityFraction {
+    /// The number of the steps the fraction is stored with.
+    const SCALE: u16 = 10_000;
+
+    /// Creates the fraction, which must be within `[0; 1]`.
+    pub fn new(fraction: f64) -> Result<Self, Error> {
+        if (0f64..=1f64).contains(&fraction) {
+            Ok(Self((fraction * Self::SCALE as f64).round() as u16))
+        } else {
+            Err(Error::Priority("The fraction must be within [0; 1]."))
+        }
+    }
+
+    /// Returns the fraction as a number within `[0; 1]`.
+    pub fn value(self) -> f64 {
+        self.0 as f64 / Self::SCALE as f64
+    }
+
+    /// Returns the value at the fraction of the way from the lowest to the
+    /// highest value, rounded to the nearest one.
+    #[cfg_attr(not(any(unix, windows)), allow(dead_code))]
+    fn interpolate(self, lowest: i32, highest: i32) -> i32 {
+        lowest + ((highest - lowest) as f64 * self.value()).round() as i32
+    }
+}
+
 /// Thread priority enumeration.
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThreadPriority {
     /// Holds a value representing the minimum possible priority.
     #[cfg_attr(
@@ -309,6 +562,10 @@ variant.
 "
     )]
     Os(ThreadPriorityOsValue),
+    /// Holds a fraction of the range of priorities allowed for the scheduling
+    /// policy, which is mapped onto that range when the priority is set, see
+    /// [`ThreadPriority::from_fraction`].
+    Fraction(ThreadPriorityFraction),
     /// Holds scheduling parameters for Deadline scheduling. These are, in order,
     /// the nanoseconds for runtime, deadline, and period. Please note that the
     /// kernel enforces runtime <= deadline <= period.
@@ -321,6 +578,9 @@ variant.
     ///                 |<-- Runtime ------->|
     ///        |<----------- Deadline ----------->|
     ///        |<-------------- Period ------------------->|
+    ///
+    /// For the validated and extensible form of these parameters, see
+    /// [`crate::unix::DeadlineParameters`].
     #[cfg(any(target_os = "linux", target_os = "android"))]
     Deadline {
         /// Set this to something larger than the average computation time
@@ -351,8 +611,193 @@ to set it to [`WinAPIThreadPriority::TimeCritical`] when it is really needed.
 
 impl ThreadPriority {
     /// Sets current thread's priority to this value.
+    ///
+    /// This goes through the registered scheduling backend, see
+    /// [`crate::backend`].
     pub fn set_for_current(self) -> Result<(), Error> {
-        set_current_thread_priority(self)
+        let backend = backend::scheduling_backend();
+        backend.set_thread_priority(backend.thread_native_id(), self)
+    }
+
+    /// Creates the priority from a fraction within `[0; 1]` of the range of
+    /// priorities allowed for the scheduling policy, which is chosen only when
+    /// the priority is set. This allows to write the tuning code which doesn't
+    /// depend on the policy or the platform.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// let priority = ThreadPriority::from_fraction(0.25).unwrap();
+    /// assert_eq!(priority.to_string(), "25%");
+    /// assert!(ThreadPriority::from_fraction(1.5).is_err());
+    /// ```
+    pub fn from_fraction(fraction: f64) -> Result<Self, Error> {
+        ThreadPriorityFraction::new(fraction).map(ThreadPriority::Fraction)
+    }
+}
+
+/// Formats the priority in the form accepted by its [`std::str::FromStr`]
+/// implementation: `min`, `max`, a crossplatform value like `57`, an OS-specific
+/// value like `os:57`, a fraction of the allowed range like `25%` or, on Linux
+/// and Android, the deadline parameters like
+/// `deadline:10ms/20ms/100ms`, optionally followed by the flags, like
+/// `deadline:10ms/20ms/100ms/RESET_ON_FORK | RECLAIM`.
+impl std::fmt::Display for ThreadPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThreadPriority::Min => write!(f, "min"),
+            ThreadPriority::Crossplatform(ThreadPriorityValue(p)) => write!(f, "{}", p),
+            ThreadPriority::Os(ThreadPriorityOsValue(p)) => write!(f, "os:{}", p),
+            // Formatted from the stored steps, as multiplying the fraction by
+            // a hundred prints `0.07` as `7.000000000000001%`.
+            ThreadPriority::Fraction(ThreadPriorityFraction(steps)) => {
+                write!(f, "{}%", *steps as f64 / 100f64)
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ThreadPriority::Deadline {
+                runtime,
+                deadline,
+                period,
+                flags,
+            } => {
+                write!(
+                    f,
+                    "deadline:{}/{}/{}",
+                    DisplayDuration(*runtime),
+                    DisplayDuration(*deadline),
+                    DisplayDuration(*period),
+                )?;
+                if !flags.is_empty() {
+                    write!(f, "/")?;
+                    bitflags::parser::to_writer(flags, &mut *f)?;
+                }
+                Ok(())
+            }
+            ThreadPriority::Max => write!(f, "max"),
+        }
+    }
+}
+
+/// Parses the priority from the form produced by its [`std::fmt::Display`]
+/// implementation.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert_eq!("min".parse(), Ok(ThreadPriority::Min));
+/// assert_eq!(
+///     "57".parse(),
+///     Ok(ThreadPriority::Crossplatform(57u8.try_into().unwrap()))
+/// );
+/// assert!("100".parse::<ThreadPriority>().is_err());
+/// ```
+impl std::str::FromStr for ThreadPriority {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("min") {
+            return Ok(ThreadPriority::Min);
+        }
+        if s.eq_ignore_ascii_case("max") {
+            return Ok(ThreadPriority::Max);
+        }
+        if let Some(percentage) = s.strip_suffix('%') {
+            let percentage: f64 = percentage
+                .trim()
+                .parse()
+                .map_err(|_| Error::Priority("The percentage must be a number."))?;
+            return ThreadPriority::from_fraction(percentage / 100f64);
+        }
+        if let Some(value) = s.strip_prefix("os:") {
+            let value: u32 = value
+                .trim()
+                .parse()
+                .map_err(|_| Error::Priority("The OS-specific priority must be a number."))?;
+            #[cfg(windows)]
+            return ThreadPriorityOsValue::try_from(value)
+                .map(ThreadPriority::Os)
+                .map_err(|_| Error::Priority("The OS-specific priority isn't a valid one."));
+            #[cfg(not(windows))]
+            return Ok(ThreadPriority::Os(ThreadPriorityOsValue(value)));
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if let Some(parameters) = s.strip_prefix("deadline:") {
+            let mut parts = parameters.splitn(4, '/');
+            let mut next_duration = || {
+                parts
+                    .next()
+                    .ok_or(Error::Priority(
+                        "The deadline priority must have the runtime, deadline and period.",
+                    ))
+                    .and_then(parse_duration)
+            };
+            let (runtime, deadline, period) =
+                (next_duration()?, next_duration()?, next_duration()?);
+            let flags = match parts.next() {
+                Some(flags) => bitflags::parser::from_str(flags)
+                    .map_err(|_| Error::Priority("The deadline flags couldn't be parsed."))?,
+                None => crate::unix::DeadlineFlags::empty(),
+            };
+            return Ok(ThreadPriority::Deadline {
+                runtime,
+                deadline,
+                period,
+                flags,
+            });
+        }
+        let value: u8 = s
+            .parse()
+            .map_err(|_| Error::Priority("The priority couldn't be parsed."))?;
+        ThreadPriorityValue::try_from(value)
+            .map(ThreadPriority::Crossplatform)
+            .map_err(|_| {
+                Error::PriorityNotInRange(
+                    ThreadPriorityValue::MIN as i32..=ThreadPriorityValue::MAX as i32,
+                )
+            })
+    }
+}
+
+/// Formats the duration with the largest of the units accepted by
+/// [`parse_duration`] representing it exactly.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+struct DisplayDuration(Duration);
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl std::fmt::Display for DisplayDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nanos = self.0.as_nanos();
+        for (unit, unit_nanos) in [("s", 1_000_000_000), ("ms", 1_000_000), ("us", 1_000)] {
+            if nanos % unit_nanos == 0 && nanos != 0 {
+                return write!(f, "{}{}", nanos / unit_nanos, unit);
+            }
+        }
+        write!(f, "{}ns", nanos)
+    }
+}
+
+/// Parses a duration consisting of an integer and one of the `s`, `ms`, `us` or
+/// `ns` units.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_duration(s: &str) -> Result<Duration, Error> {
+    let s = s.trim();
+    let (value, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let value: u64 = value
+        .parse()
+        .map_err(|_| Error::Priority("The duration couldn't be parsed."))?;
+    match unit {
+        "s" => Ok(Duration::from_secs(value)),
+        "ms" => Ok(Duration::from_millis(value)),
+        "us" => Ok(Duration::from_micros(value)),
+        "ns" => Ok(Duration::from_nanos(value)),
+        _ => Err(Error::Priority(
+            "The duration must have one of the units: s, ms, us, ns.",
+        )),
     }
 }
 
@@ -465,6 +910,11 @@ where
 /// assert_eq!(x, 2);
 /// ```
 #[derive(Clone, Debug, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ThreadBuilder {
     name: Option<String>,
     stack_size: Option<usize>,
@@ -472,19 +922,140 @@ pub struct ThreadBuilder {
 
     #[cfg(unix)]
     policy: Option<ThreadSchedulePolicy>,
+    #[cfg(unix)]
+    fallbacks: Vec<(ThreadSchedulePolicy, ThreadPriority)>,
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos"
+    ))]
+    qos_class: Option<QosClass>,
+
+    boost: Option<bool>,
+
+    restore_priority: bool,
+    #[cfg(any(unix, windows))]
+    demote_on_panic: bool,
+    #[cfg(any(unix, windows))]
+    lock_memory: bool,
 
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    affinity: Option<Vec<usize>>,
     #[cfg(windows)]
-    winapi_priority: Option<WinAPIThreadPriority>,
+    affinity_group: Option<u16>,
+
     #[cfg(windows)]
-    boost_enabled: bool,
+    winapi_priority: Option<WinAPIThreadPriority>,
     #[cfg(windows)]
     ideal_processor: Option<IdealProcessor>,
+    #[cfg(windows)]
+    numa_node: Option<u16>,
+    #[cfg(windows)]
+    mmcss_task: Option<String>,
+}
+
+#[cfg(any(unix, windows))]
+thread_local! {
+    /// Whether the current thread is demoted when it panics, see
+    /// [`ThreadBuilder::demote_on_panic`].
+    static DEMOTE_ON_PANIC: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Demotes the current thread from the realtime scheduling for the rest of its
+/// life, see [`ThreadBuilder::demote_on_panic`].
+#[cfg(any(unix, windows))]
+fn demote_panicking_thread() {
+    match RealtimeRelinquishGuard::relinquish() {
+        // The guard would restore the realtime scheduling when dropped.
+        Ok(guard) => std::mem::forget(guard),
+        Err(e) => log::warn!(
+            "Couldn't demote the panicking thread named {:?}: {:?}",
+            std::thread::current().name(),
+            e,
+        ),
+    }
+}
+
+/// Installs the panic hook demoting the threads spawned with
+/// [`ThreadBuilder::demote_on_panic`] before the previous hook is called and
+/// the unwinding starts.
+#[cfg(any(unix, windows))]
+fn install_panic_demotion_hook() {
+    static INSTALLED: std::sync::Once = std::sync::Once::new();
+    INSTALLED.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if DEMOTE_ON_PANIC.with(|demote| demote.get()) {
+                demote_panicking_thread();
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Demotes the current thread from the realtime scheduling when dropped during
+/// a panic, in case the panic hook demoting it was replaced after it had been
+/// installed.
+#[cfg(any(unix, windows))]
+struct PanicDemotion;
+
+#[cfg(any(unix, windows))]
+impl Drop for PanicDemotion {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            demote_panicking_thread();
+        }
+    }
+}
+
+/// A handle of a thread spawned by [`ThreadBuilder::spawn_wait`], which has
+/// applied its configuration and therefore runs the thread function.
+#[derive(Debug)]
+pub struct ConfiguredJoinHandle<T>(std::thread::JoinHandle<Option<T>>);
+
+impl<T> ConfiguredJoinHandle<T> {
+    /// Returns the thread, like [`std::thread::JoinHandle::thread`].
+    pub fn thread(&self) -> &std::thread::Thread {
+        self.0.thread()
+    }
+
+    /// Checks whether the thread has finished, like
+    /// [`std::thread::JoinHandle::is_finished`].
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+
+    /// Waits for the thread to finish, like [`std::thread::JoinHandle::join`].
+    pub fn join(self) -> std::thread::Result<T> {
+        // The threads which haven't applied the configuration are joined by
+        // `spawn_wait` and never get a handle.
+        self.0.join().map(|result| {
+            result.expect("The thread function is run once the thread is configured.")
+        })
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl<T> JoinHandleExt for ConfiguredJoinHandle<T> {
+    fn get_native_id(&self) -> ThreadId {
+        self.0.get_native_id()
+    }
 }
 
 impl ThreadBuilder {
     /// Names the thread-to-be. Currently the name is used for identification
     /// only in panic messages.
     ///
+    /// On Windows, the name is also set as the thread description, so that it
+    /// shows up in debuggers and traces, see `set_thread_description`. Failing
+    /// to set the description doesn't fail the thread configuration.
+    ///
     /// The name must not contain null bytes (`\0`).
     ///
     /// For more information about named threads, see
@@ -525,6 +1096,46 @@ impl ThreadBuilder {
         self
     }
 
+    /// Adds a policy and priority to fall back to when the ones set previously
+    /// couldn't be applied, for example, due to the lack of privileges. The
+    /// fallbacks are tried in the order they were added.
+    ///
+    /// For more information, see
+    /// [`crate::unix::set_thread_priority_with_fallbacks`].
+    #[cfg(unix)]
+    pub fn fallback<POLICY: Into<unix::ThreadSchedulePolicy>, PRIORITY: Into<ThreadPriority>>(
+        mut self,
+        policy: POLICY,
+        priority: PRIORITY,
+    ) -> Self {
+        self.fallbacks.push((policy.into(), priority.into()));
+        self
+    }
+
+    /// The thread's QoS class on the Apple platforms, which is set before
+    /// anything else is configured and before the thread function is run.
+    ///
+    /// The standard library doesn't expose the attributes the threads are
+    /// created with, so the class is set by the spawned thread itself. See
+    /// [`crate::unix::attr::set_qos_class`] for the threads created with
+    /// `pthread_create` directly, which get the class from the very first
+    /// instruction.
+    ///
+    /// Setting the priority as well opts the thread out of the QoS, as the
+    /// POSIX priority takes precedence over the class.
+    ///
+    /// For more information, see [`crate::unix::set_current_thread_qos_class`].
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos"
+    ))]
+    pub fn qos_class(mut self, value: QosClass) -> Self {
+        self.qos_class = Some(value);
+        self
+    }
+
     /// The WinAPI priority representation.
     ///
     /// For more information, see
@@ -543,11 +1154,48 @@ impl ThreadBuilder {
     /// For more information, see
     /// [`crate::windows::set_thread_priority_boost`].
     #[cfg(windows)]
-    pub fn boost_enabled(mut self, value: bool) -> Self {
-        self.boost_enabled = value;
+    pub fn boost_enabled(self, value: bool) -> Self {
+        self.boost(value)
+    }
+
+    /// Disables or enables the ability of the system to temporarily boost the priority of a thread.
+    ///
+    /// This is a cross-platform part of the configuration: it is mapped to
+    /// `SetThreadPriorityBoost` on Windows and ignored on other platforms, which
+    /// don't boost the thread priorities this way.
+    ///
+    /// When not set, the boost of the thread is left as it is. See
+    /// `ThreadBuilder::realtime_preset` for disabling it along with the
+    /// realtime-style priorities.
+    pub fn boost(mut self, value: bool) -> Self {
+        self.boost = Some(value);
         self
     }
 
+    /// Sets the realtime-style priority, like [`WinAPIThreadPriority::Highest`]
+    /// or [`WinAPIThreadPriority::TimeCritical`], and disables the priority
+    /// boost, so that the scheduling of the thread stays predictable. This is
+    /// the same as calling [`ThreadBuilder::winapi_priority`] and
+    /// [`ThreadBuilder::boost`], and can be combined with
+    /// [`ThreadBuilder::mmcss_task`] to join a MMCSS task too.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// let thread = ThreadBuilder::default()
+    ///     .realtime_preset(WinAPIThreadPriority::TimeCritical)
+    ///     .mmcss_task("Pro Audio")
+    ///     .spawn(|result| result)
+    ///     .unwrap();
+    /// assert!(thread.join().unwrap().is_ok());
+    /// ```
+    #[cfg(windows)]
+    pub fn realtime_preset(self, priority: WinAPIThreadPriority) -> Self {
+        self.winapi_priority(priority).boost(false)
+    }
+
     /// Sets a preferred processor for a thread. The system schedules threads on their preferred
     /// processors whenever possible.
     ///
@@ -559,6 +1207,105 @@ impl ThreadBuilder {
         self
     }
 
+    /// Sets a preferred processor for a thread within the NUMA node, instead of
+    /// a processor number hard-coded for the machine.
+    ///
+    /// For more information, see
+    /// [`crate::windows::set_thread_ideal_numa_node`].
+    #[cfg(windows)]
+    pub fn prefer_numa_node(mut self, node: u16) -> Self {
+        self.numa_node = Some(node);
+        self
+    }
+
+    /// Makes the workers of a [`crate::pool::ThreadPool`] restore the
+    /// scheduling they were configured with after each job, whether the job
+    /// has returned or panicked, so that the priority changed by a job doesn't
+    /// leak into the unrelated jobs run by the same worker later.
+    ///
+    /// The threads spawned directly run a single thread function, so they
+    /// aren't affected: wrap the work reusing such a thread with a
+    /// [`crate::PriorityGuard`] instead.
+    pub fn restore_priority(mut self, value: bool) -> Self {
+        self.restore_priority = value;
+        self
+    }
+
+    /// Demotes the thread from the realtime scheduling when it panics, before
+    /// the panic hook runs and the unwinding starts.
+    ///
+    /// A panicking thread running the panic handlers or the destructors at a
+    /// realtime priority may freeze the processor it runs on. The thread is
+    /// switched to `SCHED_OTHER` on Unix and lowered to the normal priority on
+    /// Windows, the same way as done by [`crate::RealtimeRelinquishGuard`],
+    /// but for the rest of its life.
+    ///
+    /// The demotion is done by a panic hook installed on top of the previous
+    /// one when the first such thread is spawned, so the panics caught within
+    /// the thread demote it too. If the hook is replaced later, the thread is
+    /// only demoted once the unwinding reaches the end of the thread function.
+    #[cfg(any(unix, windows))]
+    pub fn demote_on_panic(mut self, value: bool) -> Self {
+        self.demote_on_panic = value;
+        self
+    }
+
+    /// Locks the memory before the priority is set, so that the page faults
+    /// don't defeat the realtime scheduling of the thread.
+    ///
+    /// On Unix, all the memory of the process is locked, including the future
+    /// allocations, while on Windows only the stack of the thread is. For more
+    /// information, see `lock_memory`.
+    #[cfg(any(unix, windows))]
+    pub fn lock_memory(mut self, value: bool) -> Self {
+        self.lock_memory = value;
+        self
+    }
+
+    /// Confines the thread to the provided processors before the thread function
+    /// is run.
+    ///
+    /// On Windows, the processors are within the processor group the thread is
+    /// spawned in; see `ThreadBuilder::group_affinity` to choose another group.
+    ///
+    /// For more information, see [`crate::set_thread_affinity`].
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    pub fn affinity(mut self, cores: &[usize]) -> Self {
+        self.affinity = Some(cores.to_vec());
+        #[cfg(windows)]
+        {
+            self.affinity_group = None;
+        }
+        self
+    }
+
+    /// Confines the thread to the provided processors of the processor group
+    /// before the thread function is run.
+    ///
+    /// For more information, see
+    /// [`crate::windows::set_thread_group_affinity`].
+    #[cfg(windows)]
+    pub fn group_affinity(mut self, group: u16, cores: &[usize]) -> Self {
+        self.affinity = Some(cores.to_vec());
+        self.affinity_group = Some(group);
+        self
+    }
+
+    /// Makes the thread join the Multimedia Class Scheduler Service task, like
+    /// `"Pro Audio"`, for the duration of the thread function.
+    ///
+    /// For more information, see [`crate::windows::MmcssTask`].
+    #[cfg(windows)]
+    pub fn mmcss_task<VALUE: Into<String>>(mut self, value: VALUE) -> Self {
+        self.mmcss_task = Some(value.into());
+        self
+    }
+
     #[cfg(unix)]
     fn spawn_wrapper<F, T>(self, f: F) -> impl FnOnce() -> T
     where
@@ -566,17 +1313,31 @@ impl ThreadBuilder {
         F: Send,
         T: Send,
     {
-        move || match (self.priority, self.policy) {
-            (Some(priority), Some(policy)) => f(set_thread_priority_and_policy(
-                thread_native_id(),
-                priority,
-                policy,
-            )),
-            (Some(priority), None) => f(priority.set_for_current()),
-            (None, Some(_policy)) => {
-                unimplemented!("Setting the policy separately isn't currently supported.");
+        move || {
+            let _demotion = self.demote_on_panic.then(|| {
+                install_panic_demotion_hook();
+                DEMOTE_ON_PANIC.with(|demote| demote.set(true));
+                PanicDemotion
+            });
+
+            #[cfg(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "tvos",
+                target_os = "watchos"
+            ))]
+            if let Some(class) = self.qos_class {
+                if let Err(e) = set_current_thread_qos_class(class, 0) {
+                    return f(Err(e));
+                }
+            }
+            if self.lock_memory {
+                if let Err(e) = lock_memory() {
+                    return f(Err(e));
+                }
             }
-            _ => f(Ok(())),
+            let native = backend::scheduling_backend().thread_native_id();
+            f(self.plan_for(native).and_then(|plan| plan.apply()))
         }
     }
 
@@ -588,23 +1349,43 @@ impl ThreadBuilder {
         T: Send,
     {
         move || {
-            let mut result = match (self.priority, self.winapi_priority) {
-                (Some(priority), None) => set_thread_priority(thread_native_id(), priority),
-                (_, Some(priority)) => set_winapi_thread_priority(thread_native_id(), priority),
-                _ => Ok(()),
-            };
-            if result.is_ok() && self.boost_enabled {
-                result = set_current_thread_priority_boost(self.boost_enabled);
+            let _demotion = self.demote_on_panic.then(|| {
+                install_panic_demotion_hook();
+                DEMOTE_ON_PANIC.with(|demote| demote.set(true));
+                PanicDemotion
+            });
+
+            let mut result = Ok(());
+            if self.lock_memory {
+                result = lock_memory();
             }
             if result.is_ok() {
-                if let Some(ideal_processor) = self.ideal_processor {
-                    result = set_current_thread_ideal_processor(ideal_processor).map(|_| ());
+                let native = backend::scheduling_backend().thread_native_id();
+                result = self.plan_for(native).and_then(|plan| plan.apply());
+            }
+            let mut _mmcss_task = None;
+            if result.is_ok() {
+                if let Some(task) = &self.mmcss_task {
+                    result = MmcssTask::join(task).map(|task| _mmcss_task = Some(task));
                 }
             }
             f(result)
         }
     }
 
+    #[cfg(not(any(unix, windows)))]
+    fn spawn_wrapper<F, T>(self, f: F) -> impl FnOnce() -> T
+    where
+        F: FnOnce(Result<(), Error>) -> T,
+        F: Send,
+        T: Send,
+    {
+        move || {
+            let native = backend::scheduling_backend().thread_native_id();
+            f(self.plan_for(native).and_then(|plan| plan.apply()))
+        }
+    }
+
     /// Spawns a new thread by taking ownership of the `Builder`, and returns an
     /// [`std::io::Result`] to its [`std::thread::JoinHandle`].
     ///
@@ -618,6 +1399,54 @@ impl ThreadBuilder {
         self.build_std().spawn(self.spawn_wrapper(f))
     }
 
+    /// Spawns a new thread by taking ownership of the `Builder` and waits until the
+    /// thread has applied the configuration, so that the failure to do so is
+    /// returned from this call instead of being passed to the thread function.
+    ///
+    /// If the configuration couldn't be applied, the thread function isn't run and
+    /// the spawned thread is joined before returning the error. Hence, the
+    /// returned thread always runs the function.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// let thread = ThreadBuilder::default()
+    ///     .name("MyThread")
+    ///     .priority(ThreadPriority::Min)
+    ///     .spawn_wait(|| {
+    ///         // This is run only if the priority was set successfully.
+    ///         println!("Running with the minimal priority.");
+    ///     })
+    ///     .unwrap();
+    /// thread.join().unwrap();
+    /// ```
+    pub fn spawn_wait<F, T>(self, f: F) -> std::io::Result<ConfiguredJoinHandle<T>>
+    where
+        F: FnOnce() -> T,
+        F: Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+        let handle = self.spawn(move |result| {
+            let failed = result.is_err();
+            let _ = sender.send(result);
+            // Returning instead of unwinding works with `panic = "abort"` too.
+            (!failed).then(f)
+        })?;
+
+        match receiver.recv() {
+            Ok(Err(e)) => {
+                let _ = handle.join();
+                Err(e.into())
+            }
+            // The thread has either applied the configuration or panicked while
+            // doing so, which is then seen when joining it.
+            _ => Ok(ConfiguredJoinHandle(handle)),
+        }
+    }
+
     /// Spawns a new scoped thread by taking ownership of the `Builder`, and returns an
     /// [`std::io::Result`] to its [`std::thread::ScopedJoinHandle`].
     ///
@@ -885,11 +1714,11 @@ where
 }
 
 /// Spawns a thread with the specified priority.
-/// This is different from [`spawn`] in a way that the passed function doesn't
+/// This is different from [`spawn()`] in a way that the passed function doesn't
 /// need to accept the [`ThreadPriority::set_for_current`] result.
 /// In case of an error, the error is logged using the logging facilities.
 ///
-/// See [`spawn`].
+/// See [`spawn()`].
 ///
 /// ```rust
 /// use thread_priority::*;
@@ -942,3 +1771,132 @@ where
 {
     Ok(scope.spawn(move || careless_wrapper(f)(priority.set_for_current())))
 }
+
+/// The CPU time budget of a chunk of the background work, see
+/// [`run_background_chunked`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ChunkBudget {
+    cpu_time: Duration,
+    pause: Option<Duration>,
+}
+
+impl ChunkBudget {
+    /// Creates a budget yielding the processor once the work has consumed the
+    /// CPU time since the previous yield.
+    pub fn new(cpu_time: Duration) -> Self {
+        Self {
+            cpu_time,
+            pause: None,
+        }
+    }
+
+    /// Makes the work also sleep for the duration after each chunk, which
+    /// leaves the processor to the other threads even when the system is idle,
+    /// for example, to keep it cool or to save the battery.
+    pub fn pause(mut self, duration: Duration) -> Self {
+        self.pause = Some(duration);
+        self
+    }
+}
+
+impl From<Duration> for ChunkBudget {
+    fn from(cpu_time: Duration) -> Self {
+        Self::new(cpu_time)
+    }
+}
+
+/// Returns the CPU time of the current thread, or the time elapsed since the
+/// origin where the former isn't available.
+fn chunk_clock(origin: Instant) -> Duration {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "illumos",
+        target_os = "solaris",
+        windows,
+    ))]
+    if let Ok(time) = get_current_thread_cpu_time() {
+        return time;
+    }
+    origin.elapsed()
+}
+
+/// Runs a long computation on a thread with the lowest priority, splitting it
+/// into chunks which yield the processor to the other threads.
+///
+/// The function is called repeatedly, each call doing a small step of the work,
+/// until it returns the result. Once the steps have consumed the CPU time of
+/// the budget, the thread yields and, optionally, sleeps, see
+/// [`ChunkBudget::pause`]. The CPU time is measured where possible, so that the
+/// time the thread was preempted isn't counted.
+///
+/// On Linux and Android the thread is scheduled with `SCHED_IDLE`, falling back
+/// to the lowest niceness of `SCHED_OTHER`. Elsewhere, it runs with
+/// [`ThreadPriority::Min`]. If the priority couldn't be set, the work still
+/// runs and the error is logged.
+///
+/// # Usage
+///
+/// ```rust
+/// use std::time::Duration;
+/// use thread_priority::*;
+///
+/// let mut sum = 0u64;
+/// let mut next = 0u64;
+/// let handle = run_background_chunked(
+///     move || {
+///         sum += next;
+///         next += 1;
+///         (next > 1000).then(|| sum)
+///     },
+///     Duration::from_millis(1),
+/// )
+/// .unwrap();
+/// assert_eq!(handle.join().unwrap(), 500500);
+/// ```
+pub fn run_background_chunked<F, T>(
+    mut f: F,
+    chunk_budget: impl Into<ChunkBudget>,
+) -> std::io::Result<std::thread::JoinHandle<T>>
+where
+    F: FnMut() -> Option<T>,
+    F: Send + 'static,
+    T: Send + 'static,
+{
+    let budget = chunk_budget.into();
+    let builder = ThreadBuilder::default().priority(ThreadPriority::Min);
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let builder = builder
+        .policy(ThreadSchedulePolicy::Normal(
+            NormalThreadSchedulePolicy::Idle,
+        ))
+        .fallback(
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other),
+            ThreadPriority::Min,
+        );
+
+    builder.spawn_careless(move || {
+        let origin = Instant::now();
+        let mut chunk_start = chunk_clock(origin);
+        loop {
+            if let Some(result) = f() {
+                return result;
+            }
+            if chunk_clock(origin).saturating_sub(chunk_start) >= budget.cpu_time {
+                std::thread::yield_now();
+                if let Some(pause) = budget.pause {
+                    std::thread::sleep(pause);
+                }
+                chunk_start = chunk_clock(origin);
+            }
+        }
+    })
+}
@@ -0,0 +1,226 @@
+//! This module defines a handle of a spawned thread, which keeps everything
+//! needed to control the thread's scheduling from the other threads.
+//!
+//! ```rust
+//! use thread_priority::*;
+//!
+//! let (sender, receiver) = std::sync::mpsc::channel::<()>();
+//! let mut thread = ThreadBuilder::default()
+//!     .priority(ThreadPriority::Min)
+//!     .spawn_managed(move || receiver.recv().unwrap())
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     thread.config(),
+//!     &ThreadBuilder::default().priority(ThreadPriority::Min)
+//! );
+//! assert!(thread
+//!     .retune(ThreadBuilder::default().priority(ThreadPriority::Min))
+//!     .is_ok());
+//! assert!(thread.snapshot().is_ok());
+//!
+//! sender.send(()).unwrap();
+//! thread.join().unwrap();
+//! ```
+
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+use std::time::Duration;
+
+#[cfg(any(unix, windows))]
+use crate::JoinHandleExt;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::KernelThreadId;
+#[cfg(unix)]
+use crate::ThreadSchedulePolicy;
+use crate::{ConfiguredJoinHandle, Error, ThreadBuilder, ThreadId, ThreadPriority};
+
+/// The scheduling of a thread at some moment, see [`ManagedThread::snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThreadSnapshot {
+    /// The priority of the thread.
+    pub priority: ThreadPriority,
+    /// The scheduling policy of the thread.
+    #[cfg(unix)]
+    pub policy: ThreadSchedulePolicy,
+    /// The processors the thread may run on.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    pub affinity: Vec<usize>,
+}
+
+/// The resource usage of a thread, see [`ManagedThread::stats`].
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ThreadStats {
+    /// The CPU time the thread has consumed so far.
+    pub cpu_time: Duration,
+    /// The number of the CPU clock cycles the thread has consumed so far, see
+    /// `get_thread_cycle_time`.
+    #[cfg(windows)]
+    pub cycles: u64,
+}
+
+/// A spawned thread along with its identity and the configuration last applied
+/// to it, which allows to control the thread's scheduling from the other
+/// threads. See [`ThreadBuilder::spawn_managed`].
+///
+/// The thread can be controlled until it is joined.
+#[derive(Debug)]
+pub struct ManagedThread<T> {
+    handle: ConfiguredJoinHandle<T>,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    kernel_id: KernelThreadId,
+    // The id can't be obtained from the join handle on this platform.
+    #[cfg(not(any(unix, windows)))]
+    native_id: ThreadId,
+    config: ThreadBuilder,
+}
+
+impl<T> ManagedThread<T> {
+    /// Returns the native id of the thread.
+    pub fn native_id(&self) -> ThreadId {
+        #[cfg(any(unix, windows))]
+        return self.handle.get_native_id();
+        #[cfg(not(any(unix, windows)))]
+        return self.native_id;
+    }
+
+    /// Returns the kernel-level id of the thread.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn kernel_id(&self) -> KernelThreadId {
+        self.kernel_id
+    }
+
+    /// Returns the thread, like [`std::thread::JoinHandle::thread`].
+    pub fn thread(&self) -> &std::thread::Thread {
+        self.handle.thread()
+    }
+
+    /// Returns the configuration applied to the thread: the one it was spawned
+    /// with updated by the successful [`ManagedThread::retune`] and
+    /// `ManagedThread::pin` calls.
+    pub fn config(&self) -> &ThreadBuilder {
+        &self.config
+    }
+
+    /// Applies the settings of the builder to the thread, see
+    /// [`ThreadBuilder::plan_for`]. The settings which aren't set in the
+    /// builder are left as they are.
+    ///
+    /// On success, the settings are recorded in [`ManagedThread::config`].
+    pub fn retune(&mut self, builder: ThreadBuilder) -> Result<(), Error> {
+        builder.plan_for(self.native_id())?.apply()?;
+        self.config = crate::config::merge(&self.config, &builder);
+        Ok(())
+    }
+
+    /// Confines the thread to the provided processors, see
+    /// [`crate::set_thread_affinity`].
+    ///
+    /// On success, the affinity is recorded in [`ManagedThread::config`].
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    pub fn pin(&mut self, cores: &[usize]) -> Result<(), Error> {
+        crate::set_thread_affinity(self.native_id(), cores)?;
+        let pinned = ThreadBuilder::default().affinity(cores);
+        self.config = crate::config::merge(&self.config, &pinned);
+        Ok(())
+    }
+
+    /// Returns the current scheduling of the thread as seen by the operating
+    /// system, which may differ from [`ManagedThread::config`] if it was
+    /// changed elsewhere.
+    pub fn snapshot(&self) -> Result<ThreadSnapshot, Error> {
+        let native = self.native_id();
+        Ok(ThreadSnapshot {
+            priority: crate::get_thread_priority(native)?,
+            #[cfg(unix)]
+            policy: crate::thread_schedule_policy_param(native)?.0,
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                windows
+            ))]
+            affinity: crate::get_thread_affinity(native)?,
+        })
+    }
+
+    /// Returns the resource usage of the thread.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn stats(&self) -> Result<ThreadStats, Error> {
+        Ok(ThreadStats {
+            cpu_time: self.kernel_id.cpu_time()?,
+        })
+    }
+
+    /// Returns the resource usage of the thread.
+    #[cfg(windows)]
+    pub fn stats(&self) -> Result<ThreadStats, Error> {
+        let native = self.native_id();
+        Ok(ThreadStats {
+            cpu_time: crate::get_thread_times(native)?.cpu_time(),
+            cycles: crate::get_thread_cycle_time(native)?,
+        })
+    }
+
+    /// Waits for the thread to finish, like [`std::thread::JoinHandle::join`].
+    pub fn join(self) -> std::thread::Result<T> {
+        self.handle.join()
+    }
+
+    /// Returns the underlying join handle, giving up the control.
+    pub fn into_join_handle(self) -> ConfiguredJoinHandle<T> {
+        self.handle
+    }
+}
+
+impl ThreadBuilder {
+    /// Spawns a new thread like [`ThreadBuilder::spawn_wait`] and returns its
+    /// [`ManagedThread`] handle, which allows to inspect and change the
+    /// thread's scheduling afterwards.
+    ///
+    /// An error is returned if the thread couldn't be spawned or configured.
+    pub fn spawn_managed<F, T>(self, f: F) -> std::io::Result<ManagedThread<T>>
+    where
+        F: FnOnce() -> T,
+        F: Send + 'static,
+        T: Send + 'static,
+    {
+        let config = self.clone();
+        #[cfg(any(target_os = "linux", target_os = "android", not(any(unix, windows))))]
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+        let handle = self.spawn_wait(move || {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            let _ = sender.send(KernelThreadId::current());
+            #[cfg(not(any(unix, windows)))]
+            let _ = sender.send(crate::thread_native_id());
+            f()
+        })?;
+
+        Ok(ManagedThread {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            kernel_id: receiver.recv().map_err(|_| configuration_panicked())?,
+            #[cfg(not(any(unix, windows)))]
+            native_id: receiver.recv().map_err(|_| configuration_panicked())?,
+            handle,
+            config,
+        })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", not(any(unix, windows))))]
+fn configuration_panicked() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "The thread has panicked while being configured.",
+    )
+}
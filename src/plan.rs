@@ -0,0 +1,346 @@
+//! This module allows to review the operations configuring a thread before
+//! performing them, which is handy for the privileged deployment tools in the
+//! audited environments: the plan can be logged or approved first and then
+//! applied as is.
+//!
+//! ```rust
+//! use thread_priority::*;
+//!
+//! let builder = ThreadBuilder::default().priority(ThreadPriority::Min);
+//! let plan = builder.plan_for(thread_native_id()).unwrap();
+//! for action in plan.actions() {
+//!     println!("{}", action);
+//! }
+//! assert!(plan.apply().is_ok());
+//! ```
+
+use crate::backend::SchedulingBackend;
+#[cfg(unix)]
+use crate::ThreadSchedulePolicy;
+use crate::{Error, ThreadBuilder, ThreadId, ThreadPriority};
+#[cfg(windows)]
+use crate::{IdealProcessor, WinAPIThreadPriority};
+
+/// A concrete operation on the thread's scheduling.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlannedAction {
+    /// Sets the description of the thread, see `set_thread_description`.
+    /// Failing to do so doesn't stop the plan.
+    #[cfg(windows)]
+    SetDescription {
+        /// The description.
+        description: String,
+    },
+    /// Sets the affinity of the thread, see [`crate::set_thread_affinity`].
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    SetAffinity {
+        /// The processors.
+        cores: Vec<usize>,
+    },
+    /// Moves the thread to the processor group and sets its affinity within
+    /// it, see `set_thread_group_affinity`.
+    #[cfg(windows)]
+    SetGroupAffinity {
+        /// The processor group.
+        group: u16,
+        /// The processors within the group.
+        cores: Vec<usize>,
+    },
+    /// Sets the priority of the thread. On Unix, the current policy of the
+    /// thread is kept.
+    SetPriority {
+        /// The priority.
+        priority: ThreadPriority,
+    },
+    /// Sets the priority and the policy of the thread, see
+    /// [`crate::set_thread_priority_and_policy`].
+    #[cfg(unix)]
+    SetPriorityAndPolicy {
+        /// The priority.
+        priority: ThreadPriority,
+        /// The policy.
+        policy: ThreadSchedulePolicy,
+    },
+    /// Tries each of the fallbacks in order if setting the priority by the
+    /// previous action has failed, see
+    /// [`crate::set_thread_priority_with_fallbacks`].
+    #[cfg(unix)]
+    TryFallbacks {
+        /// The policies and the priorities to try.
+        fallbacks: Vec<(ThreadSchedulePolicy, ThreadPriority)>,
+    },
+    /// Sets the WinAPI priority of the thread, see `set_winapi_thread_priority`.
+    #[cfg(windows)]
+    SetWinAPIPriority {
+        /// The priority.
+        priority: WinAPIThreadPriority,
+    },
+    /// Enables or disables the priority boost of the thread, see
+    /// `set_thread_priority_boost`.
+    #[cfg(windows)]
+    SetBoost {
+        /// Whether the boost is enabled.
+        enabled: bool,
+    },
+    /// Sets the ideal processor of the thread, see `set_thread_ideal_processor`.
+    #[cfg(windows)]
+    SetIdealProcessor {
+        /// The processor.
+        processor: IdealProcessor,
+    },
+    /// Sets the ideal processor of the thread within the NUMA node, see
+    /// `set_thread_ideal_numa_node`.
+    #[cfg(windows)]
+    SetIdealNumaNode {
+        /// The NUMA node.
+        node: u16,
+    },
+}
+
+impl std::fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(windows)]
+            PlannedAction::SetDescription { description } => {
+                write!(f, "set the description to {:?}", description)
+            }
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                windows
+            ))]
+            PlannedAction::SetAffinity { cores } => write!(f, "set the affinity to {:?}", cores),
+            #[cfg(windows)]
+            PlannedAction::SetGroupAffinity { group, cores } => write!(
+                f,
+                "set the affinity to {:?} of the processor group {}",
+                cores, group
+            ),
+            PlannedAction::SetPriority { priority } => {
+                write!(f, "set the priority to {}", priority)
+            }
+            #[cfg(unix)]
+            PlannedAction::SetPriorityAndPolicy { priority, policy } => write!(
+                f,
+                "set the priority to {} and the policy to {}",
+                priority, policy
+            ),
+            #[cfg(unix)]
+            PlannedAction::TryFallbacks { fallbacks } => {
+                write!(f, "if that fails, try")?;
+                for (index, (policy, priority)) in fallbacks.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { "," };
+                    write!(f, "{} the priority {} with {}", separator, priority, policy)?;
+                }
+                Ok(())
+            }
+            #[cfg(windows)]
+            PlannedAction::SetWinAPIPriority { priority } => {
+                write!(f, "set the WinAPI priority to {:?}", priority)
+            }
+            #[cfg(windows)]
+            PlannedAction::SetBoost { enabled } => {
+                let state = if *enabled { "enable" } else { "disable" };
+                write!(f, "{} the priority boost", state)
+            }
+            #[cfg(windows)]
+            PlannedAction::SetIdealProcessor { processor } => {
+                write!(f, "set the ideal processor to {}", processor)
+            }
+            #[cfg(windows)]
+            PlannedAction::SetIdealNumaNode { node } => {
+                write!(f, "set the ideal processor within the NUMA node {}", node)
+            }
+        }
+    }
+}
+
+/// The ordered operations configuring a thread, see
+/// [`ThreadBuilder::plan_for`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulePlan {
+    native: ThreadId,
+    actions: Vec<PlannedAction>,
+}
+
+impl SchedulePlan {
+    /// Returns the thread the plan is for.
+    pub fn target(&self) -> ThreadId {
+        self.native
+    }
+
+    /// Returns the operations in the order they are performed.
+    pub fn actions(&self) -> &[PlannedAction] {
+        &self.actions
+    }
+
+    /// Performs the operations in order, stopping at the first failed one.
+    /// The failure of setting the priority is only returned if there are no
+    /// fallbacks or all of them have failed too.
+    ///
+    /// The priority, the policy and the affinity are changed through the
+    /// registered [`crate::backend::SchedulingBackend`], while the
+    /// Windows-specific operations, which the backends don't have, always use
+    /// the operating system directly.
+    pub fn apply(&self) -> Result<(), Error> {
+        let backend = crate::backend::scheduling_backend();
+        let native = self.native;
+        let mut result = Ok(());
+        for action in &self.actions {
+            result = match (action, result) {
+                #[cfg(unix)]
+                (PlannedAction::TryFallbacks { fallbacks }, Err(_)) => backend
+                    .set_thread_priority_with_fallbacks(native, fallbacks)
+                    .map(|_| ()),
+                #[cfg(unix)]
+                (PlannedAction::TryFallbacks { .. }, Ok(())) => Ok(()),
+                (_, Err(e)) => return Err(e),
+                (action, Ok(())) => apply_action(&*backend, native, action),
+            };
+        }
+        result
+    }
+}
+
+fn apply_action(
+    backend: &dyn SchedulingBackend,
+    native: ThreadId,
+    action: &PlannedAction,
+) -> Result<(), Error> {
+    match action {
+        #[cfg(windows)]
+        PlannedAction::SetDescription { description } => {
+            let _ = crate::set_thread_description(native, description);
+            Ok(())
+        }
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "freebsd",
+            windows
+        ))]
+        PlannedAction::SetAffinity { cores } => backend.set_thread_affinity(native, cores),
+        #[cfg(windows)]
+        PlannedAction::SetGroupAffinity { group, cores } => {
+            crate::set_thread_group_affinity(native, *group, cores)
+        }
+        PlannedAction::SetPriority { priority } => backend.set_thread_priority(native, *priority),
+        #[cfg(unix)]
+        PlannedAction::SetPriorityAndPolicy { priority, policy } => {
+            backend.set_thread_priority_and_policy(native, *priority, *policy)
+        }
+        // The fallbacks are only tried after a failure, see `SchedulePlan::apply`.
+        #[cfg(unix)]
+        PlannedAction::TryFallbacks { .. } => Ok(()),
+        #[cfg(windows)]
+        PlannedAction::SetWinAPIPriority { priority } => {
+            crate::set_winapi_thread_priority(native, *priority)
+        }
+        #[cfg(windows)]
+        PlannedAction::SetBoost { enabled } => crate::set_thread_priority_boost(native, *enabled),
+        #[cfg(windows)]
+        PlannedAction::SetIdealProcessor { processor } => {
+            crate::set_thread_ideal_processor(native, *processor).map(|_| ())
+        }
+        #[cfg(windows)]
+        PlannedAction::SetIdealNumaNode { node } => {
+            crate::set_thread_ideal_numa_node(native, *node).map(|_| ())
+        }
+    }
+}
+
+impl ThreadBuilder {
+    /// Returns the operations the builder performs to configure the thread,
+    /// without performing them. The spawned threads configure themselves by
+    /// applying this very plan.
+    ///
+    /// The MMCSS task, the restoring of the priority and the demotion on panic
+    /// are bound to the lifetime of the spawned thread and therefore aren't
+    /// planned. The memory locking isn't planned either, as it applies to the
+    /// whole process on Unix and to the calling thread on Windows, and is done
+    /// by the spawned threads before applying the plan. The QoS class can only
+    /// be set by the thread itself and isn't planned either. The name is only
+    /// planned on Windows, where it is set as the thread description.
+    ///
+    /// An error is returned if the policy is set without the priority, as it
+    /// isn't currently supported.
+    pub fn plan_for(&self, native: ThreadId) -> Result<SchedulePlan, Error> {
+        let mut actions = Vec::new();
+
+        #[cfg(windows)]
+        if let Some(name) = &self.name {
+            actions.push(PlannedAction::SetDescription {
+                description: name.clone(),
+            });
+        }
+
+        #[cfg(unix)]
+        {
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+            if let Some(cores) = &self.affinity {
+                actions.push(PlannedAction::SetAffinity {
+                    cores: cores.clone(),
+                });
+            }
+            match (self.priority, self.policy) {
+                (Some(priority), Some(policy)) => {
+                    actions.push(PlannedAction::SetPriorityAndPolicy { priority, policy })
+                }
+                (Some(priority), None) => actions.push(PlannedAction::SetPriority { priority }),
+                (None, Some(_)) => {
+                    return Err(Error::Priority(
+                        "Setting the policy separately isn't currently supported.",
+                    ))
+                }
+                (None, None) => {}
+            }
+            if self.priority.is_some() && !self.fallbacks.is_empty() {
+                actions.push(PlannedAction::TryFallbacks {
+                    fallbacks: self.fallbacks.clone(),
+                });
+            }
+        }
+
+        #[cfg(windows)]
+        {
+            match (self.priority, self.winapi_priority) {
+                (_, Some(priority)) => actions.push(PlannedAction::SetWinAPIPriority { priority }),
+                (Some(priority), None) => actions.push(PlannedAction::SetPriority { priority }),
+                (None, None) => {}
+            }
+            if let Some(enabled) = self.boost {
+                actions.push(PlannedAction::SetBoost { enabled });
+            }
+            if let Some(cores) = &self.affinity {
+                actions.push(match self.affinity_group {
+                    Some(group) => PlannedAction::SetGroupAffinity {
+                        group,
+                        cores: cores.clone(),
+                    },
+                    None => PlannedAction::SetAffinity {
+                        cores: cores.clone(),
+                    },
+                });
+            }
+            if let Some(processor) = self.ideal_processor {
+                actions.push(PlannedAction::SetIdealProcessor { processor });
+            }
+            if let Some(node) = self.numa_node {
+                actions.push(PlannedAction::SetIdealNumaNode { node });
+            }
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        if let Some(priority) = self.priority {
+            actions.push(PlannedAction::SetPriority { priority });
+        }
+
+        Ok(SchedulePlan { native, actions })
+    }
+}
@@ -0,0 +1,175 @@
+//! This module defines a fixed-size thread pool whose workers are created
+//! using the [`ThreadBuilder`], so that all of them run with the desired
+//! priority, policy, affinity and name.
+//!
+//! ```rust
+//! use thread_priority::pool::ThreadPool;
+//! use thread_priority::*;
+//!
+//! let pool = ThreadPool::new(
+//!     2,
+//!     ThreadBuilder::default()
+//!         .name("Background")
+//!         .priority(ThreadPriority::Min),
+//! )
+//! .unwrap();
+//!
+//! let (sender, receiver) = std::sync::mpsc::channel();
+//! for i in 0..4 {
+//!     let sender = sender.clone();
+//!     pool.execute(move || sender.send(i).unwrap());
+//! }
+//! pool.join();
+//!
+//! assert_eq!(receiver.try_iter().sum::<i32>(), 6);
+//! ```
+
+use std::sync::{mpsc, Arc, Mutex};
+
+use crate::config::{self, ResolvedConfig};
+use crate::{ConfiguredJoinHandle, ThreadBuilder};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// A fixed-size pool of threads executing the jobs in the order they were
+/// submitted.
+///
+/// The panics of the jobs are caught, so that the workers keep running. See
+/// [`ThreadBuilder::restore_priority`] to restore the scheduling of the workers
+/// after each job.
+#[derive(Debug)]
+pub struct ThreadPool {
+    workers: Vec<ConfiguredJoinHandle<()>>,
+    configs: Vec<ResolvedConfig>,
+    sender: Option<mpsc::Sender<Job>>,
+}
+
+impl ThreadPool {
+    /// Creates a pool of `size` workers configured using the builder on top of
+    /// the global defaults, see [`crate::config`]. When the resolved
+    /// configuration has a name, the workers are named after it with their
+    /// index appended, like `Background-0`.
+    ///
+    /// An error is returned if a worker couldn't be spawned or configured, see
+    /// [`ThreadBuilder::spawn_wait`].
+    pub fn new(size: usize, builder: ThreadBuilder) -> std::io::Result<Self> {
+        let name = config::resolve(Some(&builder), &ThreadBuilder::default())
+            .into_builder()
+            .name;
+        Self::spawn((0..size).map(|index| {
+            let mut thread = ThreadBuilder::default();
+            if let Some(name) = &name {
+                thread.name = Some(format!("{}-{}", name, index));
+            }
+            config::resolve(Some(&builder), &thread)
+        }))
+    }
+
+    /// Creates a pool with a worker for each of the builders, which allows to
+    /// configure the workers individually, for example, pinning each of them to
+    /// its own processor. The builders override the global defaults, see
+    /// [`crate::config`].
+    ///
+    /// An error is returned if a worker couldn't be spawned or configured, see
+    /// [`ThreadBuilder::spawn_wait`].
+    pub fn with_builders<I: IntoIterator<Item = ThreadBuilder>>(
+        builders: I,
+    ) -> std::io::Result<Self> {
+        Self::spawn(
+            builders
+                .into_iter()
+                .map(|builder| config::resolve(None, &builder)),
+        )
+    }
+
+    fn spawn<I: IntoIterator<Item = ResolvedConfig>>(configs: I) -> std::io::Result<Self> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut pool = Self {
+            workers: Vec::new(),
+            configs: Vec::new(),
+            sender: Some(sender),
+        };
+
+        for config in configs {
+            let receiver = Arc::clone(&receiver);
+            pool.configs.push(config.clone());
+            let builder = config.into_builder();
+            let restore_priority = builder.restore_priority;
+            // The pool is shut down on the error when dropped.
+            pool.workers.push(builder.spawn_wait(move || loop {
+                let job = match receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => return,
+                };
+                match job {
+                    Ok(job) => run_job(job, restore_priority),
+                    Err(_) => return,
+                }
+            })?);
+        }
+
+        Ok(pool)
+    }
+
+    /// Returns the number of the workers.
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Returns the resolved configuration of the worker with the index, which
+    /// tells where each of its settings came from.
+    pub fn worker_config(&self, index: usize) -> Option<&ResolvedConfig> {
+        self.configs.get(index)
+    }
+
+    /// Submits the job to be executed by one of the workers.
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if let Some(sender) = &self.sender {
+            // The workers only exit once the sender is dropped.
+            let _ = sender.send(Box::new(f));
+        }
+    }
+
+    /// Waits for all the submitted jobs to be executed and stops the workers.
+    pub fn join(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Runs the job catching its panic and, if asked to, restores the scheduling
+/// the worker had before the job, see [`ThreadBuilder::restore_priority`].
+fn run_job(job: Job, restore_priority: bool) {
+    let _guard = if restore_priority {
+        match crate::PriorityGuard::save() {
+            Ok(guard) => Some(guard),
+            Err(e) => {
+                log::warn!(
+                    "Couldn't save the scheduling of the worker named {:?}: {:?}",
+                    std::thread::current().name(),
+                    e,
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job));
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
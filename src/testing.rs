@@ -0,0 +1,241 @@
+//! This module helps to test the priority management logic of the
+//! applications deterministically and without privileges.
+//!
+//! The [`FakeScheduler`] is a [`SchedulingBackend`] recording the operations
+//! instead of performing them, which may also be told to fail them. It can be
+//! passed to the logic written against the backend trait, or registered with
+//! [`crate::backend::set_scheduling_backend`] to record what the threads
+//! spawned by the [`crate::ThreadBuilder`] do. The settings of the builder
+//! which don't go through the backend, like the boost or the memory locking,
+//! are still applied by the operating system.
+//!
+//! ```rust
+//! use thread_priority::backend::*;
+//! use thread_priority::testing::{FakeScheduler, SchedulingOperation};
+//! use thread_priority::*;
+//!
+//! fn make_background(backend: &dyn SchedulingBackend, native: ThreadId) -> Result<(), Error> {
+//!     backend.set_thread_priority(native, ThreadPriority::Min)
+//! }
+//!
+//! let scheduler = FakeScheduler::default();
+//! let native = thread_native_id();
+//!
+//! assert_eq!(make_background(&scheduler, native), Ok(()));
+//! assert_eq!(
+//!     scheduler.operations(),
+//!     vec![SchedulingOperation::SetPriority {
+//!         native,
+//!         priority: ThreadPriority::Min,
+//!     }]
+//! );
+//! assert_eq!(scheduler.get_thread_priority(native), Ok(ThreadPriority::Min));
+//!
+//! scheduler.fail_next(Error::OS(1));
+//! assert_eq!(make_background(&scheduler, native), Err(Error::OS(1)));
+//!
+//! // The clones share the recorded operations.
+//! scheduler.clear();
+//! set_scheduling_backend(scheduler.clone());
+//! let result = ThreadBuilder::default()
+//!     .priority(ThreadPriority::Max)
+//!     .spawn(|result| result)
+//!     .unwrap()
+//!     .join()
+//!     .unwrap();
+//! reset_scheduling_backend();
+//! assert_eq!(result, Ok(()));
+//! assert!(matches!(
+//!     scheduler.operations()[..],
+//!     [SchedulingOperation::SetPriority {
+//!         priority: ThreadPriority::Max,
+//!         ..
+//!     }]
+//! ));
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::backend::SchedulingBackend;
+#[cfg(unix)]
+use crate::ThreadSchedulePolicy;
+use crate::{Error, ThreadId, ThreadPriority};
+
+/// A scheduling operation recorded by the [`FakeScheduler`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchedulingOperation {
+    /// The priority of the thread was set.
+    SetPriority {
+        /// The thread.
+        native: ThreadId,
+        /// The priority.
+        priority: ThreadPriority,
+    },
+    /// The priority and the policy of the thread were set.
+    #[cfg(unix)]
+    SetPriorityAndPolicy {
+        /// The thread.
+        native: ThreadId,
+        /// The priority.
+        priority: ThreadPriority,
+        /// The policy.
+        policy: ThreadSchedulePolicy,
+    },
+    /// The affinity of the thread was set.
+    SetAffinity {
+        /// The thread.
+        native: ThreadId,
+        /// The cores.
+        cores: Vec<usize>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct FakeSchedulerState {
+    operations: Vec<SchedulingOperation>,
+    failures: VecDeque<Error>,
+    priorities: Vec<(ThreadId, ThreadPriority)>,
+    #[cfg(unix)]
+    policies: Vec<(ThreadId, ThreadSchedulePolicy)>,
+    affinities: Vec<(ThreadId, Vec<usize>)>,
+}
+
+// The thread handles are only compared and never used to access the threads.
+#[cfg(windows)]
+unsafe impl Send for FakeSchedulerState {}
+
+/// The scheduling backend recording the intended operations instead of
+/// performing them.
+///
+/// Every operation is recorded, including the failed ones. An operation fails
+/// with the error injected by [`FakeScheduler::fail_next`], if there is one,
+/// and succeeds otherwise. The clones share the state, so that a clone can be
+/// registered as the backend while the original one is inspected.
+#[derive(Debug, Clone, Default)]
+pub struct FakeScheduler {
+    state: Arc<Mutex<FakeSchedulerState>>,
+}
+
+impl FakeScheduler {
+    /// Creates a scheduler without any operations recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the next operation fail with the error. Calling it several times
+    /// makes the same number of the following operations fail in order.
+    pub fn fail_next(&self, error: Error) {
+        self.state().failures.push_back(error);
+    }
+
+    /// Returns the operations recorded so far.
+    pub fn operations(&self) -> Vec<SchedulingOperation> {
+        self.state().operations.clone()
+    }
+
+    /// Forgets the recorded operations, the injected failures and the
+    /// settings of the threads.
+    pub fn clear(&self) {
+        *self.state() = FakeSchedulerState::default();
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, FakeSchedulerState> {
+        // The state stays consistent even if a user's assertion panicked.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Records the operation and, unless it is made to fail, applies its
+    /// effect to the state.
+    fn record(
+        &self,
+        operation: SchedulingOperation,
+        apply: impl FnOnce(&mut FakeSchedulerState),
+    ) -> Result<(), Error> {
+        let mut state = self.state();
+        state.operations.push(operation);
+        if let Some(error) = state.failures.pop_front() {
+            return Err(error);
+        }
+        apply(&mut state);
+        Ok(())
+    }
+}
+
+/// Replaces the value stored for the thread.
+fn store<T>(values: &mut Vec<(ThreadId, T)>, native: ThreadId, value: T) {
+    values.retain(|(thread, _)| *thread != native);
+    values.push((native, value));
+}
+
+/// Returns the value stored for the thread.
+fn load<T: Clone>(values: &[(ThreadId, T)], native: ThreadId) -> Option<T> {
+    values
+        .iter()
+        .find(|(thread, _)| *thread == native)
+        .map(|(_, value)| value.clone())
+}
+
+impl SchedulingBackend for FakeScheduler {
+    /// Returns the real native id of the current thread, so that the
+    /// operations of the different threads can be told apart.
+    fn thread_native_id(&self) -> ThreadId {
+        crate::thread_native_id()
+    }
+
+    fn set_thread_priority(&self, native: ThreadId, priority: ThreadPriority) -> Result<(), Error> {
+        self.record(
+            SchedulingOperation::SetPriority { native, priority },
+            |state| store(&mut state.priorities, native, priority),
+        )
+    }
+
+    /// Returns the priority last successfully set for the thread.
+    fn get_thread_priority(&self, native: ThreadId) -> Result<ThreadPriority, Error> {
+        load(&self.state().priorities, native)
+            .ok_or(Error::Priority("The priority of the thread wasn't set."))
+    }
+
+    #[cfg(unix)]
+    fn set_thread_priority_and_policy(
+        &self,
+        native: ThreadId,
+        priority: ThreadPriority,
+        policy: ThreadSchedulePolicy,
+    ) -> Result<(), Error> {
+        self.record(
+            SchedulingOperation::SetPriorityAndPolicy {
+                native,
+                priority,
+                policy,
+            },
+            |state| {
+                store(&mut state.priorities, native, priority);
+                store(&mut state.policies, native, policy);
+            },
+        )
+    }
+
+    /// Returns the policy last successfully set for the thread.
+    #[cfg(unix)]
+    fn thread_schedule_policy(&self, native: ThreadId) -> Result<ThreadSchedulePolicy, Error> {
+        load(&self.state().policies, native)
+            .ok_or(Error::Priority("The policy of the thread wasn't set."))
+    }
+
+    fn set_thread_affinity(&self, native: ThreadId, cores: &[usize]) -> Result<(), Error> {
+        self.record(
+            SchedulingOperation::SetAffinity {
+                native,
+                cores: cores.to_vec(),
+            },
+            |state| store(&mut state.affinities, native, cores.to_vec()),
+        )
+    }
+
+    /// Returns the processors last successfully set for the thread.
+    fn get_thread_affinity(&self, native: ThreadId) -> Result<Vec<usize>, Error> {
+        load(&self.state().affinities, native)
+            .ok_or(Error::Priority("The affinity of the thread wasn't set."))
+    }
+}
@@ -0,0 +1,381 @@
+//! This module describes the logical CPUs of the system: which of them share a
+//! physical core (the SMT siblings), a last level cache or a NUMA node, and,
+//! on the hybrid CPUs, which of them are the performance and the efficiency
+//! cores.
+//!
+//! The CPUs are identified by the same numbers the affinity functions of the
+//! crate take, so the topology can be used to choose the affinity directly:
+//!
+//! ```rust
+//! use thread_priority::topology::*;
+//! use thread_priority::*;
+//!
+//! let topology = cpu_topology().unwrap();
+//! let allowed = get_thread_affinity(thread_native_id()).unwrap();
+//! // One logical CPU per physical core, so that the thread doesn't compete
+//! // with the SMT siblings.
+//! let cores: Vec<usize> = topology
+//!     .cores()
+//!     .iter()
+//!     .filter_map(|core| core.iter().map(|cpu| cpu.id).find(|id| allowed.contains(id)))
+//!     .collect();
+//! assert!(set_thread_affinity(thread_native_id(), &cores).is_ok());
+//! ```
+//!
+//! The topology is read from sysfs on Linux and Android, and from the CPU sets
+//! on Windows 10 and later.
+
+use std::collections::BTreeMap;
+
+use crate::Error;
+
+/// The kind of the core of a logical CPU on the hybrid CPUs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum CoreKind {
+    /// A performance core, like the Intel P-cores or the ARM "big" cores.
+    Performance,
+    /// An efficiency core, like the Intel E-cores or the ARM "LITTLE" cores.
+    Efficiency,
+    /// All the cores of the system are of the same kind.
+    Uniform,
+}
+
+/// A logical CPU of the system.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct LogicalCpu {
+    /// The number of the CPU, as the affinity functions take it. On Windows,
+    /// the number is within the processor group.
+    pub id: usize,
+    /// The processor group of the CPU.
+    #[cfg(windows)]
+    pub group: u16,
+    /// The index of the physical core of the CPU, shared by its SMT siblings.
+    pub core: usize,
+    /// The index of the last level cache the CPU uses, if known.
+    pub last_level_cache: Option<usize>,
+    /// The NUMA node of the CPU.
+    pub numa_node: usize,
+    /// The kind of the core of the CPU.
+    pub kind: CoreKind,
+}
+
+/// The logical CPUs of the system, see [`cpu_topology`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CpuTopology {
+    cpus: Vec<LogicalCpu>,
+}
+
+impl CpuTopology {
+    /// Returns all the logical CPUs.
+    pub fn cpus(&self) -> &[LogicalCpu] {
+        &self.cpus
+    }
+
+    /// Returns the other logical CPUs of the physical core of the CPU.
+    pub fn smt_siblings(&self, cpu: &LogicalCpu) -> Vec<&LogicalCpu> {
+        self.cpus
+            .iter()
+            .filter(|other| other.core == cpu.core && *other != cpu)
+            .collect()
+    }
+
+    /// Returns the logical CPUs grouped by their physical cores.
+    pub fn cores(&self) -> Vec<Vec<&LogicalCpu>> {
+        self.group_by(|cpu| Some(cpu.core))
+    }
+
+    /// Returns the logical CPUs grouped by the last level caches they share.
+    /// The CPUs with the cache unknown aren't included.
+    pub fn last_level_cache_groups(&self) -> Vec<Vec<&LogicalCpu>> {
+        self.group_by(|cpu| cpu.last_level_cache)
+    }
+
+    /// Returns the logical CPUs grouped by their NUMA nodes.
+    pub fn numa_nodes(&self) -> Vec<Vec<&LogicalCpu>> {
+        self.group_by(|cpu| Some(cpu.numa_node))
+    }
+
+    /// Returns whether the system has both the performance and the efficiency
+    /// cores.
+    pub fn is_hybrid(&self) -> bool {
+        self.cpus.iter().any(|cpu| cpu.kind != CoreKind::Uniform)
+    }
+
+    /// Returns the logical CPUs of the cores of the kind.
+    pub fn cpus_of_kind(&self, kind: CoreKind) -> Vec<&LogicalCpu> {
+        self.cpus.iter().filter(|cpu| cpu.kind == kind).collect()
+    }
+
+    fn group_by(&self, key: impl Fn(&LogicalCpu) -> Option<usize>) -> Vec<Vec<&LogicalCpu>> {
+        let mut groups: BTreeMap<usize, Vec<&LogicalCpu>> = BTreeMap::new();
+        for cpu in &self.cpus {
+            if let Some(key) = key(cpu) {
+                groups.entry(key).or_default().push(cpu);
+            }
+        }
+        groups.into_values().collect()
+    }
+}
+
+/// Replaces the keys with their indices in the ascending order, so that the
+/// indices of the cores and the caches are the same on all the platforms.
+fn dense_indices<K: Ord + Copy>(keys: &[K]) -> Vec<usize> {
+    let mut sorted = keys.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    keys.iter()
+        .map(|key| sorted.binary_search(key).unwrap_or_default())
+        .collect()
+}
+
+/// Classifies the cores by their performance, where the higher is the
+/// better: the cores of the highest one are the performance ones, unless all
+/// of them are the same.
+fn core_kinds<P: Ord + Copy>(performance: &[P]) -> Vec<CoreKind> {
+    let best = performance.iter().max();
+    let hybrid = performance.iter().any(|p| Some(p) != best);
+    performance
+        .iter()
+        .map(|p| match (hybrid, Some(p) == best) {
+            (false, _) => CoreKind::Uniform,
+            (true, true) => CoreKind::Performance,
+            (true, false) => CoreKind::Efficiency,
+        })
+        .collect()
+}
+
+/// Returns the logical CPUs of the system.
+///
+/// On Linux and Android, only the online CPUs are listed. The kinds of the
+/// cores are taken from the `cpu_core` and `cpu_atom` devices of the Intel
+/// hybrid CPUs, or from the capacities of the cores otherwise.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::topology::*;
+///
+/// let topology = cpu_topology().unwrap();
+/// let cpu = &topology.cpus()[0];
+/// assert!(topology.smt_siblings(cpu).iter().all(|s| s.core == cpu.core));
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn cpu_topology() -> Result<CpuTopology, Error> {
+    use crate::unix::parse_cpu_list;
+
+    let read = |path: String| std::fs::read_to_string(path).ok();
+    let read_list = |path: &str| read(path.to_owned()).and_then(|list| parse_cpu_list(&list));
+    let ids = read_list("/sys/devices/system/cpu/online")
+        .ok_or(Error::Ffi("Couldn't read the online CPUs."))?;
+
+    let mut cores = Vec::new();
+    let mut caches = Vec::new();
+    let mut nodes = Vec::new();
+    let mut capacities = Vec::new();
+    for &id in &ids {
+        let cpu = format!("/sys/devices/system/cpu/cpu{}", id);
+        // A core is identified by its first logical CPU.
+        let siblings = read(format!("{}/topology/thread_siblings_list", cpu))
+            .and_then(|list| parse_cpu_list(&list));
+        cores.push(siblings.and_then(|s| s.into_iter().min()).unwrap_or(id));
+
+        // The last level cache is the one of the highest level, identified by
+        // the first CPU sharing it.
+        let cache = (0..)
+            .map_while(|index| {
+                let dir = format!("{}/cache/index{}", cpu, index);
+                let level: u32 = read(format!("{}/level", dir))?.trim().parse().ok()?;
+                Some((level, read(format!("{}/shared_cpu_list", dir))))
+            })
+            .max_by_key(|&(level, _)| level)
+            .and_then(|(_, shared)| parse_cpu_list(&shared?)?.into_iter().min());
+        caches.push(cache);
+
+        let node = std::fs::read_dir(&cpu)
+            .ok()
+            .and_then(|entries| {
+                entries.filter_map(Result::ok).find_map(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()?
+                        .strip_prefix("node")?
+                        .parse()
+                        .ok()
+                })
+            })
+            .unwrap_or(0usize);
+        nodes.push(node);
+
+        capacities.push(
+            read(format!("{}/cpu_capacity", cpu))
+                .and_then(|capacity| capacity.trim().parse::<u32>().ok())
+                .unwrap_or(0),
+        );
+    }
+
+    let kinds = match (
+        read_list("/sys/devices/cpu_core/cpus"),
+        read_list("/sys/devices/cpu_atom/cpus"),
+    ) {
+        (Some(performance), Some(_)) => core_kinds(
+            &ids.iter()
+                .map(|id| performance.contains(id))
+                .collect::<Vec<_>>(),
+        ),
+        _ => core_kinds(&capacities),
+    };
+
+    let core_indices = dense_indices(&cores);
+    let mut cache_indices =
+        dense_indices(&caches.iter().flatten().copied().collect::<Vec<_>>()).into_iter();
+    Ok(CpuTopology {
+        cpus: ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| LogicalCpu {
+                id,
+                core: core_indices[i],
+                last_level_cache: caches[i].and_then(|_| cache_indices.next()),
+                numa_node: nodes[i],
+                kind: kinds[i],
+            })
+            .collect(),
+    })
+}
+
+/// Returns the logical CPUs of the system, see [`crate::system_cpu_sets`].
+/// The kinds of the cores are taken from their efficiency classes.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::topology::*;
+///
+/// let topology = cpu_topology().unwrap();
+/// let cpu = &topology.cpus()[0];
+/// assert!(topology.smt_siblings(cpu).iter().all(|s| s.core == cpu.core));
+/// ```
+#[cfg(windows)]
+pub fn cpu_topology() -> Result<CpuTopology, Error> {
+    let cpu_sets = crate::system_cpu_sets()?;
+    // The core and the cache indices are relative to the processor group.
+    let cores = dense_indices(
+        &cpu_sets
+            .iter()
+            .map(|set| (set.group, set.core_index))
+            .collect::<Vec<_>>(),
+    );
+    let caches = dense_indices(
+        &cpu_sets
+            .iter()
+            .map(|set| (set.group, set.last_level_cache_index))
+            .collect::<Vec<_>>(),
+    );
+    let kinds = core_kinds(
+        &cpu_sets
+            .iter()
+            .map(|set| set.efficiency_class)
+            .collect::<Vec<_>>(),
+    );
+    Ok(CpuTopology {
+        cpus: cpu_sets
+            .iter()
+            .enumerate()
+            .map(|(i, set)| LogicalCpu {
+                id: set.logical_processor_index as usize,
+                group: set.group,
+                core: cores[i],
+                last_level_cache: Some(caches[i]),
+                numa_node: set.numa_node_index as usize,
+                kind: kinds[i],
+            })
+            .collect(),
+    })
+}
+
+/// Binds the current thread to the NUMA node: confines it to the CPUs of the
+/// node allowed for the process, see [`crate::get_allowed_cpus`], and makes
+/// it allocate the memory from the node only with `set_mempolicy(2)` and
+/// `MPOL_BIND`.
+///
+/// The memory policy applies to the calling thread only, which is why the
+/// thread can't be chosen. If the policy can't be set, the affinity of the
+/// thread is restored.
+///
+/// * May require privileges in the containers
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::topology::*;
+///
+/// let topology = cpu_topology().unwrap();
+/// assert!(set_current_thread_numa_node(topology.cpus()[0].numa_node).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_current_thread_numa_node(node: usize) -> Result<(), Error> {
+    use crate::unix::{errno, parse_cpu_list};
+    use crate::{get_allowed_cpus, get_thread_affinity, set_thread_affinity, thread_native_id};
+
+    let cpus = std::fs::read_to_string(format!("/sys/devices/system/node/node{}/cpulist", node))
+        .ok()
+        .and_then(|list| parse_cpu_list(&list))
+        .ok_or(Error::Priority("The NUMA node doesn't exist."))?;
+    let allowed = get_allowed_cpus()?;
+    let cores: Vec<usize> = cpus
+        .into_iter()
+        .filter(|cpu| allowed.contains(cpu))
+        .collect();
+    if cores.is_empty() {
+        return Err(Error::CpusNotAllowed(allowed));
+    }
+
+    let bits = libc::c_ulong::BITS as usize;
+    let mut nodemask = vec![0 as libc::c_ulong; node / bits + 1];
+    nodemask[node / bits] |= 1 << (node % bits);
+
+    let native = thread_native_id();
+    let previous = get_thread_affinity(native)?;
+    set_thread_affinity(native, &cores)?;
+    // The kernel ignores the last bit of the `maxnode` ones.
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_set_mempolicy,
+            libc::MPOL_BIND,
+            nodemask.as_ptr(),
+            nodemask.len() * bits + 1,
+        )
+    };
+    if ret != 0 {
+        let error = Error::OS(errno());
+        let _ = set_thread_affinity(native, &previous);
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Binds the current thread to the NUMA node, confining it to the processors
+/// of the node within their processor group, see
+/// [`crate::get_numa_node_group_affinity`].
+///
+/// Windows has no memory policy of a thread, but allocates the memory from
+/// the node of the processor the thread runs on first touching it, so the
+/// memory of the thread stays on the node as well.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::topology::*;
+///
+/// let topology = cpu_topology().unwrap();
+/// assert!(set_current_thread_numa_node(topology.cpus()[0].numa_node).is_ok());
+/// ```
+#[cfg(windows)]
+pub fn set_current_thread_numa_node(node: usize) -> Result<(), Error> {
+    let node = u16::try_from(node).map_err(|_| Error::Priority("The NUMA node doesn't exist."))?;
+    let (group, mask) = crate::get_numa_node_group_affinity(node)?;
+    crate::set_thread_group_affinity_mask(crate::thread_native_id(), group, mask).map(|_| ())
+}
@@ -6,18 +6,81 @@
 
 use std::convert::TryFrom;
 
+#[cfg(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub mod attr;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub mod spawn;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod isolation;
+#[cfg(all(feature = "android-adpf", target_os = "android"))]
+pub mod performance_hint;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod pressure;
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+pub mod priocntl;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod process;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use process::{configure_main_thread, Process, ProcessThread};
+#[cfg(all(
+    feature = "apple-audio",
+    any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos"
+    )
+))]
+pub mod workgroup;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "dragonfly"))]
+use libc::cpu_set_t;
+#[cfg(target_os = "freebsd")]
+use libc::cpuset_t as cpu_set_t;
 #[cfg(target_os = "android")]
 use libc::SCHED_NORMAL as SCHED_OTHER;
 #[cfg(not(target_os = "android"))]
 use libc::SCHED_OTHER;
 #[cfg(target_os = "vxworks")]
 use libc::SCHED_SPORADIC;
+#[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+use libc::{pthread_getschedparam, pthread_setschedparam};
 #[cfg(any(target_os = "linux", target_os = "android"))]
 use libc::{SCHED_BATCH, SCHED_IDLE};
 use libc::{SCHED_FIFO, SCHED_RR};
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+use libc::{SCHED_FSS, SCHED_FX, SCHED_IA};
 
 use crate::{Error, ThreadPriority, ThreadPriorityValue};
 use std::mem::MaybeUninit;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+use std::time::Duration;
 
 // Processes scheduled under one of the real-time policies
 // (SCHED_FIFO, SCHED_RR) have a sched_priority value in the range 1
@@ -37,6 +100,22 @@ pub const NICENESS_MAX: i8 = -20;
 /// of niceness have the lowest priority possible.
 pub const NICENESS_MIN: i8 = 19;
 
+// These functions are missing in `libc`.
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+extern "C" {
+    fn pthread_setschedparam(
+        native: libc::pthread_t,
+        policy: libc::c_int,
+        param: *const libc::sched_param,
+    ) -> libc::c_int;
+
+    fn pthread_getschedparam(
+        native: libc::pthread_t,
+        policy: *mut libc::c_int,
+        param: *mut libc::sched_param,
+    ) -> libc::c_int;
+}
+
 /// Proxy structure to maintain compatibility between glibc and musl
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct ScheduleParams {
@@ -44,15 +123,17 @@ pub struct ScheduleParams {
     pub sched_priority: libc::c_int,
 }
 
-fn errno() -> libc::c_int {
+pub(crate) fn errno() -> libc::c_int {
     unsafe {
         cfg_if::cfg_if! {
             if #[cfg(any(target_os = "openbsd", target_os = "netbsd", target_os = "android"))] {
                 *libc::__errno()
             } else if #[cfg(target_os = "linux")] {
                 *libc::__errno_location()
-            } else if #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))] {
+            } else if #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos", target_os = "freebsd"))] {
                 *libc::__error()
+            } else if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+                *libc::___errno()
             } else if #[cfg(target_os = "vxworks")] {
                 libc::errnoGet()
             } else {
@@ -69,8 +150,10 @@ fn set_errno(number: libc::c_int) {
                 *libc::__errno() = number;
             } else if #[cfg(target_os = "linux")] {
                 *libc::__errno_location() = number;
-            } else if #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))] {
+            } else if #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos", target_os = "freebsd"))] {
                 *libc::__error() = number;
+            } else if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+                *libc::___errno() = number;
             } else if #[cfg(target_os = "vxworks")] {
                 let _ = libc::errnoSet(number);
             } else {
@@ -135,6 +218,7 @@ bitflags::bitflags! {
     /// Flags for controlling Deadline scheduling behavior.
     #[repr(transparent)]
     #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct DeadlineFlags: u64 {
         /// Children created by [`libc::fork`] will not inherit privileged
         /// scheduling policies.
@@ -145,19 +229,152 @@ bitflags::bitflags! {
         /// Allows a task to get informed about runtime overruns through the
         /// delivery of SIGXCPU signals.
         const DEADLINE_OVERRUN = 0x04;
+        /// Keeps the current policy of the thread, so that only its
+        /// parameters are changed.
+        const KEEP_POLICY = 0x08;
+        /// Keeps the current parameters of the thread, so that only the
+        /// utilization clamps are changed. Implies
+        /// [`DeadlineFlags::KEEP_POLICY`].
+        const KEEP_PARAMS = 0x10;
+        /// Applies the minimum utilization clamp, see
+        /// [`DeadlineParameters::with_util_clamp`].
+        const UTIL_CLAMP_MIN = 0x20;
+        /// Applies the maximum utilization clamp, see
+        /// [`DeadlineParameters::with_util_clamp`].
+        const UTIL_CLAMP_MAX = 0x40;
     }
 }
 
 /// Returns scheduling attributes for the current thread.
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub fn get_thread_scheduling_attributes() -> Result<SchedAttr, Error> {
-    let mut sched_attr = SchedAttr::default();
     let current_thread = 0;
+    sched_getattr(current_thread)
+}
+
+/// Converts the thread id into a `pid_t` expected by the thread-level syscalls,
+/// like `sched_setattr` or `ioprio_set`.
+///
+/// The current thread, which may also be given as `0`, is translated into `0`,
+/// which the kernel interprets as "the calling thread". A `pthread_t` of any
+/// other thread can't be converted, as it isn't a kernel thread id, so an error
+/// is returned: such threads are addressed with [`KernelThreadId`] instead.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn kernel_tid(native: ThreadId) -> Result<libc::pid_t, Error> {
+    if native == 0 || native == thread_native_id() {
+        Ok(0)
+    } else {
+        Err(Error::Priority(
+            "Only the current thread can be addressed by its pthread id, use KernelThreadId for the other threads.",
+        ))
+    }
+}
+
+/// The largest utilization clamp value, standing for the full capacity.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const UTIL_CLAMP_MAX: u32 = 1024;
+/// The flags changing only the utilization clamps of the thread.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const UTIL_CLAMP_ONLY: DeadlineFlags = DeadlineFlags::KEEP_POLICY
+    .union(DeadlineFlags::KEEP_PARAMS)
+    .union(DeadlineFlags::UTIL_CLAMP_MIN)
+    .union(DeadlineFlags::UTIL_CLAMP_MAX);
+
+/// Checks the utilization clamp values are within `[0; 1024]` and ordered.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn validate_util_clamp(min: u32, max: u32) -> Result<(), Error> {
+    if min > max || max > UTIL_CLAMP_MAX {
+        return Err(Error::Priority(
+            "Utilization clamps must satisfy min <= max <= 1024.",
+        ));
+    }
+    Ok(())
+}
+
+/// Sets the utilization clamp values of the thread, keeping its schedule
+/// policy and parameters intact.
+///
+/// The values are in the `[0; 1024]` range, where `1024` stands for the full
+/// capacity of the most capable CPU in the system. The scheduler uses them as
+/// frequency and task placement hints. Requires a kernel built with
+/// `CONFIG_UCLAMP_TASK`, otherwise `EOPNOTSUPP` is returned.
+///
+/// Only the current thread can be given, the other threads are changed with
+/// [`KernelThreadId::set_util_clamp`].
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let (_, max) = get_thread_util_clamp(thread_id).unwrap();
+/// assert!(set_thread_util_clamp(thread_id, 512, max).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_thread_util_clamp(native: ThreadId, min: u32, max: u32) -> Result<(), Error> {
+    set_util_clamp(kernel_tid(native)?, min, max)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_util_clamp(tid: libc::pid_t, min: u32, max: u32) -> Result<(), Error> {
+    validate_util_clamp(min, max)?;
+    let mut sched_attr = sched_getattr(tid)?;
+    sched_attr.size = std::mem::size_of::<SchedAttr>() as u32;
+    sched_attr.sched_flags |= UTIL_CLAMP_ONLY.bits();
+    sched_attr.sched_util_min = min;
+    sched_attr.sched_util_max = max;
+    sched_setattr(tid, &sched_attr)
+}
+
+/// Returns the utilization clamp values of the thread as a `(min, max)` pair.
+///
+/// The values are in the `[0; 1024]` range, where `1024` stands for the full
+/// capacity of the most capable CPU in the system. Kernels built without
+/// `CONFIG_UCLAMP_TASK` report zeroes.
+///
+/// Only the current thread can be given, the other threads are queried with
+/// [`KernelThreadId::util_clamp`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (min, max) = get_thread_util_clamp(thread_native_id()).unwrap();
+/// assert!(min <= max);
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn get_thread_util_clamp(native: ThreadId) -> Result<(u32, u32), Error> {
+    let attributes = sched_getattr(kernel_tid(native)?)?;
+    Ok((attributes.sched_util_min, attributes.sched_util_max))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn sched_setattr(tid: libc::pid_t, sched_attr: &SchedAttr) -> Result<(), Error> {
+    let flags = 0;
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_sched_setattr,
+            tid,
+            sched_attr as *const SchedAttr,
+            flags,
+        )
+    };
+    if ret < 0 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn sched_getattr(tid: libc::pid_t) -> Result<SchedAttr, Error> {
+    let mut sched_attr = SchedAttr::default();
     let flags = 0;
     let ret = unsafe {
         libc::syscall(
             libc::SYS_sched_getattr,
-            current_thread,
+            tid,
             &mut sched_attr as *mut _,
             std::mem::size_of::<SchedAttr>() as u32,
             flags,
@@ -172,6 +389,7 @@ pub fn get_thread_scheduling_attributes() -> Result<SchedAttr, Error> {
 /// The following "real-time" policies are also supported, for special time-critical applications
 /// that need precise control over the way in which runnable processes are selected for execution
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RealtimeThreadSchedulePolicy {
     /// A first-in, first-out policy
     Fifo,
@@ -205,12 +423,38 @@ impl RealtimeThreadSchedulePolicy {
             RealtimeThreadSchedulePolicy::Deadline => 6,
         }
     }
+
+    /// Checks whether the running kernel supports the policy, so that a
+    /// fallback can be chosen up front instead of interpreting the errors of
+    /// setting it. Whether the process is privileged enough to use the policy
+    /// isn't checked, see [`can_set_realtime_priority`].
+    ///
+    /// The `RealtimeThreadSchedulePolicy::Deadline` policy requires Linux
+    /// 3.14 and the `sched_getattr(2)` and `sched_setattr(2)` system calls,
+    /// which may be blocked by the seccomp filters of the containers and of
+    /// Android.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// assert!(RealtimeThreadSchedulePolicy::Fifo.is_available());
+    /// ```
+    pub fn is_available(self) -> bool {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if self == RealtimeThreadSchedulePolicy::Deadline && sched_getattr(0).is_err() {
+            return false;
+        }
+        ThreadPriority::get_max_priority(ThreadSchedulePolicy::Realtime(self)).is_ok()
+    }
 }
 
 /// Normal (non-realtime) schedule policies
 /// For these schedule policies, [`niceness`](https://man7.org/linux/man-pages/man7/sched.7.html)
 /// is used.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NormalThreadSchedulePolicy {
     /// For running very low priority background jobs.
     /// (Since Linux 2.6.23.) `SCHED_IDLE` can be used only at static priority 0;
@@ -247,7 +491,23 @@ pub enum NormalThreadSchedulePolicy {
     /// This ensures fair progress among all `SCHED_OTHER` threads.
     ///
     /// In the Linux kernel source code, the `SCHED_OTHER` policy is actually named `SCHED_NORMAL`.
+    ///
+    /// On illumos and Solaris, this is the time-sharing (`TS`) class.
     Other,
+    /// The interactive (`IA`) class of illumos and Solaris, which is the
+    /// time-sharing class boosting the threads of the window in focus.
+    #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+    Interactive,
+    /// The fair share (`FSS`) class of illumos and Solaris, dividing the
+    /// processor time between the projects by their shares. This is the usual
+    /// class of the processes in zones.
+    #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+    FairShare,
+    /// The fixed priority (`FX`) class of illumos and Solaris, where the
+    /// priority isn't adjusted by the scheduler, unlike under
+    /// [`NormalThreadSchedulePolicy::Other`].
+    #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+    Fixed,
 }
 impl NormalThreadSchedulePolicy {
     fn to_posix(self) -> libc::c_int {
@@ -257,12 +517,19 @@ impl NormalThreadSchedulePolicy {
             #[cfg(any(target_os = "linux", target_os = "android"))]
             NormalThreadSchedulePolicy::Batch => SCHED_BATCH,
             NormalThreadSchedulePolicy::Other => SCHED_OTHER,
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            NormalThreadSchedulePolicy::Interactive => SCHED_IA,
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            NormalThreadSchedulePolicy::FairShare => SCHED_FSS,
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            NormalThreadSchedulePolicy::Fixed => SCHED_FX,
         }
     }
 }
 
 /// Thread schedule policy definition.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThreadSchedulePolicy {
     /// Normal thread schedule policies.
     Normal(NormalThreadSchedulePolicy),
@@ -270,6 +537,33 @@ pub enum ThreadSchedulePolicy {
     Realtime(RealtimeThreadSchedulePolicy),
 }
 impl ThreadSchedulePolicy {
+    /// Returns the raw policy number, like `SCHED_FIFO`, as accepted by
+    /// `pthread_setschedparam` and the other C APIs. This is a stable part of
+    /// the API meant for the FFI layers and the language bindings.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// let policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    /// assert_eq!(policy.to_raw(), libc::SCHED_FIFO);
+    /// assert_eq!(ThreadSchedulePolicy::from_raw(libc::SCHED_FIFO), Ok(policy));
+    /// ```
+    pub fn to_raw(self) -> libc::c_int {
+        self.to_posix()
+    }
+
+    /// Parses the raw policy number, as returned by `pthread_getschedparam` and
+    /// the other C APIs. This is a stable part of the API meant for the FFI
+    /// layers and the language bindings.
+    ///
+    /// On Linux and Android, the `SCHED_RESET_ON_FORK` flag is ignored. An
+    /// unknown policy results in [`Error::Ffi`].
+    pub fn from_raw(policy: libc::c_int) -> Result<ThreadSchedulePolicy, Error> {
+        Self::from_posix(policy)
+    }
+
     fn to_posix(self) -> libc::c_int {
         match self {
             ThreadSchedulePolicy::Normal(p) => p.to_posix(),
@@ -278,6 +572,10 @@ impl ThreadSchedulePolicy {
     }
 
     fn from_posix(policy: libc::c_int) -> Result<ThreadSchedulePolicy, Error> {
+        // The reset-on-fork flag may be reported alongside the policy itself.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let policy = policy & !libc::SCHED_RESET_ON_FORK;
+
         match policy {
             SCHED_OTHER => Ok(ThreadSchedulePolicy::Normal(
                 NormalThreadSchedulePolicy::Other,
@@ -290,6 +588,18 @@ impl ThreadSchedulePolicy {
             SCHED_IDLE => Ok(ThreadSchedulePolicy::Normal(
                 NormalThreadSchedulePolicy::Idle,
             )),
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            SCHED_IA => Ok(ThreadSchedulePolicy::Normal(
+                NormalThreadSchedulePolicy::Interactive,
+            )),
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            SCHED_FSS => Ok(ThreadSchedulePolicy::Normal(
+                NormalThreadSchedulePolicy::FairShare,
+            )),
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            SCHED_FX => Ok(ThreadSchedulePolicy::Normal(
+                NormalThreadSchedulePolicy::Fixed,
+            )),
             SCHED_FIFO => Ok(ThreadSchedulePolicy::Realtime(
                 RealtimeThreadSchedulePolicy::Fifo,
             )),
@@ -312,6 +622,198 @@ impl ThreadSchedulePolicy {
     }
 }
 
+impl ThreadSchedulePolicy {
+    /// Returns the policy name used by its [`std::fmt::Display`] and
+    /// [`std::str::FromStr`] implementations.
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Idle) => "idle",
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Batch) => "batch",
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other) => "other",
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Interactive) => "ia",
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::FairShare) => "fss",
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Fixed) => "fx",
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo) => "fifo",
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin) => "rr",
+            #[cfg(target_os = "vxworks")]
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Sporadic) => "sporadic",
+            #[cfg(all(
+                any(target_os = "linux", target_os = "android"),
+                not(target_arch = "wasm32")
+            ))]
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline) => "deadline",
+        }
+    }
+}
+
+/// Formats the policy as its lowercase name: `other`, `batch`, `idle`, `ia`,
+/// `fss`, `fx`, `fifo`, `rr`, `deadline` or `sporadic`, depending on the
+/// platform.
+impl std::fmt::Display for ThreadSchedulePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the policy from its name, case-insensitively.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert_eq!(
+///     "fifo".parse(),
+///     Ok(ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo))
+/// );
+/// ```
+impl std::str::FromStr for ThreadSchedulePolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        [
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Idle),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Batch),
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other),
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Interactive),
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::FairShare),
+            #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Fixed),
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin),
+            #[cfg(target_os = "vxworks")]
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Sporadic),
+            #[cfg(all(
+                any(target_os = "linux", target_os = "android"),
+                not(target_arch = "wasm32")
+            ))]
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline),
+        ]
+        .into_iter()
+        .find(|policy| policy.name().eq_ignore_ascii_case(s))
+        .ok_or(Error::Priority("The schedule policy couldn't be parsed."))
+    }
+}
+
+/// Parses a policy together with the priority, separated by a colon, like
+/// `fifo:80` or `other:min`. The deadline policy is followed by its parameters,
+/// like `deadline:10ms/20ms/100ms`; see [`ThreadPriority`]'s
+/// [`std::str::FromStr`] implementation for the forms of the priority.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert_eq!(
+///     parse_policy_and_priority("fifo:80"),
+///     Ok((
+///         ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+///         ThreadPriority::Crossplatform(80u8.try_into().unwrap()),
+///     ))
+/// );
+/// ```
+pub fn parse_policy_and_priority(s: &str) -> Result<(ThreadSchedulePolicy, ThreadPriority), Error> {
+    let s = s.trim();
+    let (policy, priority) = s.split_once(':').ok_or(Error::Priority(
+        "The policy and priority must be separated by a colon.",
+    ))?;
+    let policy: ThreadSchedulePolicy = policy.parse()?;
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        not(target_arch = "wasm32")
+    ))]
+    if policy == ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline) {
+        return Ok((policy, s.parse()?));
+    }
+
+    Ok((policy, priority.parse()?))
+}
+
+/// Defines how the operating system interprets the priority values of a policy.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PriorityRangeKind {
+    /// The values are the niceness, where the lower value means the higher
+    /// priority.
+    Niceness,
+    /// The values are the static `sched_priority`, where the higher value means
+    /// the higher priority.
+    StaticPriority,
+}
+
+/// The range of the priority values allowed for a scheduling policy.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+/// let range = PriorityRange::for_policy(policy).unwrap();
+/// assert_eq!(range.kind, PriorityRangeKind::StaticPriority);
+/// assert!(range.contains(range.max));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PriorityRange {
+    /// The value of the lowest priority. For the niceness, this is numerically
+    /// the largest value.
+    pub min: libc::c_int,
+    /// The value of the highest priority. For the niceness, this is numerically
+    /// the smallest value.
+    pub max: libc::c_int,
+    /// The way the values are interpreted.
+    pub kind: PriorityRangeKind,
+}
+
+impl PriorityRange {
+    /// Returns the range of the priority values allowed for the policy.
+    /// See [`ThreadPriority::min_value_for_policy`] and
+    /// [`ThreadPriority::max_value_for_policy`] for more info.
+    pub fn for_policy(policy: ThreadSchedulePolicy) -> Result<Self, Error> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let uses_niceness = matches!(policy, ThreadSchedulePolicy::Normal(normal) if normal != NormalThreadSchedulePolicy::Idle);
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let uses_niceness = false;
+
+        Ok(Self {
+            min: ThreadPriority::min_value_for_policy(policy)?,
+            max: ThreadPriority::max_value_for_policy(policy)?,
+            kind: if uses_niceness {
+                PriorityRangeKind::Niceness
+            } else {
+                PriorityRangeKind::StaticPriority
+            },
+        })
+    }
+
+    /// Returns the range with the numerically smaller value first.
+    pub fn to_inclusive_range(self) -> std::ops::RangeInclusive<libc::c_int> {
+        std::cmp::min(self.min, self.max)..=std::cmp::max(self.min, self.max)
+    }
+
+    /// Checks whether the value is within the range.
+    pub fn contains(self, value: libc::c_int) -> bool {
+        self.to_inclusive_range().contains(&value)
+    }
+
+    /// Returns the closest value within the range.
+    pub fn clamp(self, value: libc::c_int) -> libc::c_int {
+        let range = self.to_inclusive_range();
+        value.clamp(*range.start(), *range.end())
+    }
+}
+
 /// Defines the type of the priority edge value: minimum or maximum.
 #[derive(Debug, Copy, Clone)]
 pub enum PriorityPolicyEdgeValueType {
@@ -362,8 +864,8 @@ impl ThreadPriority {
                                 PriorityPolicyEdgeValueType::Maximum => NICENESS_MAX as libc::c_int,
                             })
                         }
-                    } else if #[cfg(any(target_os = "macos", target_os = "ios", target_os = "vxworks"))] {
-                        // macOS/iOS and VxWorks allow specifying the priority using sched params.
+                    } else if #[cfg(any(target_os = "macos", target_os = "ios", target_os = "tvos", target_os = "watchos", target_os = "netbsd", target_os = "illumos", target_os = "solaris", target_os = "vxworks"))] {
+                        // macOS/iOS, NetBSD, illumos and VxWorks allow specifying the priority using sched params.
                         get_edge_priority(policy)
                     } else {
                         Err(Error::Priority(
@@ -391,13 +893,7 @@ impl ThreadPriority {
         priority: libc::c_int,
         policy: ThreadSchedulePolicy,
     ) -> Result<libc::c_int, Error> {
-        let min_priority = Self::min_value_for_policy(policy)?;
-        let max_priority = Self::max_value_for_policy(policy)?;
-        let (min, max) = (
-            std::cmp::min(min_priority, max_priority),
-            std::cmp::max(min_priority, max_priority),
-        );
-        let allowed_range = min..=max;
+        let allowed_range = PriorityRange::for_policy(policy)?.to_inclusive_range();
         if allowed_range.contains(&priority) {
             Ok(priority)
         } else {
@@ -413,6 +909,19 @@ impl ThreadPriority {
     /// Source: <https://man7.org/linux/man-pages/man7/sched.7.html>
     /// Due to this restriction of normal scheduling policies and the intention of the library, the niceness is used
     /// instead for such processes.
+    ///
+    /// This is a stable part of the API meant for the FFI layers and the
+    /// language bindings, see also [`ThreadSchedulePolicy::to_raw`].
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// let policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    /// let max = ThreadPriority::max_value_for_policy(policy).unwrap();
+    /// assert_eq!(ThreadPriority::Max.to_posix(policy), Ok(max));
+    /// ```
     pub fn to_posix(self, policy: ThreadSchedulePolicy) -> Result<libc::c_int, Error> {
         let ret = match self {
             ThreadPriority::Min => match policy {
@@ -441,15 +950,35 @@ impl ThreadPriority {
                 // XNU and the derivatives allow to change the priority
                 // for the SCHED_OTHER policy.
                 // <https://www.usenix.org/legacy/publications/library/proceedings/bsdcon02/full_papers/gerbarg/gerbarg_html/index.html>
+                // NetBSD keeps such a priority for every LWP, as well as
+                // the scheduling classes of illumos.
                 #[cfg(all(
-                    any(target_os = "macos", target_os = "ios", target_os = "vxworks"),
+                    any(
+                        target_os = "macos",
+                        target_os = "ios",
+                        target_os = "tvos",
+                        target_os = "watchos",
+                        target_os = "netbsd",
+                        target_os = "illumos",
+                        target_os = "solaris",
+                        target_os = "vxworks"
+                    ),
                     not(target_arch = "wasm32")
                 ))]
                 ThreadSchedulePolicy::Normal(_) => {
                     Self::to_allowed_value_for_policy(p as i32, policy).map(|v| v as u32)
                 }
                 #[cfg(not(all(
-                    any(target_os = "macos", target_os = "ios", target_os = "vxworks"),
+                    any(
+                        target_os = "macos",
+                        target_os = "ios",
+                        target_os = "tvos",
+                        target_os = "watchos",
+                        target_os = "netbsd",
+                        target_os = "illumos",
+                        target_os = "solaris",
+                        target_os = "vxworks"
+                    ),
                     not(target_arch = "wasm32")
                 )))]
                 ThreadSchedulePolicy::Normal(_) => {
@@ -473,6 +1002,20 @@ impl ThreadPriority {
                 ),
                 _ => Self::to_allowed_value_for_policy(p as i32, policy).map(|v| v as u32),
             },
+            ThreadPriority::Fraction(fraction) => match policy {
+                // SCHED_DEADLINE doesn't really have a notion of priority, this is an error
+                #[cfg(all(
+                    any(target_os = "linux", target_os = "android"),
+                    not(target_arch = "wasm32")
+                ))]
+                ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline) => Err(
+                    Error::Priority("Deadline scheduling must use deadline priority."),
+                ),
+                _ => Ok(fraction.interpolate(
+                    Self::min_value_for_policy(policy)?,
+                    Self::max_value_for_policy(policy)?,
+                ) as u32),
+            },
             ThreadPriority::Max => match policy {
                 // SCHED_DEADLINE doesn't really have a notion of priority, this is an error
                 #[cfg(all(
@@ -501,58 +1044,278 @@ impl ThreadPriority {
     pub fn from_posix(params: ScheduleParams) -> ThreadPriority {
         ThreadPriority::Crossplatform(ThreadPriorityValue(params.sched_priority as u8))
     }
+
+    /// Checks whether this and the other priority result in the same POSIX value
+    /// when used with the provided policy, for example, [`ThreadPriority::Min`] and
+    /// `ThreadPriority::Crossplatform(1)` with the `SCHED_FIFO` policy on Linux.
+    ///
+    /// This allows to compare priorities without knowing the mapping
+    /// used on the platform.
+    ///
+    /// Only the mapping of the host the code runs on is known, as the POSIX
+    /// priority ranges are queried from the system, so this doesn't tell
+    /// whether the priorities are equivalent on the other platforms.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// let policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    /// assert_eq!(
+    ///     ThreadPriority::Min.is_equivalent_for_policy(ThreadPriority::Max, policy),
+    ///     Ok(false)
+    /// );
+    /// ```
+    pub fn is_equivalent_for_policy(
+        self,
+        other: ThreadPriority,
+        policy: ThreadSchedulePolicy,
+    ) -> Result<bool, Error> {
+        Ok(self.to_posix(policy)? == other.to_posix(policy)?)
+    }
+}
+
+/// Typed parameters of the [`RealtimeThreadSchedulePolicy::Deadline`] scheduling.
+///
+/// Unlike [`ThreadPriority::Deadline`], this structure can grow new fields as the
+/// kernel does, without breaking the users, so it has to be created using
+/// [`DeadlineParameters::new`]:
+///
+/// ```rust
+/// use thread_priority::*;
+/// use std::time::Duration;
+///
+/// let parameters = DeadlineParameters::new(
+///     Duration::from_millis(1),
+///     Duration::from_millis(10),
+///     Duration::from_millis(100),
+/// )
+/// .with_flags(DeadlineFlags::RESET_ON_FORK);
+/// assert!(set_thread_deadline_parameters(thread_native_id(), &parameters).is_ok());
+///
+/// // The utilization hints require a kernel built with `CONFIG_UCLAMP_TASK`.
+/// assert!(parameters.with_util_clamp(0, 512).validate().is_ok());
+/// ```
+///
+/// The durations can also be set by their names, starting from the default
+/// parameters, and checked while converting them into a [`ThreadPriority`]:
+///
+/// ```rust
+/// use thread_priority::*;
+/// use std::time::Duration;
+///
+/// let priority = DeadlineParameters::default()
+///     .with_runtime(Duration::from_millis(1))
+///     .with_deadline(Duration::from_millis(10))
+///     .with_period(Duration::from_millis(100))
+///     .into_priority()
+///     .unwrap();
+/// assert!(set_thread_priority_and_policy(
+///     thread_native_id(),
+///     priority,
+///     ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline),
+/// )
+/// .is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct DeadlineParameters {
+    /// The computation time granted to the thread within each period.
+    pub runtime: Duration,
+    /// The relative deadline of each activation of the thread.
+    pub deadline: Duration,
+    /// The period of the thread. A zero period means the same as the deadline.
+    pub period: Duration,
+    /// Deadline flags.
+    pub flags: DeadlineFlags,
+    /// Optional utilization hints as a `(min, max)` pair within `[0; 1024]`.
+    pub util_clamp: Option<(u32, u32)>,
 }
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
-fn set_thread_priority_and_policy_deadline(
-    native: ThreadId,
-    priority: ThreadPriority,
-) -> Result<(), Error> {
-    use std::convert::TryInto as _;
+impl DeadlineParameters {
+    /// The smallest runtime the kernel accepts.
+    const MIN_RUNTIME: Duration = Duration::from_nanos(1 << 10);
 
-    let (runtime, deadline, period, flags) = match priority {
-        ThreadPriority::Deadline {
+    /// Creates the parameters with no flags and no utilization hints.
+    pub fn new(runtime: Duration, deadline: Duration, period: Duration) -> Self {
+        Self {
             runtime,
             deadline,
             period,
-            flags,
-        } => (|| {
+            ..Default::default()
+        }
+    }
+
+    /// Sets the computation time granted to the thread within each period.
+    pub fn with_runtime(mut self, runtime: Duration) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Sets the relative deadline of each activation of the thread.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Sets the period of the thread. A zero period means the same as the
+    /// deadline.
+    pub fn with_period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Sets the deadline flags.
+    pub fn with_flags(mut self, flags: DeadlineFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the utilization hints. Both of them are applied, unless only one
+    /// of [`DeadlineFlags::UTIL_CLAMP_MIN`] and
+    /// [`DeadlineFlags::UTIL_CLAMP_MAX`] is set.
+    pub fn with_util_clamp(mut self, min: u32, max: u32) -> Self {
+        self.util_clamp = Some((min, max));
+        self
+    }
+
+    /// Checks the parameters against the rules the kernel enforces, so that
+    /// a descriptive error is returned instead of a bare `EINVAL`.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.to_sched_attr().map(|_| ())
+    }
+
+    /// Validates the parameters and converts them into a
+    /// [`ThreadPriority::Deadline`], see [`DeadlineParameters::validate`].
+    ///
+    /// The utilization hints have no place in the priority and are dropped
+    /// after being validated.
+    pub fn into_priority(self) -> Result<ThreadPriority, Error> {
+        self.validate()?;
+        Ok(self.into())
+    }
+
+    fn to_sched_attr(self) -> Result<SchedAttr, Error> {
+        use std::convert::TryInto as _;
+
+        let (runtime, deadline, period): (u64, u64, u64) = (|| {
             Ok((
-                runtime.as_nanos().try_into()?,
-                deadline.as_nanos().try_into()?,
-                period.as_nanos().try_into()?,
-                flags,
+                self.runtime.as_nanos().try_into()?,
+                self.deadline.as_nanos().try_into()?,
+                self.period.as_nanos().try_into()?,
             ))
         })()
         .map_err(|_: std::num::TryFromIntError| {
             Error::Priority("Deadline policy durations don't fit into a `u64`.")
-        })?,
-        _ => {
+        })?;
+
+        if self.runtime < Self::MIN_RUNTIME {
             return Err(Error::Priority(
-                "Deadline policy given without deadline priority.",
-            ))
+                "Deadline policy runtime must be at least 1024 nanoseconds.",
+            ));
+        }
+        if self.runtime > self.deadline {
+            return Err(Error::Priority(
+                "Deadline policy runtime must not exceed the deadline.",
+            ));
+        }
+        if !self.period.is_zero() && self.deadline > self.period {
+            return Err(Error::Priority(
+                "Deadline policy deadline must not exceed the period.",
+            ));
         }
-    };
-    let tid = native as libc::pid_t;
-    let sched_attr = SchedAttr {
-        size: std::mem::size_of::<SchedAttr>() as u32,
-        sched_policy: RealtimeThreadSchedulePolicy::Deadline.to_posix() as u32,
-        sched_runtime: runtime,
-        sched_deadline: deadline,
-        sched_period: period,
-        sched_flags: flags.bits(),
-        ..Default::default()
-    };
-    let ret =
-        unsafe { libc::syscall(libc::SYS_sched_setattr, tid, &sched_attr as *const _, 0) as i32 };
 
-    match ret {
-        0 => Ok(()),
-        e => Err(Error::OS(e)),
-    }
-}
+        let mut sched_attr = SchedAttr {
+            size: std::mem::size_of::<SchedAttr>() as u32,
+            sched_policy: RealtimeThreadSchedulePolicy::Deadline.to_posix() as u32,
+            sched_runtime: runtime,
+            sched_deadline: deadline,
+            sched_period: period,
+            sched_flags: self.flags.bits(),
+            ..Default::default()
+        };
 
-/// Sets thread's priority and schedule policy
+        let clamp_flags = DeadlineFlags::UTIL_CLAMP_MIN | DeadlineFlags::UTIL_CLAMP_MAX;
+        match self.util_clamp {
+            Some((min, max)) => {
+                validate_util_clamp(min, max)?;
+                // Both of the clamps are applied unless one of them is chosen
+                // by the flags.
+                if !self.flags.intersects(clamp_flags) {
+                    sched_attr.sched_flags |= clamp_flags.bits();
+                }
+                sched_attr.sched_util_min = min;
+                sched_attr.sched_util_max = max;
+            }
+            None if self.flags.intersects(clamp_flags) => {
+                return Err(Error::Priority(
+                    "Utilization clamp flags require the clamp values to be set.",
+                ))
+            }
+            None => {}
+        }
+
+        Ok(sched_attr)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl From<DeadlineParameters> for ThreadPriority {
+    /// Converts the parameters into a priority, dropping the utilization hints
+    /// along with their flags.
+    fn from(parameters: DeadlineParameters) -> Self {
+        ThreadPriority::Deadline {
+            runtime: parameters.runtime,
+            deadline: parameters.deadline,
+            period: parameters.period,
+            flags: parameters.flags
+                - (DeadlineFlags::UTIL_CLAMP_MIN | DeadlineFlags::UTIL_CLAMP_MAX),
+        }
+    }
+}
+
+/// Sets the thread's schedule policy to [`RealtimeThreadSchedulePolicy::Deadline`]
+/// using the provided parameters.
+///
+/// * May require privileges
+///
+/// The parameters are validated with [`DeadlineParameters::validate`] before
+/// the kernel is called. Only the current thread can be given, the other
+/// threads are changed with [`KernelThreadId::set_deadline_parameters`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_thread_deadline_parameters(
+    native: ThreadId,
+    parameters: &DeadlineParameters,
+) -> Result<(), Error> {
+    let sched_attr = parameters.to_sched_attr()?;
+    sched_setattr(kernel_tid(native)?, &sched_attr)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_thread_priority_and_policy_deadline(
+    native: ThreadId,
+    priority: ThreadPriority,
+) -> Result<(), Error> {
+    let parameters = match priority {
+        ThreadPriority::Deadline {
+            runtime,
+            deadline,
+            period,
+            flags,
+        } => DeadlineParameters::new(runtime, deadline, period).with_flags(flags),
+        _ => {
+            return Err(Error::Priority(
+                "Deadline policy given without deadline priority.",
+            ))
+        }
+    };
+    set_thread_deadline_parameters(native, &parameters)
+}
+
+/// Sets thread's priority and schedule policy
 ///
 /// * May require privileges
 ///
@@ -571,6 +1334,12 @@ fn set_thread_priority_and_policy_deadline(
 ///
 /// # Note
 ///
+/// The niceness is only used for the normal policies where it can be set for
+/// a single thread. NetBSD sets the priority of the thread's LWP instead. On
+/// OpenBSD and DragonFly, the niceness is shared by the whole process, so it is
+/// changed for all the threads; use `set_thread_priority_and_policy_strict` to
+/// get an error instead. DragonFly controls the LWPs with `set_thread_rtprio`.
+///
 /// In case the value is specified as [`ThreadPriority::Crossplatform`] and is incompatible with the policy, an error is returned.
 /// However if [`ThreadPriority::Min`] or [`ThreadPriority::Max`] are used, the correct value is used automatically according
 /// to the range of the policy's allowed values.
@@ -588,11 +1357,19 @@ pub fn set_thread_priority_and_policy(
         _ => {
             let fixed_priority = priority.to_posix(policy)?;
             // On VxWorks, macOS and iOS it is possible to set the priority
-            // this way.
+            // this way. On NetBSD, `pthread_setschedparam` sets the priority
+            // of the thread's LWP, see `_lwp_self`, while the niceness is
+            // shared by the whole process. The same goes for the scheduling
+            // classes of illumos, see `priocntl(2)`.
             if matches!(policy, ThreadSchedulePolicy::Realtime(_))
                 || cfg!(any(
                     target_os = "macos",
                     target_os = "ios",
+                    target_os = "tvos",
+                    target_os = "watchos",
+                    target_os = "netbsd",
+                    target_os = "illumos",
+                    target_os = "solaris",
                     target_os = "vxworks"
                 ))
             {
@@ -604,13 +1381,17 @@ pub fn set_thread_priority_and_policy(
                 .into_posix();
 
                 let ret = unsafe {
-                    libc::pthread_setschedparam(
+                    pthread_setschedparam(
                         native,
                         policy.to_posix(),
                         &params as *const libc::sched_param,
                     )
                 };
 
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                if matches!(policy, ThreadSchedulePolicy::Realtime(_)) {
+                    report_realtime_throttling(ret);
+                }
                 match ret {
                     0 => Ok(()),
                     e => Err(Error::OS(e)),
@@ -634,7 +1415,7 @@ pub fn set_thread_priority_and_policy(
                 let params = ScheduleParams { sched_priority: 0 }.into_posix();
 
                 let ret = unsafe {
-                    libc::pthread_setschedparam(
+                    pthread_setschedparam(
                         native,
                         policy.to_posix(),
                         &params as *const libc::sched_param,
@@ -658,6 +1439,39 @@ pub fn set_thread_priority_and_policy(
     }
 }
 
+/// Sets thread's priority and schedule policy like
+/// [`set_thread_priority_and_policy`], but fails for the normal policies
+/// instead of changing the niceness of the whole process, as OpenBSD and
+/// DragonFly keep the niceness per process. DragonFly controls the LWPs with
+/// `set_thread_rtprio` instead.
+///
+/// * May require privileges
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+/// assert!(
+///     set_thread_priority_and_policy_strict(thread_native_id(), ThreadPriority::Min, normal)
+///         .is_err()
+/// );
+/// ```
+#[cfg(any(target_os = "openbsd", target_os = "dragonfly"))]
+pub fn set_thread_priority_and_policy_strict(
+    native: ThreadId,
+    priority: ThreadPriority,
+    policy: ThreadSchedulePolicy,
+) -> Result<(), Error> {
+    if matches!(policy, ThreadSchedulePolicy::Normal(_)) {
+        return Err(Error::Priority(
+            "The niceness is shared by all the threads of the process on this OS.",
+        ));
+    }
+    set_thread_priority_and_policy(native, priority, policy)
+}
+
 /// Set current thread's priority.
 /// In order to properly map a value of the thread priority, the thread scheduling
 /// must be known. This function attempts to retrieve the current thread's
@@ -678,6 +1492,316 @@ pub fn set_current_thread_priority(priority: ThreadPriority) -> Result<(), Error
     set_thread_priority_and_policy(thread_id, priority, policy)
 }
 
+/// Sets current thread's priority and reads it back, returning the priority
+/// actually applied, as the requested one may be adjusted by the system.
+///
+/// Under the normal policies using niceness, several crossplatform values map to
+/// the same niceness, so the lowest of them is returned. Use
+/// [`ThreadPriority::is_equivalent_for_policy`] to compare it with the
+/// requested one.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(set_current_thread_priority_verified(ThreadPriority::Min).is_ok());
+/// ```
+pub fn set_current_thread_priority_verified(
+    priority: ThreadPriority,
+) -> Result<ThreadPriority, Error> {
+    set_current_thread_priority(priority)?;
+    match get_current_thread_interpreted_priority()?.crossplatform {
+        Some(value) => Ok(ThreadPriority::Crossplatform(value)),
+        None => get_current_thread_priority(),
+    }
+}
+
+/// Tries to set the thread's policy and priority using each of the provided
+/// specifications in order until one succeeds, for example, falling back from
+/// `SCHED_FIFO` to `SCHED_RR` and then to niceness when the thread lacks the
+/// privileges required.
+///
+/// Returns the specification which was applied. If none of them could be
+/// applied, the error of the last one is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+/// let fallbacks = [
+///     (
+///         ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+///         ThreadPriority::Max,
+///     ),
+///     (normal, ThreadPriority::Min),
+/// ];
+/// assert!(set_thread_priority_with_fallbacks(thread_native_id(), &fallbacks).is_ok());
+/// ```
+pub fn set_thread_priority_with_fallbacks(
+    native: ThreadId,
+    fallbacks: &[(ThreadSchedulePolicy, ThreadPriority)],
+) -> Result<(ThreadSchedulePolicy, ThreadPriority), Error> {
+    let mut result = Err(Error::Priority("No priority to set"));
+    for &(policy, priority) in fallbacks {
+        result =
+            set_thread_priority_and_policy(native, priority, policy).map(|_| (policy, priority));
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+/// Tries to set the current thread's policy and priority using each of the
+/// provided specifications in order until one succeeds.
+/// For more info read [`set_thread_priority_with_fallbacks`].
+pub fn set_current_thread_priority_with_fallbacks(
+    fallbacks: &[(ThreadSchedulePolicy, ThreadPriority)],
+) -> Result<(ThreadSchedulePolicy, ThreadPriority), Error> {
+    set_thread_priority_with_fallbacks(thread_native_id(), fallbacks)
+}
+
+/// Runs the function on a temporary thread scheduled with the provided priority
+/// and policy, and returns its result once it has finished.
+///
+/// This is handy for the occasional latency-critical or intentionally
+/// deprioritized operations, which don't deserve a dedicated thread. The
+/// function may borrow from the caller's stack. If the scheduling couldn't be
+/// set, the function isn't run and the error is returned. If the function
+/// panics, the panic is propagated to the caller.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let data = vec![1, 2, 3];
+/// let sum = run_with_priority(
+///     ThreadPriority::Min,
+///     ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other),
+///     || data.iter().sum::<i32>(),
+/// );
+/// assert_eq!(sum, Ok(6));
+/// ```
+#[rustversion::since(1.63)]
+pub fn run_with_priority<F, T>(
+    priority: ThreadPriority,
+    policy: ThreadSchedulePolicy,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce() -> T,
+    F: Send,
+    T: Send,
+{
+    std::thread::scope(|scope| {
+        let handle = crate::ThreadBuilder::default()
+            .priority(priority)
+            .policy(policy)
+            .spawn_scoped(scope, |result| result.map(|()| f()))
+            .map_err(io_error)?;
+        handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Returns the CPU time the current thread has consumed so far, which doesn't
+/// include the time it was waiting or preempted.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_current_thread_cpu_time().is_ok());
+/// ```
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
+))]
+pub fn get_current_thread_cpu_time() -> Result<Duration, Error> {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) } != 0 {
+        return Err(io_error(std::io::Error::last_os_error()));
+    }
+    Ok(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+/// Returns the CPU time the thread has consumed so far, see
+/// [`get_current_thread_cpu_time`]. Unlike the latter, this works for any
+/// thread of the process.
+///
+/// The time is read from the CPU clock of the thread, the one of
+/// `pthread_getcpuclockid`, or, on macOS and iOS, from `thread_info`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let native = thread_native_id();
+/// assert!(thread_cpu_time(native).unwrap() <= get_current_thread_cpu_time().unwrap());
+/// ```
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd"
+))]
+pub fn thread_cpu_time(native: ThreadId) -> Result<Duration, Error> {
+    let mut clock: libc::clockid_t = 0;
+    let ret = unsafe { libc::pthread_getcpuclockid(native, &mut clock) };
+    if ret != 0 {
+        return Err(Error::OS(ret));
+    }
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(clock, &mut time) } != 0 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+/// Returns the CPU time the thread has consumed so far, see
+/// [`get_current_thread_cpu_time`]. Unlike the latter, this works for any
+/// thread of the process.
+///
+/// The time is read from the CPU clock of the thread, the one of
+/// `pthread_getcpuclockid`, or, on macOS and iOS, from `thread_info`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let native = thread_native_id();
+/// assert!(thread_cpu_time(native).unwrap() <= get_current_thread_cpu_time().unwrap());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn thread_cpu_time(native: ThreadId) -> Result<Duration, Error> {
+    let mut info = std::mem::MaybeUninit::<libc::thread_basic_info>::uninit();
+    let mut count = libc::THREAD_BASIC_INFO_COUNT;
+    let ret = unsafe {
+        libc::thread_info(
+            thread_mach_port(native),
+            libc::THREAD_BASIC_INFO as libc::thread_flavor_t,
+            info.as_mut_ptr() as libc::thread_info_t,
+            &mut count,
+        )
+    };
+    if ret != libc::KERN_SUCCESS {
+        return Err(Error::OS(ret));
+    }
+    let info = unsafe { info.assume_init() };
+    let duration = |time: libc::time_value_t| {
+        Duration::new(time.seconds as u64, time.microseconds as u32 * 1_000)
+    };
+    Ok(duration(info.user_time) + duration(info.system_time))
+}
+
+/// The resource usage of a thread, see [`get_current_thread_rusage`].
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "openbsd"
+))]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ThreadResourceUsage {
+    /// The time the thread has spent executing in the user mode.
+    pub user_time: Duration,
+    /// The time the thread has spent executing in the kernel mode.
+    pub system_time: Duration,
+    /// The maximum resident set size in bytes. The kernels account it for
+    /// the whole process only, so this is the one of the process.
+    pub max_rss: u64,
+    /// The number of times the thread has given up the CPU itself, for
+    /// example, to sleep or to wait for the I/O.
+    pub voluntary_switches: u64,
+    /// The number of times the thread has been preempted, which grows quickly
+    /// when the priority of the thread is too low for its work.
+    pub involuntary_switches: u64,
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "openbsd"
+))]
+impl ThreadResourceUsage {
+    /// Returns the CPU time the thread has consumed in both the user and the
+    /// kernel modes.
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+}
+
+/// Returns the resource usage of the current thread using
+/// `getrusage(RUSAGE_THREAD)`: its CPU times and context switches.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let usage = get_current_thread_rusage().unwrap();
+/// println!("The thread has been preempted {} times", usage.involuntary_switches);
+/// ```
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "openbsd"
+))]
+pub fn get_current_thread_rusage() -> Result<ThreadResourceUsage, Error> {
+    // Not exported by the libc crate for Android.
+    #[cfg(target_os = "android")]
+    const RUSAGE_THREAD: libc::c_int = 1;
+    #[cfg(not(target_os = "android"))]
+    use libc::RUSAGE_THREAD;
+
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    if unsafe { libc::getrusage(RUSAGE_THREAD, usage.as_mut_ptr()) } != 0 {
+        return Err(Error::OS(errno()));
+    }
+    let usage = unsafe { usage.assume_init() };
+    let duration =
+        |time: libc::timeval| Duration::new(time.tv_sec as u64, time.tv_usec as u32 * 1_000);
+    Ok(ThreadResourceUsage {
+        user_time: duration(usage.ru_utime),
+        system_time: duration(usage.ru_stime),
+        // The size is in kilobytes.
+        max_rss: usage.ru_maxrss as u64 * 1024,
+        voluntary_switches: usage.ru_nvcsw as u64,
+        involuntary_switches: usage.ru_nivcsw as u64,
+    })
+}
+
 /// Returns policy parameters (schedule policy and other schedule parameters) for current process
 ///
 /// # Usage
@@ -708,7 +1832,7 @@ pub fn thread_schedule_policy_param(
         let mut policy = 0i32;
         let mut params = ScheduleParams { sched_priority: 0 }.into_posix();
 
-        let ret = libc::pthread_getschedparam(
+        let ret = pthread_getschedparam(
             native,
             &mut policy as *mut libc::c_int,
             &mut params as *mut libc::sched_param,
@@ -724,10 +1848,58 @@ pub fn thread_schedule_policy_param(
 }
 
 /// Get the thread's priority value.
+///
+/// On Linux and Android, when the thread is scheduled under the
+/// `RealtimeThreadSchedulePolicy::Deadline` policy, the returned value is a
+/// `ThreadPriority::Deadline` read back using `sched_getattr`. This is only
+/// possible for the current thread, for the other threads an error is returned
+/// and their parameters are read with `KernelThreadId::scheduling_attributes`.
+///
+/// On the Apple platforms, when the thread has a QoS class under the normal
+/// policy, the returned value is synthesized from the class and the relative
+/// priority, see `QosClass::base_priority`, as the static priority reported
+/// for such threads doesn't reflect their scheduling. The value is clamped to
+/// the range of the policy, so the classes below it, like
+/// `QosClass::Background`, read as its lowest priority. If the class can't be
+/// read, the static priority is returned. Note that setting the returned
+/// priority back opts the thread out of the QoS, as it is done with
+/// `pthread_setschedparam`; use `set_current_thread_qos_class` to keep the
+/// class.
 pub fn get_thread_priority(native: ThreadId) -> Result<ThreadPriority, Error> {
-    Ok(ThreadPriority::from_posix(
-        thread_schedule_policy_param(native)?.1,
-    ))
+    let (policy, params) = thread_schedule_policy_param(native)?;
+    match policy {
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos"
+        ))]
+        ThreadSchedulePolicy::Normal(_) => {
+            let synthesize = || -> Option<ScheduleParams> {
+                let (class, relative_priority) = get_thread_qos_class(native).ok()?;
+                let base = class.base_priority()?;
+                let range = PriorityRange::for_policy(policy).ok()?.to_inclusive_range();
+                Some(ScheduleParams {
+                    sched_priority: (base + relative_priority).clamp(*range.start(), *range.end()),
+                })
+            };
+            Ok(ThreadPriority::from_posix(synthesize().unwrap_or(params)))
+        }
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            not(target_arch = "wasm32")
+        ))]
+        ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline) => {
+            let attributes = sched_getattr(kernel_tid(native)?)?;
+            Ok(ThreadPriority::Deadline {
+                runtime: Duration::from_nanos(attributes.sched_runtime),
+                deadline: Duration::from_nanos(attributes.sched_deadline),
+                period: Duration::from_nanos(attributes.sched_period),
+                flags: DeadlineFlags::from_bits_truncate(attributes.sched_flags),
+            })
+        }
+        _ => Ok(ThreadPriority::from_posix(params)),
+    }
 }
 
 /// Get current thread's priority value.
@@ -735,110 +1907,3140 @@ pub fn get_current_thread_priority() -> Result<ThreadPriority, Error> {
     get_thread_priority(thread_native_id())
 }
 
-/// A helper trait for other threads to implement to be able to call methods
-/// on threads themselves.
-///
-/// ```rust
-/// use thread_priority::*;
-///
-/// assert!(std::thread::current().get_priority().is_ok());
-///
-/// let join_handle = std::thread::spawn(|| println!("Hello world!"));
-/// assert!(join_handle.thread().get_priority().is_ok());
+/// The thread's priority together with the way the operating system interprets it.
 ///
-/// join_handle.join();
-/// ```
-pub trait ThreadExt {
-    /// Gets the current thread's priority.
-    /// For more info read [`get_current_thread_priority`].
-    ///
-    /// ```rust
-    /// use thread_priority::*;
-    ///
-    /// assert!(std::thread::current().get_priority().is_ok());
-    /// ```
-    fn get_priority(&self) -> Result<ThreadPriority, Error> {
-        get_current_thread_priority()
-    }
-
-    /// Sets the current thread's priority.
-    /// For more info see [`ThreadPriority::set_for_current`].
-    ///
-    /// ```rust
-    /// use thread_priority::*;
-    ///
-    /// assert!(std::thread::current().set_priority(ThreadPriority::Min).is_ok());
-    /// ```
-    fn set_priority(&self, priority: ThreadPriority) -> Result<(), Error> {
-        priority.set_for_current()
-    }
-
-    /// Gets the current thread's schedule policy.
-    /// For more info read [`thread_schedule_policy`].
-    fn get_schedule_policy(&self) -> Result<ThreadSchedulePolicy, Error> {
-        thread_schedule_policy()
-    }
-
-    /// Returns current thread's schedule policy and parameters.
-    /// For more info read [`thread_schedule_policy_param`].
-    fn get_schedule_policy_param(&self) -> Result<(ThreadSchedulePolicy, ScheduleParams), Error> {
-        thread_schedule_policy_param(thread_native_id())
-    }
+/// Under the realtime policies the priority is the static `sched_priority`,
+/// while under the normal ones it is usually the niceness, so the raw values of
+/// the different policies can't be compared with each other.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct InterpretedPriority {
+    /// The scheduling policy of the thread.
+    pub policy: ThreadSchedulePolicy,
+    /// The raw value used by the operating system: the niceness for the normal
+    /// policies using it and the static priority otherwise.
+    pub raw: libc::c_int,
+    /// The crossplatform value resulting in the raw value with this policy, if any.
+    /// It is `None` when no crossplatform value maps to the raw value, for
+    /// example, under the deadline scheduling.
+    pub crossplatform: Option<ThreadPriorityValue>,
+}
 
-    /// Sets current thread's schedule policy.
-    /// For more info read [`set_thread_priority_and_policy`].
-    fn set_priority_and_policy(
-        &self,
-        policy: ThreadSchedulePolicy,
-        priority: ThreadPriority,
-    ) -> Result<(), Error> {
-        cfg_if::cfg_if! {
-            if #[cfg(all(any(target_os = "linux", target_os = "android"), not(target_arch = "wasm32")))] {
-                if policy == ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline) {
-                    set_thread_priority_and_policy(thread_native_id(), ThreadPriority::Crossplatform(ThreadPriorityValue(0)), policy)
-                } else {
-                    set_thread_priority_and_policy(thread_native_id(), priority, policy)
-                }
+impl InterpretedPriority {
+    /// Returns the score of the scheduling strength in the `[0; 100]` range,
+    /// comparable across the platforms, see [`effective_priority_score`].
+    pub fn effective_score(&self) -> u8 {
+        let position = |range: PriorityRange| {
+            if range.max == range.min {
+                0f32
             } else {
-                set_thread_priority_and_policy(thread_native_id(), priority, policy)
+                ((self.raw - range.min) as f32 / (range.max - range.min) as f32).clamp(0f32, 1f32)
             }
+        };
+        match self.policy {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline) => 100,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Idle) => 0,
+            ThreadSchedulePolicy::Normal(_) => PriorityRange::for_policy(self.policy)
+                .map_or(25, |range| 1 + (position(range) * 49f32).round() as u8),
+            ThreadSchedulePolicy::Realtime(_) => PriorityRange::for_policy(self.policy)
+                .map_or(75, |range| 51 + (position(range) * 48f32).round() as u8),
         }
     }
 
-    /// Returns native unix thread id.
-    /// For more info read [`thread_native_id`].
-    ///
-    /// ```rust
-    /// use thread_priority::*;
-    ///
-    /// assert!(std::thread::current().get_native_id().unwrap() > 0);
-    fn get_native_id(&self) -> Result<ThreadId, Error>;
-}
+    fn new(policy: ThreadSchedulePolicy, raw: libc::c_int) -> Self {
+        let crossplatform = (ThreadPriorityValue::MIN..=ThreadPriorityValue::MAX)
+            .map(ThreadPriorityValue)
+            .find(|&value| ThreadPriority::Crossplatform(value).to_posix(policy) == Ok(raw));
 
-/// Auto-implementation of this trait for the [`std::thread::Thread`].
-impl ThreadExt for std::thread::Thread {
-    fn get_native_id(&self) -> Result<ThreadId, Error> {
-        if self.id() == std::thread::current().id() {
-            Ok(thread_native_id())
-        } else {
-            Err(Error::Priority("The `ThreadExt::get_native_id()` is currently limited to be called on the current thread."))
+        Self {
+            policy,
+            raw,
+            crossplatform,
         }
     }
 }
 
-/// Returns current thread id, which is the current OS's native handle.
-/// It may or may not be equal or even related to rust's thread id,
-/// there is absolutely no guarantee for that.
+/// Returns the thread's priority together with its policy and the crossplatform
+/// value it corresponds to.
+///
+/// The niceness can only be obtained for the current thread, so for the other
+/// threads under the normal policies using it, an error is returned.
 ///
 /// # Usage
 ///
 /// ```rust
-/// use thread_priority::thread_native_id;
+/// use thread_priority::*;
 ///
-/// assert!(thread_native_id() > 0);
+/// let priority = get_thread_interpreted_priority(thread_native_id()).unwrap();
+/// assert_eq!(Ok(priority.policy), thread_schedule_policy());
 /// ```
-pub fn thread_native_id() -> ThreadId {
-    unsafe { libc::pthread_self() }
+pub fn get_thread_interpreted_priority(native: ThreadId) -> Result<InterpretedPriority, Error> {
+    let (policy, params) = thread_schedule_policy_param(native)?;
+    let uses_niceness = matches!(policy, ThreadSchedulePolicy::Normal(_))
+        && !cfg!(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "netbsd",
+            target_os = "illumos",
+            target_os = "solaris",
+            target_os = "vxworks"
+        ));
+    let raw = if uses_niceness {
+        if native != thread_native_id() {
+            return Err(Error::Priority(
+                "Niceness of a normal policy can only be obtained for the current thread.",
+            ));
+        }
+        set_errno(0);
+        let niceness = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+        if niceness == -1 && errno() != 0 {
+            return Err(Error::OS(errno()));
+        }
+        niceness
+    } else {
+        params.sched_priority
+    };
+    Ok(InterpretedPriority::new(policy, raw))
+}
+
+/// Returns the score of the thread's scheduling strength in the `[0; 100]`
+/// range, computed from its actual policy and priority, which allows to
+/// compare the threads across the platforms:
+///
+/// * `0` is the idle scheduling;
+/// * `1..=50` are the normal policies, from the highest niceness to the lowest;
+/// * `51..=99` are the realtime policies, from the lowest static priority to
+///   the highest;
+/// * `100` is the deadline scheduling.
+///
+/// The same limitations as of [`get_thread_interpreted_priority`] apply.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let score = effective_priority_score(thread_native_id()).unwrap();
+/// assert!(score <= 100);
+/// ```
+pub fn effective_priority_score(native: ThreadId) -> Result<u8, Error> {
+    get_thread_interpreted_priority(native).map(|priority| priority.effective_score())
+}
+
+/// Returns the current thread's priority together with its interpretation.
+/// For more info read [`get_thread_interpreted_priority`].
+pub fn get_current_thread_interpreted_priority() -> Result<InterpretedPriority, Error> {
+    get_thread_interpreted_priority(thread_native_id())
+}
+
+/// A guard raising the current thread's priority and restoring the previous
+/// scheduling state (policy, priority and niceness) when dropped.
+///
+/// The guard is bound to the thread which created it and therefore can't be sent
+/// to other threads.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// {
+///     let _guard = PriorityGuard::raise(
+///         ThreadPriority::Max,
+///         ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+///     )
+///     .unwrap();
+///     // The critical section runs with the raised priority.
+/// }
+/// // The previous scheduling state is restored here.
+/// ```
+#[derive(Debug)]
+pub struct PriorityGuard {
+    policy: ThreadSchedulePolicy,
+    #[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+    priority: ThreadPriority,
+    params: ScheduleParams,
+    #[cfg_attr(target_os = "vxworks", allow(dead_code))]
+    niceness: Option<libc::c_int>,
+    restored: bool,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl PriorityGuard {
+    /// Records the current thread's scheduling state and sets the new priority
+    /// and policy.
+    /// For more info read [`set_thread_priority_and_policy`].
+    pub fn raise(priority: ThreadPriority, policy: ThreadSchedulePolicy) -> Result<Self, Error> {
+        let mut guard = Self::save()?;
+        if let Err(e) = set_thread_priority_and_policy(thread_native_id(), priority, policy) {
+            guard.restored = true;
+            return Err(e);
+        }
+        Ok(guard)
+    }
+
+    /// Records the current thread's scheduling state without changing it, so that
+    /// it is restored when the guard is dropped, including during unwinding.
+    pub fn save() -> Result<Self, Error> {
+        let native = thread_native_id();
+        let (current_policy, params) = thread_schedule_policy_param(native)?;
+        let current_priority = get_thread_priority(native)?;
+        let niceness = if matches!(current_policy, ThreadSchedulePolicy::Normal(_))
+            && !cfg!(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "tvos",
+                target_os = "watchos",
+                target_os = "netbsd",
+                target_os = "illumos",
+                target_os = "solaris",
+                target_os = "vxworks"
+            )) {
+            set_errno(0);
+            let niceness = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+            if niceness == -1 && errno() != 0 {
+                return Err(Error::OS(errno()));
+            }
+            Some(niceness)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            policy: current_policy,
+            priority: current_priority,
+            params,
+            niceness,
+            restored: false,
+            _not_send: std::marker::PhantomData,
+        })
+    }
+
+    /// Restores the recorded scheduling state, returning the result of doing so.
+    /// Dropping the guard does the same, but only logs the errors.
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.restored = true;
+        self.restore_inner()
+    }
+
+    fn restore_inner(&self) -> Result<(), Error> {
+        let native = thread_native_id();
+
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            not(target_arch = "wasm32")
+        ))]
+        if let ThreadPriority::Deadline { .. } = self.priority {
+            return set_thread_priority_and_policy(native, self.priority, self.policy);
+        }
+
+        let params = self.params.into_posix();
+        let ret = unsafe {
+            pthread_setschedparam(
+                native,
+                self.policy.to_posix(),
+                &params as *const libc::sched_param,
+            )
+        };
+        if ret != 0 {
+            return Err(Error::OS(ret));
+        }
+
+        #[cfg(not(target_os = "vxworks"))]
+        if let Some(niceness) = self.niceness {
+            set_errno(0);
+            let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) };
+            if ret != 0 {
+                return Err(Error::OS(errno()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        if self.restored {
+            return;
+        }
+        if let Err(e) = self.restore_inner() {
+            log::warn!(
+                "Couldn't restore the scheduling of the thread named {:?}: {:?}",
+                std::thread::current().name(),
+                e,
+            );
+        }
+    }
+}
+
+/// A guard temporarily moving the current thread from a realtime policy to
+/// `SCHED_OTHER`, keeping its niceness, and restoring the realtime scheduling
+/// when dropped.
+///
+/// This prevents a realtime thread from starving the system while doing
+/// blocking or unbounded non-critical work, like logging or I/O. If the thread
+/// isn't scheduled under a realtime policy, the guard does nothing.
+///
+/// The guard is bound to the thread which created it and therefore can't be sent
+/// to other threads.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// {
+///     let _guard = RealtimeRelinquishGuard::relinquish().unwrap();
+///     // The non-critical work runs under the normal policy.
+/// }
+/// // The realtime scheduling is restored here.
+/// ```
+#[derive(Debug)]
+pub struct RealtimeRelinquishGuard {
+    guard: Option<PriorityGuard>,
+}
+
+impl RealtimeRelinquishGuard {
+    /// Records the current thread's scheduling state and switches it to the
+    /// `SCHED_OTHER` policy if it is a realtime one.
+    pub fn relinquish() -> Result<Self, Error> {
+        let native = thread_native_id();
+        let (policy, _) = thread_schedule_policy_param(native)?;
+        if !matches!(policy, ThreadSchedulePolicy::Realtime(_)) {
+            return Ok(Self { guard: None });
+        }
+
+        let mut guard = PriorityGuard::save()?;
+        // The niceness isn't changed, so the thread gets back the niceness it
+        // had before becoming a realtime one.
+        let params = ScheduleParams { sched_priority: 0 }.into_posix();
+        let ret = unsafe {
+            pthread_setschedparam(
+                native,
+                ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other).to_posix(),
+                &params as *const libc::sched_param,
+            )
+        };
+        if ret != 0 {
+            guard.restored = true;
+            return Err(Error::OS(ret));
+        }
+        Ok(Self { guard: Some(guard) })
+    }
+
+    /// Returns whether the realtime scheduling was relinquished, which is
+    /// `false` when the thread wasn't scheduled under a realtime policy.
+    pub fn is_relinquished(&self) -> bool {
+        self.guard.is_some()
+    }
+
+    /// Restores the realtime scheduling, returning the result of doing so.
+    /// Dropping the guard does the same, but only logs the errors.
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.guard.take().map_or(Ok(()), PriorityGuard::restore)
+    }
+}
+
+/// A helper trait for other threads to implement to be able to call methods
+/// on threads themselves.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(std::thread::current().get_priority().is_ok());
+///
+/// let join_handle = std::thread::spawn(|| println!("Hello world!"));
+/// assert!(join_handle.thread().get_priority().is_ok());
+///
+/// join_handle.join();
+/// ```
+pub trait ThreadExt {
+    /// Gets the current thread's priority.
+    /// For more info read [`get_current_thread_priority`].
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// assert!(std::thread::current().get_priority().is_ok());
+    /// ```
+    fn get_priority(&self) -> Result<ThreadPriority, Error> {
+        get_current_thread_priority()
+    }
+
+    /// Sets the current thread's priority.
+    /// For more info see [`ThreadPriority::set_for_current`].
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// assert!(std::thread::current().set_priority(ThreadPriority::Min).is_ok());
+    /// ```
+    fn set_priority(&self, priority: ThreadPriority) -> Result<(), Error> {
+        priority.set_for_current()
+    }
+
+    /// Gets the current thread's schedule policy.
+    /// For more info read [`thread_schedule_policy`].
+    fn get_schedule_policy(&self) -> Result<ThreadSchedulePolicy, Error> {
+        thread_schedule_policy()
+    }
+
+    /// Returns current thread's schedule policy and parameters.
+    /// For more info read [`thread_schedule_policy_param`].
+    fn get_schedule_policy_param(&self) -> Result<(ThreadSchedulePolicy, ScheduleParams), Error> {
+        thread_schedule_policy_param(thread_native_id())
+    }
+
+    /// Sets current thread's schedule policy.
+    /// For more info read [`set_thread_priority_and_policy`].
+    fn set_priority_and_policy(
+        &self,
+        policy: ThreadSchedulePolicy,
+        priority: ThreadPriority,
+    ) -> Result<(), Error> {
+        cfg_if::cfg_if! {
+            if #[cfg(all(any(target_os = "linux", target_os = "android"), not(target_arch = "wasm32")))] {
+                if policy == ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline) {
+                    set_thread_priority_and_policy(thread_native_id(), ThreadPriority::Crossplatform(ThreadPriorityValue(0)), policy)
+                } else {
+                    set_thread_priority_and_policy(thread_native_id(), priority, policy)
+                }
+            } else {
+                set_thread_priority_and_policy(thread_native_id(), priority, policy)
+            }
+        }
+    }
+
+    /// Returns native unix thread id.
+    /// For more info read [`thread_native_id`].
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// assert!(std::thread::current().get_native_id().unwrap() > 0);
+    fn get_native_id(&self) -> Result<ThreadId, Error>;
+}
+
+/// Auto-implementation of this trait for the [`std::thread::Thread`].
+impl ThreadExt for std::thread::Thread {
+    fn get_native_id(&self) -> Result<ThreadId, Error> {
+        if self.id() == std::thread::current().id() {
+            Ok(thread_native_id())
+        } else {
+            Err(Error::Priority("The `ThreadExt::get_native_id()` is currently limited to be called on the current thread."))
+        }
+    }
+}
+
+/// Allows controlling the scheduling of a spawned thread from the thread
+/// owning its [`std::thread::JoinHandle`], for example from a supervisor or a pool.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (sender, receiver) = std::sync::mpsc::channel::<()>();
+/// let join_handle = std::thread::spawn(move || receiver.recv());
+///
+/// assert!(join_handle.get_priority().is_ok());
+/// assert!(join_handle
+///     .set_priority_and_policy(
+///         ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+///         ThreadPriority::Min,
+///     )
+///     .is_ok());
+///
+/// sender.send(()).unwrap();
+/// join_handle.join().unwrap().unwrap();
+/// ```
+///
+/// # Note
+///
+/// The normal scheduling policies on most of the unix systems use niceness,
+/// which can only be changed for the calling thread. For such policies an error
+/// is returned, unless the handle refers to the current thread.
+pub trait JoinHandleExt {
+    /// Returns native unix thread id of the spawned thread.
+    fn get_native_id(&self) -> ThreadId;
+
+    /// Gets the spawned thread's priority.
+    /// For more info read [`get_thread_priority`].
+    fn get_priority(&self) -> Result<ThreadPriority, Error> {
+        get_thread_priority(self.get_native_id())
+    }
+
+    /// Sets the spawned thread's priority keeping its current schedule policy.
+    /// For more info read [`set_thread_priority_and_policy`].
+    fn set_priority(&self, priority: ThreadPriority) -> Result<(), Error> {
+        let policy = self.get_schedule_policy()?;
+        self.set_priority_and_policy(policy, priority)
+    }
+
+    /// Gets the spawned thread's schedule policy.
+    /// For more info read [`thread_schedule_policy_param`].
+    fn get_schedule_policy(&self) -> Result<ThreadSchedulePolicy, Error> {
+        thread_schedule_policy_param(self.get_native_id()).map(|policy| policy.0)
+    }
+
+    /// Sets the spawned thread's schedule policy and priority.
+    /// For more info read [`set_thread_priority_and_policy`].
+    fn set_priority_and_policy(
+        &self,
+        policy: ThreadSchedulePolicy,
+        priority: ThreadPriority,
+    ) -> Result<(), Error> {
+        let native = self.get_native_id();
+        let uses_niceness = matches!(policy, ThreadSchedulePolicy::Normal(_))
+            && !cfg!(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "tvos",
+                target_os = "watchos",
+                target_os = "netbsd",
+                target_os = "illumos",
+                target_os = "solaris",
+                target_os = "vxworks"
+            ));
+        if uses_niceness && native != thread_native_id() {
+            return Err(Error::Priority(
+                "Niceness of a normal policy can only be changed for the current thread.",
+            ));
+        }
+        set_thread_priority_and_policy(native, priority, policy)
+    }
+
+    /// Gets the spawned thread's CPU affinity.
+    /// For more info read [`get_thread_affinity`].
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fn get_affinity(&self) -> Result<Vec<usize>, Error> {
+        get_thread_affinity(self.get_native_id())
+    }
+
+    /// Sets the spawned thread's CPU affinity.
+    /// For more info read [`set_thread_affinity`].
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fn set_affinity(&self, cores: &[usize]) -> Result<(), Error> {
+        set_thread_affinity(self.get_native_id(), cores)
+    }
+}
+
+/// Auto-implementation of this trait for the [`std::thread::JoinHandle`].
+impl<T> JoinHandleExt for std::thread::JoinHandle<T> {
+    fn get_native_id(&self) -> ThreadId {
+        std::os::unix::thread::JoinHandleExt::as_pthread_t(self)
+    }
+}
+
+/// Allows starting the child processes with the scheduling chosen up front.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let status = std::process::Command::new("true").niceness(10).status().unwrap();
+/// assert!(status.success());
+/// ```
+#[cfg(not(target_os = "vxworks"))]
+pub trait CommandExt {
+    /// Sets the niceness of the child process with `setpriority(2)` before
+    /// the program is executed, so that the child never runs with the niceness
+    /// of its parent. If the niceness can't be set, spawning the child fails.
+    ///
+    /// * Lowering the niceness below the one of the parent requires privileges
+    fn niceness(&mut self, niceness: libc::c_int) -> &mut Self;
+}
+
+/// Auto-implementation of this trait for the [`std::process::Command`].
+#[cfg(not(target_os = "vxworks"))]
+impl CommandExt for std::process::Command {
+    fn niceness(&mut self, niceness: libc::c_int) -> &mut Self {
+        let set_niceness = move || {
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        };
+        // `setpriority` is async-signal-safe, so it can be called between
+        // `fork` and `exec`.
+        unsafe { std::os::unix::process::CommandExt::pre_exec(self, set_niceness) }
+    }
+}
+
+/// Returns current thread id, which is the current OS's native handle.
+/// It may or may not be equal or even related to rust's thread id,
+/// there is absolutely no guarantee for that.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::thread_native_id;
+///
+/// assert!(thread_native_id() > 0);
+/// ```
+pub fn thread_native_id() -> ThreadId {
+    unsafe { libc::pthread_self() }
+}
+
+/// Returns the id of the current thread as used by the OpenBSD kernel. Wraps
+/// `getthrid`.
+///
+/// OpenBSD keeps the niceness per process, so the normal policies can't have
+/// a priority for a single thread there, see
+/// [`set_thread_priority_and_policy_strict`].
+#[cfg(target_os = "openbsd")]
+pub fn current_thread_lwpid() -> libc::pid_t {
+    unsafe { libc::getthrid() }
+}
+
+/// Returns the id of the LWP of the current thread as used by the NetBSD
+/// kernel. Wraps `_lwp_self`.
+///
+/// The priority of the normal policies is set for the LWP rather than through
+/// the niceness, which is shared by the whole process.
+#[cfg(target_os = "netbsd")]
+pub fn current_thread_lwpid() -> libc::lwpid_t {
+    unsafe { libc::_lwp_self() }
+}
+
+/// Returns the OS-level name of the thread, which is the one shown in
+/// debuggers and tools like `top -H`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread = std::thread::Builder::new()
+///     .name("MyThread".to_owned())
+///     .spawn(|| get_thread_name(thread_native_id()))
+///     .unwrap();
+/// assert_eq!(thread.join().unwrap(), Ok("MyThread".to_owned()));
+/// ```
+#[cfg(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+))]
+pub fn get_thread_name(native: ThreadId) -> Result<String, Error> {
+    // macOS allows the longest names of 64 bytes including the null terminator.
+    let mut buffer = [0 as libc::c_char; 64];
+    let ret = unsafe { libc::pthread_getname_np(native, buffer.as_mut_ptr(), buffer.len()) };
+    if ret != 0 {
+        return Err(Error::OS(ret));
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(buffer.as_ptr()) };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+/// Converts the list of CPU indices into a CPU set.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+// `CPU_SETSIZE` is a `c_int` on Linux, but a `usize` on Android.
+#[allow(clippy::unnecessary_cast)]
+fn cpu_set_from_cores(cores: &[usize]) -> Result<cpu_set_t, Error> {
+    let mut set = unsafe { MaybeUninit::<cpu_set_t>::zeroed().assume_init() };
+    for &core in cores {
+        if core >= libc::CPU_SETSIZE as usize {
+            return Err(Error::Priority(
+                "The CPU index doesn't fit into the CPU set.",
+            ));
+        }
+        unsafe { libc::CPU_SET(core, &mut set) };
+    }
+    Ok(set)
+}
+
+/// Converts the CPU set into the list of CPU indices.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+// `CPU_SETSIZE` is a `c_int` on Linux, but a `usize` on Android.
+#[allow(clippy::unnecessary_cast)]
+fn cores_from_cpu_set(set: &cpu_set_t) -> Vec<usize> {
+    (0..libc::CPU_SETSIZE as usize)
+        .filter(|&core| unsafe { libc::CPU_ISSET(core, set) })
+        .collect()
+}
+
+/// The I/O scheduling class. Check out the `ioprio_set(2)` man page for more info:
+/// <https://man7.org/linux/man-pages/man2/ioprio_set.2.html>
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum IoPriorityClass {
+    /// No I/O priority was set explicitly, so the thread gets a best-effort
+    /// priority derived from its niceness.
+    None,
+    /// The thread is given first access to the disk, regardless of what else is
+    /// going on in the system.
+    ///
+    /// * Requires privileges
+    RealTime,
+    /// The default class, where the level determines the share of the I/O
+    /// bandwidth within the class.
+    BestEffort,
+    /// The thread only gets disk time when no other thread has asked for disk
+    /// I/O for a defined grace period. The level is ignored for this class.
+    Idle,
+}
+
+/// The I/O priority of a thread: the class and the level within this class.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct IoPriority {
+    /// The I/O scheduling class.
+    pub class: IoPriorityClass,
+    /// The level within the class, from `0` (the highest priority) to `7`
+    /// (the lowest priority).
+    pub level: u8,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl IoPriority {
+    /// The highest priority level within a class.
+    pub const LEVEL_MAX: u8 = 0;
+    /// The lowest priority level within a class.
+    pub const LEVEL_MIN: u8 = 7;
+
+    const CLASS_SHIFT: libc::c_int = 13;
+    const WHO_PROCESS: libc::c_int = 1;
+
+    fn to_raw(self) -> Result<libc::c_int, Error> {
+        if self.level > Self::LEVEL_MIN {
+            return Err(Error::PriorityNotInRange(
+                Self::LEVEL_MAX as i32..=Self::LEVEL_MIN as i32,
+            ));
+        }
+        let class = match self.class {
+            IoPriorityClass::None => 0,
+            IoPriorityClass::RealTime => 1,
+            IoPriorityClass::BestEffort => 2,
+            IoPriorityClass::Idle => 3,
+        };
+        Ok(class << Self::CLASS_SHIFT | self.level as libc::c_int)
+    }
+
+    fn from_raw(raw: libc::c_int) -> Result<Self, Error> {
+        let class = match raw >> Self::CLASS_SHIFT {
+            0 => IoPriorityClass::None,
+            1 => IoPriorityClass::RealTime,
+            2 => IoPriorityClass::BestEffort,
+            3 => IoPriorityClass::Idle,
+            _ => return Err(Error::Ffi("Can't parse I/O priority class")),
+        };
+        Ok(Self {
+            class,
+            level: (raw & ((1 << Self::CLASS_SHIFT) - 1)) as u8,
+        })
+    }
+}
+
+/// Sets the I/O priority of the thread.
+///
+/// * May require privileges
+///
+/// Only the current thread can be given, the other threads are changed with
+/// [`KernelThreadId::set_io_priority`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let priority = IoPriority {
+///     class: IoPriorityClass::Idle,
+///     level: 0,
+/// };
+/// assert!(set_thread_io_priority(thread_native_id(), priority).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_thread_io_priority(native: ThreadId, priority: IoPriority) -> Result<(), Error> {
+    set_io_priority(kernel_tid(native)?, priority)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_io_priority(tid: libc::pid_t, priority: IoPriority) -> Result<(), Error> {
+    let raw = priority.to_raw()?;
+    let ret = unsafe { libc::syscall(libc::SYS_ioprio_set, IoPriority::WHO_PROCESS, tid, raw) };
+    if ret < 0 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(())
+}
+
+/// Returns the I/O priority of the thread.
+///
+/// Only the current thread can be given, the other threads are queried with
+/// [`KernelThreadId::io_priority`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_thread_io_priority(thread_native_id()).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn get_thread_io_priority(native: ThreadId) -> Result<IoPriority, Error> {
+    get_io_priority(kernel_tid(native)?)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_io_priority(tid: libc::pid_t) -> Result<IoPriority, Error> {
+    let ret = unsafe { libc::syscall(libc::SYS_ioprio_get, IoPriority::WHO_PROCESS, tid) };
+    if ret < 0 {
+        return Err(Error::OS(errno()));
+    }
+    IoPriority::from_raw(ret as libc::c_int)
+}
+
+/// Converts the I/O error into the crate's error.
+fn io_error(error: std::io::Error) -> Error {
+    Error::OS(error.raw_os_error().unwrap_or(libc::EIO))
+}
+
+/// The system-wide configuration of the [`RealtimeThreadSchedulePolicy::RoundRobin`]
+/// policy, which allows tuning the fairness between the realtime threads of
+/// equal priority.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let config = RoundRobinConfig::current().unwrap();
+/// assert!(!config.timeslice.is_zero());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct RoundRobinConfig {
+    /// The quantum given to each round-robin thread before it is moved to the
+    /// end of the run queue of its priority. The kernel uses millisecond
+    /// granularity, and setting a zero value restores the default.
+    pub timeslice: Duration,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl RoundRobinConfig {
+    const TIMESLICE_PATH: &'static str = "/proc/sys/kernel/sched_rr_timeslice_ms";
+
+    /// Reads the current configuration from `/proc/sys/kernel/sched_rr_timeslice_ms`.
+    pub fn current() -> Result<Self, Error> {
+        let timeslice = std::fs::read_to_string(Self::TIMESLICE_PATH).map_err(io_error)?;
+        let timeslice = timeslice
+            .trim()
+            .parse()
+            .map_err(|_| Error::Ffi("Can't parse the round-robin timeslice"))?;
+        Ok(Self {
+            timeslice: Duration::from_millis(timeslice),
+        })
+    }
+
+    /// Applies the configuration system-wide.
+    ///
+    /// * Requires privileges
+    pub fn apply(&self) -> Result<(), Error> {
+        std::fs::write(Self::TIMESLICE_PATH, self.timeslice.as_millis().to_string())
+            .map_err(io_error)
+    }
+}
+
+/// Returns the round-robin quantum of the thread, which is zero for the threads
+/// not scheduled under the [`RealtimeThreadSchedulePolicy::RoundRobin`] policy.
+///
+/// Only the current thread can be given, the other threads are queried with
+/// [`KernelThreadId::round_robin_interval`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_thread_round_robin_interval(thread_native_id()).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn get_thread_round_robin_interval(native: ThreadId) -> Result<Duration, Error> {
+    get_round_robin_interval(kernel_tid(native)?)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_round_robin_interval(tid: libc::pid_t) -> Result<Duration, Error> {
+    let mut interval = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let ret = unsafe { libc::sched_rr_get_interval(tid, &mut interval) };
+    if ret != 0 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(Duration::new(
+        interval.tv_sec as u64,
+        interval.tv_nsec as u32,
+    ))
+}
+
+/// Parses the kernel's CPU list format, like `0-3,5,7-8`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cores = Vec::new();
+    for range in list.trim().split(',').filter(|r| !r.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => cores.extend(first.parse::<usize>().ok()?..=last.parse().ok()?),
+            None => cores.push(range.parse().ok()?),
+        }
+    }
+    Some(cores)
+}
+
+/// Returns the CPUs the process is allowed to use: the effective CPUs of its
+/// cgroup cpuset if there is one, or all the online CPUs otherwise.
+///
+/// Containers often restrict the CPUs available, in which case the affinity
+/// setters fail for the CPUs not in this list.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let allowed = get_allowed_cpus().unwrap();
+/// let cores = get_thread_affinity(thread_native_id()).unwrap();
+/// assert!(cores.iter().all(|core| allowed.contains(core)));
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn get_allowed_cpus() -> Result<Vec<usize>, Error> {
+    let cgroups = std::fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
+    let cpuset_files = cgroups.lines().filter_map(|line| {
+        let mut fields = line.splitn(3, ':');
+        let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+        if controllers.is_empty() {
+            // The unified (v2) hierarchy.
+            Some(format!("/sys/fs/cgroup{}/cpuset.cpus.effective", path))
+        } else if controllers.split(',').any(|c| c == "cpuset") {
+            Some(format!(
+                "/sys/fs/cgroup/cpuset{}/cpuset.effective_cpus",
+                path
+            ))
+        } else {
+            None
+        }
+    });
+
+    cpuset_files
+        .chain(std::iter::once("/sys/devices/system/cpu/online".to_owned()))
+        .filter_map(|file| std::fs::read_to_string(file).ok())
+        .find_map(|list| parse_cpu_list(&list).filter(|cores| !cores.is_empty()))
+        .ok_or(Error::Priority(
+            "Couldn't determine the CPUs allowed for the process.",
+        ))
+}
+
+/// Returns the CPUs the process is allowed to use: the CPUs of the cpuset the
+/// process belongs to, which may be restricted, for example, in a jail.
+///
+/// The affinity setters fail for the CPUs not in this list.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let allowed = get_allowed_cpus().unwrap();
+/// let cores = get_thread_affinity(thread_native_id()).unwrap();
+/// assert!(cores.iter().all(|core| allowed.contains(core)));
+/// ```
+#[cfg(target_os = "freebsd")]
+pub fn get_allowed_cpus() -> Result<Vec<usize>, Error> {
+    cpuset_get_affinity(libc::CPU_LEVEL_CPUSET, libc::CPU_WHICH_PID, -1)
+}
+
+/// Returns the CPUs of the mask selected by the level and the target, see
+/// `cpuset_getaffinity(2)`.
+#[cfg(target_os = "freebsd")]
+fn cpuset_get_affinity(
+    level: libc::cpulevel_t,
+    which: libc::cpuwhich_t,
+    id: libc::id_t,
+) -> Result<Vec<usize>, Error> {
+    let mut set = cpu_set_from_cores(&[])?;
+    do_with_errno(|| unsafe {
+        libc::cpuset_getaffinity(level, which, id, std::mem::size_of::<cpu_set_t>(), &mut set)
+    })?;
+    Ok(cores_from_cpu_set(&set))
+}
+
+/// Sets the CPUs of the mask selected by the level and the target, see
+/// `cpuset_setaffinity(2)`.
+#[cfg(target_os = "freebsd")]
+fn cpuset_set_affinity(
+    level: libc::cpulevel_t,
+    which: libc::cpuwhich_t,
+    id: libc::id_t,
+    cores: &[usize],
+) -> Result<(), Error> {
+    let set = cpu_set_from_cores(cores)?;
+    do_with_errno(|| unsafe {
+        libc::cpuset_setaffinity(level, which, id, std::mem::size_of::<cpu_set_t>(), &set)
+    })
+    .map(|_| ())
+}
+
+/// Checks that all the CPUs requested are allowed to be used by the process.
+/// If the allowed CPUs can't be determined, the check is left to the OS.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn validate_allowed_cpus(cores: &[usize]) -> Result<(), Error> {
+    match get_allowed_cpus() {
+        Ok(allowed) if !cores.iter().all(|core| allowed.contains(core)) => {
+            Err(Error::CpusNotAllowed(allowed))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sets the CPU affinity of the thread, confining it to the provided CPUs.
+///
+/// * May require privileges
+///
+/// If any of the CPUs isn't allowed for the process, [`Error::CpusNotAllowed`]
+/// listing the allowed CPUs is returned. See [`get_allowed_cpus`].
+///
+/// On FreeBSD, this is `cpuset_setaffinity` with `CPU_LEVEL_WHICH` and
+/// `CPU_WHICH_TID` for the thread, as done by `pthread_setaffinity_np`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let cores = get_thread_affinity(thread_id).unwrap();
+/// assert!(set_thread_affinity(thread_id, &cores[..1]).is_ok());
+/// assert!(set_thread_affinity(thread_id, &cores).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub fn set_thread_affinity(native: ThreadId, cores: &[usize]) -> Result<(), Error> {
+    validate_allowed_cpus(cores)?;
+    let set = cpu_set_from_cores(cores)?;
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "android")] {
+            let ret = unsafe {
+                libc::sched_setaffinity(
+                    libc::pthread_gettid_np(native),
+                    std::mem::size_of::<cpu_set_t>(),
+                    &set,
+                )
+            };
+            if ret != 0 {
+                return Err(Error::OS(errno()));
+            }
+        } else {
+            let ret = unsafe {
+                libc::pthread_setaffinity_np(native, std::mem::size_of::<cpu_set_t>(), &set)
+            };
+            if ret != 0 {
+                return Err(Error::OS(ret));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the CPU affinity of the thread as the list of CPUs it may run on.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(!get_thread_affinity(thread_native_id()).unwrap().is_empty());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub fn get_thread_affinity(native: ThreadId) -> Result<Vec<usize>, Error> {
+    let mut set = cpu_set_from_cores(&[])?;
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "android")] {
+            let ret = unsafe {
+                libc::sched_getaffinity(
+                    libc::pthread_gettid_np(native),
+                    std::mem::size_of::<cpu_set_t>(),
+                    &mut set,
+                )
+            };
+            if ret != 0 {
+                return Err(Error::OS(errno()));
+            }
+        } else {
+            let ret = unsafe {
+                libc::pthread_getaffinity_np(
+                    native,
+                    std::mem::size_of::<cpu_set_t>(),
+                    &mut set,
+                )
+            };
+            if ret != 0 {
+                return Err(Error::OS(ret));
+            }
+        }
+    }
+    Ok(cores_from_cpu_set(&set))
+}
+
+/// Returns the CPU the current thread is running on, which is handy for
+/// verifying the affinity. The thread may be moved to another CPU right after
+/// the call, unless it is confined to a single one.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let cpu = current_cpu().unwrap();
+/// println!("Running on the CPU {cpu}");
+/// ```
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+pub fn current_cpu() -> Result<usize, Error> {
+    match unsafe { libc::sched_getcpu() } {
+        -1 => Err(Error::OS(errno())),
+        cpu => Ok(cpu as usize),
+    }
+}
+
+/// Sets the CPU affinity of the whole process: every thread currently running
+/// in the process is confined to the provided CPUs. Threads created afterwards
+/// inherit the affinity of their creator.
+///
+/// * May require privileges
+///
+/// If any of the CPUs isn't allowed for the process, [`Error::CpusNotAllowed`]
+/// listing the allowed CPUs is returned. See [`get_allowed_cpus`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let cores = get_process_affinity().unwrap();
+/// assert!(set_process_affinity(&cores).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_process_affinity(cores: &[usize]) -> Result<(), Error> {
+    validate_allowed_cpus(cores)?;
+    let set = cpu_set_from_cores(cores)?;
+    let tasks = std::fs::read_dir("/proc/self/task").map_err(io_error)?;
+
+    for task in tasks.flatten() {
+        let tid = match task.file_name().to_str().and_then(|n| n.parse().ok()) {
+            Some(tid) => tid,
+            None => continue,
+        };
+        let ret = unsafe { libc::sched_setaffinity(tid, std::mem::size_of::<cpu_set_t>(), &set) };
+        // The thread may have exited since the directory was listed.
+        if ret != 0 && errno() != libc::ESRCH {
+            return Err(Error::OS(errno()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the CPU affinity of the process, which is the affinity of its main thread.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(!get_process_affinity().unwrap().is_empty());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn get_process_affinity() -> Result<Vec<usize>, Error> {
+    let mut set = cpu_set_from_cores(&[])?;
+    let ret = unsafe {
+        libc::sched_getaffinity(libc::getpid(), std::mem::size_of::<cpu_set_t>(), &mut set)
+    };
+    if ret != 0 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(cores_from_cpu_set(&set))
+}
+
+/// Sets the CPU affinity of the process, confining all its threads to the
+/// provided CPUs, using `cpuset_setaffinity` with `CPU_WHICH_PID`.
+///
+/// If any of the CPUs isn't allowed for the process, [`Error::CpusNotAllowed`]
+/// listing the allowed CPUs is returned. See [`get_allowed_cpus`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let cores = get_process_affinity().unwrap();
+/// assert!(set_process_affinity(&cores).is_ok());
+/// ```
+#[cfg(target_os = "freebsd")]
+pub fn set_process_affinity(cores: &[usize]) -> Result<(), Error> {
+    validate_allowed_cpus(cores)?;
+    cpuset_set_affinity(libc::CPU_LEVEL_WHICH, libc::CPU_WHICH_PID, -1, cores)
+}
+
+/// Returns the CPU affinity of the process.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(!get_process_affinity().unwrap().is_empty());
+/// ```
+#[cfg(target_os = "freebsd")]
+pub fn get_process_affinity() -> Result<Vec<usize>, Error> {
+    cpuset_get_affinity(libc::CPU_LEVEL_WHICH, libc::CPU_WHICH_PID, -1)
+}
+
+/// Sets the CPU affinity of the thread with the kernel id, see
+/// [`current_thread_lwpid`], using `cpuset_setaffinity` with `CPU_WHICH_TID`.
+/// The id `-1` stands for the current thread.
+///
+/// If any of the CPUs isn't allowed for the process, [`Error::CpusNotAllowed`]
+/// listing the allowed CPUs is returned. See [`get_allowed_cpus`].
+#[cfg(target_os = "freebsd")]
+pub fn set_lwp_affinity(lwpid: libc::lwpid_t, cores: &[usize]) -> Result<(), Error> {
+    validate_allowed_cpus(cores)?;
+    cpuset_set_affinity(
+        libc::CPU_LEVEL_WHICH,
+        libc::CPU_WHICH_TID,
+        lwpid as libc::id_t,
+        cores,
+    )
+}
+
+/// Returns the CPU affinity of the thread with the kernel id, see
+/// [`set_lwp_affinity`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let cores = get_lwp_affinity(current_thread_lwpid()).unwrap();
+/// assert_eq!(cores, get_thread_affinity(thread_native_id()).unwrap());
+/// ```
+#[cfg(target_os = "freebsd")]
+pub fn get_lwp_affinity(lwpid: libc::lwpid_t) -> Result<Vec<usize>, Error> {
+    cpuset_get_affinity(
+        libc::CPU_LEVEL_WHICH,
+        libc::CPU_WHICH_TID,
+        lwpid as libc::id_t,
+    )
+}
+
+// These functions are missing in `libc`.
+#[cfg(target_os = "dragonfly")]
+extern "C" {
+    fn lwp_gettid() -> libc::lwpid_t;
+
+    fn lwp_setaffinity(
+        pid: libc::pid_t,
+        lwpid: libc::lwpid_t,
+        mask: *const cpu_set_t,
+    ) -> libc::c_int;
+
+    fn lwp_getaffinity(pid: libc::pid_t, lwpid: libc::lwpid_t, mask: *mut cpu_set_t)
+        -> libc::c_int;
+}
+
+/// Sets the CPU affinity of the LWP with the kernel id, see
+/// [`current_thread_lwpid`], using `lwp_setaffinity`. The id `-1` stands for
+/// the current thread.
+///
+/// * May require privileges
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::*;
+///
+/// assert!(set_lwp_affinity(current_thread_lwpid(), &[0]).is_ok());
+/// ```
+#[cfg(target_os = "dragonfly")]
+pub fn set_lwp_affinity(lwpid: libc::lwpid_t, cores: &[usize]) -> Result<(), Error> {
+    let set = cpu_set_from_cores(cores)?;
+    do_with_errno(|| unsafe { lwp_setaffinity(0, lwpid, &set) }).map(|_| ())
+}
+
+/// Returns the CPU affinity of the LWP with the kernel id, see
+/// [`set_lwp_affinity`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(!get_lwp_affinity(current_thread_lwpid()).unwrap().is_empty());
+/// ```
+#[cfg(target_os = "dragonfly")]
+pub fn get_lwp_affinity(lwpid: libc::lwpid_t) -> Result<Vec<usize>, Error> {
+    let mut set = cpu_set_from_cores(&[])?;
+    do_with_errno(|| unsafe { lwp_getaffinity(0, lwpid, &mut set) })?;
+    Ok(cores_from_cpu_set(&set))
+}
+
+/// The kernel-level id of a thread on Linux, as returned by `gettid(2)`.
+///
+/// Unlike [`ThreadId`], which is a `pthread_t` meaningful only within the
+/// process, this is the id the kernel interfaces, like `setpriority(2)`,
+/// `sched_setaffinity(2)` and `sched_setattr(2)`, take for a thread. It is also
+/// the one shown in `/proc/<pid>/task` and by the tools like `top -H`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let tid = KernelThreadId::current();
+/// let niceness = tid.niceness().unwrap();
+/// assert!(tid.set_niceness(niceness).is_ok());
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct KernelThreadId(pub libc::pid_t);
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl KernelThreadId {
+    /// Returns the kernel-level id of the current thread.
+    pub fn current() -> Self {
+        Self(unsafe { libc::syscall(libc::SYS_gettid) } as libc::pid_t)
+    }
+
+    /// Returns the niceness of the thread, which is used by the normal
+    /// scheduling policies.
+    pub fn niceness(self) -> Result<libc::c_int, Error> {
+        set_errno(0);
+        let niceness = unsafe { libc::getpriority(libc::PRIO_PROCESS, self.0 as libc::id_t) };
+        if niceness == -1 && errno() != 0 {
+            return Err(Error::OS(errno()));
+        }
+        Ok(niceness)
+    }
+
+    /// Sets the niceness of the thread, which is used by the normal scheduling
+    /// policies.
+    ///
+    /// * May require privileges to decrease the niceness
+    pub fn set_niceness(self, niceness: libc::c_int) -> Result<(), Error> {
+        let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, self.0 as libc::id_t, niceness) };
+        if ret != 0 {
+            return Err(Error::OS(errno()));
+        }
+        Ok(())
+    }
+
+    /// Returns the CPU affinity of the thread as the list of CPUs it may run on.
+    pub fn affinity(self) -> Result<Vec<usize>, Error> {
+        let mut set = cpu_set_from_cores(&[])?;
+        let ret =
+            unsafe { libc::sched_getaffinity(self.0, std::mem::size_of::<cpu_set_t>(), &mut set) };
+        if ret != 0 {
+            return Err(Error::OS(errno()));
+        }
+        Ok(cores_from_cpu_set(&set))
+    }
+
+    /// Confines the thread to the provided CPUs, see [`set_thread_affinity`].
+    pub fn set_affinity(self, cores: &[usize]) -> Result<(), Error> {
+        validate_allowed_cpus(cores)?;
+        let set = cpu_set_from_cores(cores)?;
+        let ret =
+            unsafe { libc::sched_setaffinity(self.0, std::mem::size_of::<cpu_set_t>(), &set) };
+        if ret != 0 {
+            return Err(Error::OS(errno()));
+        }
+        Ok(())
+    }
+
+    /// Returns the CPU time the thread has consumed so far, see
+    /// [`get_current_thread_cpu_time`].
+    pub fn cpu_time(self) -> Result<Duration, Error> {
+        // The per-thread CPU clock id as made by the kernel's `MAKE_THREAD_CPUCLOCK`
+        // with `CPUCLOCK_SCHED`.
+        let clock = (!self.0 << 3) | 6;
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        if unsafe { libc::clock_gettime(clock as libc::clockid_t, &mut time) } != 0 {
+            return Err(Error::OS(errno()));
+        }
+        Ok(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+    }
+
+    /// Returns the scheduling attributes of the thread.
+    pub fn scheduling_attributes(self) -> Result<SchedAttr, Error> {
+        sched_getattr(self.0)
+    }
+
+    /// Returns the utilization clamp values of the thread, see
+    /// [`get_thread_util_clamp`].
+    pub fn util_clamp(self) -> Result<(u32, u32), Error> {
+        let attributes = sched_getattr(self.0)?;
+        Ok((attributes.sched_util_min, attributes.sched_util_max))
+    }
+
+    /// Sets the utilization clamp values of the thread, see
+    /// [`set_thread_util_clamp`].
+    pub fn set_util_clamp(self, min: u32, max: u32) -> Result<(), Error> {
+        set_util_clamp(self.0, min, max)
+    }
+
+    /// Returns the I/O priority of the thread, see [`get_thread_io_priority`].
+    pub fn io_priority(self) -> Result<IoPriority, Error> {
+        get_io_priority(self.0)
+    }
+
+    /// Sets the I/O priority of the thread, see [`set_thread_io_priority`].
+    ///
+    /// * May require privileges
+    pub fn set_io_priority(self, priority: IoPriority) -> Result<(), Error> {
+        set_io_priority(self.0, priority)
+    }
+
+    /// Returns the round-robin quantum of the thread, see
+    /// [`get_thread_round_robin_interval`].
+    pub fn round_robin_interval(self) -> Result<Duration, Error> {
+        get_round_robin_interval(self.0)
+    }
+
+    /// Sets the thread's schedule policy to
+    /// [`RealtimeThreadSchedulePolicy::Deadline`] using the provided
+    /// parameters, see [`set_thread_deadline_parameters`].
+    ///
+    /// * May require privileges
+    pub fn set_deadline_parameters(self, parameters: &DeadlineParameters) -> Result<(), Error> {
+        sched_setattr(self.0, &parameters.to_sched_attr()?)
+    }
+
+    /// Returns the priority of the thread together with its policy and the
+    /// crossplatform value it corresponds to. Unlike
+    /// [`get_thread_interpreted_priority`], this works for any thread.
+    pub fn interpreted_priority(self) -> Result<InterpretedPriority, Error> {
+        let attributes = sched_getattr(self.0)?;
+        let policy = ThreadSchedulePolicy::from_posix(attributes.sched_policy as libc::c_int)?;
+        let raw = match policy {
+            ThreadSchedulePolicy::Normal(_) => attributes.sched_nice,
+            ThreadSchedulePolicy::Realtime(_) => attributes.sched_priority as libc::c_int,
+        };
+        Ok(InterpretedPriority::new(policy, raw))
+    }
+
+    /// Sets the priority and the schedule policy of the thread using
+    /// `sched_setattr(2)`, see [`set_thread_priority_and_policy`].
+    ///
+    /// * May require privileges
+    pub fn set_priority_and_policy(
+        self,
+        priority: ThreadPriority,
+        policy: ThreadSchedulePolicy,
+    ) -> Result<(), Error> {
+        let sched_attr = match (policy, priority) {
+            (
+                ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline),
+                ThreadPriority::Deadline {
+                    runtime,
+                    deadline,
+                    period,
+                    flags,
+                },
+            ) => DeadlineParameters::new(runtime, deadline, period)
+                .with_flags(flags)
+                .to_sched_attr()?,
+            (ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline), _) => {
+                return Err(Error::Priority(
+                    "Deadline policy given without deadline priority.",
+                ))
+            }
+            (ThreadSchedulePolicy::Realtime(_), _) => SchedAttr {
+                size: std::mem::size_of::<SchedAttr>() as u32,
+                sched_policy: policy.to_posix() as u32,
+                sched_priority: priority.to_posix(policy)? as u32,
+                ..Default::default()
+            },
+            (ThreadSchedulePolicy::Normal(_), _) => SchedAttr {
+                size: std::mem::size_of::<SchedAttr>() as u32,
+                sched_policy: policy.to_posix() as u32,
+                sched_nice: priority.to_posix(policy)?,
+                ..Default::default()
+            },
+        };
+        sched_setattr(self.0, &sched_attr)
+    }
+
+    /// Returns the scheduling statistics of the thread, see
+    /// [`get_thread_sched_stats`].
+    pub fn sched_stats(self) -> Result<ThreadSchedStats, Error> {
+        get_thread_sched_stats(self)
+    }
+}
+
+/// The scheduling statistics of a thread as accounted by the kernel, see
+/// [`get_thread_sched_stats`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ThreadSchedStats {
+    /// The time the thread has run on a CPU.
+    pub runtime: Duration,
+    /// The time the thread has waited on a run queue for a CPU, while it was
+    /// runnable but some other thread was running.
+    pub wait_time: Duration,
+    /// The number of times the thread has been run on a CPU.
+    pub timeslices: u64,
+    /// The number of context switches of the thread.
+    pub nr_switches: u64,
+    /// The number of times the thread has given up the CPU itself, for
+    /// example, to sleep or to wait for the I/O.
+    pub nr_voluntary_switches: u64,
+    /// The number of times the thread has been preempted.
+    pub nr_involuntary_switches: u64,
+}
+
+/// Returns the scheduling statistics of a thread of any process, which show
+/// whether the thread gets the CPU when it needs to: a thread with a high
+/// enough priority rarely waits for a CPU and is rarely preempted.
+///
+/// The times are read from `/proc/<pid>/task/<tid>/schedstat`, which requires
+/// a kernel built with `CONFIG_SCHED_INFO`. The switches are read from
+/// `/proc/<pid>/task/<tid>/sched`, or from the `status` file of the thread
+/// when the kernel doesn't expose the former.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let stats = get_thread_sched_stats(KernelThreadId::current()).unwrap();
+/// println!("The thread has waited for a CPU for {:?}", stats.wait_time);
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn get_thread_sched_stats(tid: KernelThreadId) -> Result<ThreadSchedStats, Error> {
+    // Any thread is reachable as a process with its own id, which the task
+    // directory of is that of the whole thread group.
+    let task = format!("/proc/{0}/task/{0}", tid.0);
+    let schedstat = std::fs::read_to_string(format!("{}/schedstat", task)).map_err(io_error)?;
+    let mut fields = schedstat.split_whitespace().map(str::parse::<u64>);
+    let mut field = || {
+        fields
+            .next()
+            .and_then(Result::ok)
+            .ok_or(Error::Ffi("Couldn't parse the scheduling statistics."))
+    };
+    let mut stats = ThreadSchedStats {
+        runtime: Duration::from_nanos(field()?),
+        wait_time: Duration::from_nanos(field()?),
+        timeslices: field()?,
+        ..Default::default()
+    };
+
+    let (sched, voluntary, involuntary) = match std::fs::read_to_string(format!("{}/sched", task)) {
+        Ok(sched) => (sched, "nr_voluntary_switches", "nr_involuntary_switches"),
+        Err(_) => (
+            std::fs::read_to_string(format!("{}/status", task)).map_err(io_error)?,
+            "voluntary_ctxt_switches",
+            "nonvoluntary_ctxt_switches",
+        ),
+    };
+    for line in sched.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        let value = match value.parse() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        if key == voluntary {
+            stats.nr_voluntary_switches = value;
+        } else if key == involuntary {
+            stats.nr_involuntary_switches = value;
+        }
+    }
+    stats.nr_switches = stats.nr_voluntary_switches + stats.nr_involuntary_switches;
+    Ok(stats)
+}
+
+/// A thread priority of the Android framework, as the `THREAD_PRIORITY_*`
+/// constants of `android.os.Process`. The priorities are listed from the
+/// lowest to the highest one.
+///
+/// The framework applies them as the niceness of the thread, see
+/// [`set_current_thread_android_priority`].
+#[cfg(target_os = "android")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AndroidThreadPriority {
+    /// `THREAD_PRIORITY_LOWEST`, the work which may be deferred indefinitely.
+    Lowest,
+    /// `THREAD_PRIORITY_BACKGROUND`, the work which doesn't affect the
+    /// responsiveness of the user interface.
+    Background,
+    /// `THREAD_PRIORITY_DEFAULT`, the priority of the application threads.
+    Default,
+    /// `THREAD_PRIORITY_FOREGROUND`, the threads of the foreground
+    /// applications.
+    Foreground,
+    /// `THREAD_PRIORITY_DISPLAY`, the threads updating the user interface.
+    Display,
+    /// `THREAD_PRIORITY_URGENT_DISPLAY`, the threads of the most important
+    /// display work, like the input handling.
+    UrgentDisplay,
+    /// `THREAD_PRIORITY_VIDEO`, the threads playing the video.
+    Video,
+    /// `THREAD_PRIORITY_AUDIO`, the threads playing the audio.
+    Audio,
+    /// `THREAD_PRIORITY_URGENT_AUDIO`, the threads of the most important audio
+    /// work.
+    UrgentAudio,
+}
+
+#[cfg(target_os = "android")]
+impl AndroidThreadPriority {
+    /// Returns the niceness the priority stands for.
+    pub fn niceness(self) -> libc::c_int {
+        match self {
+            AndroidThreadPriority::Lowest => 19,
+            AndroidThreadPriority::Background => 10,
+            AndroidThreadPriority::Default => 0,
+            AndroidThreadPriority::Foreground => -2,
+            AndroidThreadPriority::Display => -4,
+            AndroidThreadPriority::UrgentDisplay => -8,
+            AndroidThreadPriority::Video => -10,
+            AndroidThreadPriority::Audio => -16,
+            AndroidThreadPriority::UrgentAudio => -19,
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+impl TryFrom<libc::c_int> for AndroidThreadPriority {
+    type Error = Error;
+
+    fn try_from(niceness: libc::c_int) -> Result<Self, Self::Error> {
+        [
+            AndroidThreadPriority::Lowest,
+            AndroidThreadPriority::Background,
+            AndroidThreadPriority::Default,
+            AndroidThreadPriority::Foreground,
+            AndroidThreadPriority::Display,
+            AndroidThreadPriority::UrgentDisplay,
+            AndroidThreadPriority::Video,
+            AndroidThreadPriority::Audio,
+            AndroidThreadPriority::UrgentAudio,
+        ]
+        .into_iter()
+        .find(|priority| priority.niceness() == niceness)
+        .ok_or(Error::Priority(
+            "The niceness doesn't match any of the Android thread priorities.",
+        ))
+    }
+}
+
+/// Sets the Android framework priority of the thread the same way
+/// `Process.setThreadPriority` does: by setting the niceness of the thread.
+///
+/// Unlike the framework, the thread isn't moved into the background cgroup
+/// for [`AndroidThreadPriority::Background`] and the lower priorities.
+///
+/// * May require privileges to decrease the niceness
+#[cfg(target_os = "android")]
+pub fn set_android_thread_priority(
+    tid: KernelThreadId,
+    priority: AndroidThreadPriority,
+) -> Result<(), Error> {
+    tid.set_niceness(priority.niceness())
+}
+
+/// Sets the Android framework priority of the current thread.
+///
+/// This is a short-hand of the [`set_android_thread_priority`] function for
+/// the current thread.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(set_current_thread_android_priority(AndroidThreadPriority::Background).is_ok());
+/// ```
+#[cfg(target_os = "android")]
+pub fn set_current_thread_android_priority(priority: AndroidThreadPriority) -> Result<(), Error> {
+    set_android_thread_priority(KernelThreadId::current(), priority)
+}
+
+/// Returns the Android framework priority of the thread, like
+/// `Process.getThreadPriority`. The niceness not matching any of the
+/// priorities results in [`Error::Priority`].
+#[cfg(target_os = "android")]
+pub fn get_android_thread_priority(tid: KernelThreadId) -> Result<AndroidThreadPriority, Error> {
+    tid.niceness()?.try_into()
+}
+
+/// The `SCHED_FIFO` priority the Android audio framework gives to the audio
+/// callback threads of the applications.
+#[cfg(target_os = "android")]
+const AUDIO_THREAD_FIFO_PRIORITY: u32 = 2;
+
+/// Promotes the current thread to an audio thread: sets the `SCHED_FIFO`
+/// policy with the priority the Android audio framework uses for the audio
+/// callback threads and, as most of the applications aren't allowed the
+/// realtime policies, falls back to [`AndroidThreadPriority::UrgentAudio`].
+///
+/// Returns the policy the thread is scheduled with afterwards. If neither
+/// could be set, the error of the fallback is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// std::thread::spawn(|| {
+///     let policy = promote_to_audio_thread().unwrap();
+///     println!("Rendering the audio with {policy:?}");
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[cfg(target_os = "android")]
+pub fn promote_to_audio_thread() -> Result<ThreadSchedulePolicy, Error> {
+    let fifo = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    let priority = ThreadPriority::Os(crate::ThreadPriorityOsValue(AUDIO_THREAD_FIFO_PRIORITY));
+    if set_thread_priority_and_policy(thread_native_id(), priority, fifo).is_ok() {
+        return Ok(fifo);
+    }
+    set_current_thread_android_priority(AndroidThreadPriority::UrgentAudio)?;
+    thread_schedule_policy()
+}
+
+/// Checks whether the process has the `CAP_SYS_NICE` capability in its
+/// effective set.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn has_cap_sys_nice() -> Result<bool, Error> {
+    const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+    const CAP_SYS_NICE: u32 = 23;
+
+    #[repr(C)]
+    struct CapHeader {
+        version: u32,
+        pid: libc::c_int,
+    }
+
+    #[repr(C)]
+    #[derive(Copy, Clone, Default)]
+    struct CapData {
+        effective: u32,
+        permitted: u32,
+        inheritable: u32,
+    }
+
+    let mut header = CapHeader {
+        version: LINUX_CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let mut data = [CapData::default(); 2];
+    let ret = unsafe { libc::syscall(libc::SYS_capget, &mut header, data.as_mut_ptr()) };
+    if ret != 0 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(data[0].effective & (1 << CAP_SYS_NICE) != 0)
+}
+
+/// The type of the resources accepted by `getrlimit`, which depends on the libc.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    not(all(target_os = "linux", target_env = "gnu"))
+))]
+type RlimitResource = libc::c_int;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn soft_resource_limit(resource: RlimitResource) -> Result<libc::rlim_t, Error> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(limit.rlim_cur)
+}
+
+/// Checks whether the current thread may switch to a realtime policy, so that
+/// the scheduling strategy can be chosen without probing for `EPERM`.
+///
+/// On Linux, this is the case when the process has the `CAP_SYS_NICE`
+/// capability or a non-zero `RLIMIT_RTPRIO` limit, which then also caps the
+/// allowed static priority. On the other platforms, the process has to be run
+/// by the superuser.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let policy = if can_set_realtime_priority().unwrap() {
+///     ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo)
+/// } else {
+///     ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other)
+/// };
+/// ```
+pub fn can_set_realtime_priority() -> Result<bool, Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(any(target_os = "linux", target_os = "android"))] {
+            Ok(has_cap_sys_nice()? || soft_resource_limit(libc::RLIMIT_RTPRIO)? > 0)
+        } else {
+            Ok(unsafe { libc::geteuid() } == 0)
+        }
+    }
+}
+
+/// Checks whether the current thread may lower its niceness, which raises its
+/// priority under the normal policies.
+///
+/// On Linux, this is the case when the process has the `CAP_SYS_NICE`
+/// capability or its `RLIMIT_NICE` limit allows a niceness lower than the
+/// current one. On the other platforms, the process has to be run by the
+/// superuser.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(can_lower_niceness().is_ok());
+/// ```
+pub fn can_lower_niceness() -> Result<bool, Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(any(target_os = "linux", target_os = "android"))] {
+            if has_cap_sys_nice()? {
+                return Ok(true);
+            }
+            // The limit allows the niceness down to `20 - limit`.
+            let lowest = 20 - soft_resource_limit(libc::RLIMIT_NICE)?.min(40) as libc::c_int;
+            Ok(lowest < KernelThreadId::current().niceness()?)
+        } else {
+            Ok(unsafe { libc::geteuid() } == 0)
+        }
+    }
+}
+
+/// The budget of the realtime threads, which the kernel stops running, or
+/// throttles, once they have run for the `runtime` within the `period`, so
+/// that a runaway realtime thread can't lock up the system. See
+/// [`realtime_throttling`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RealtimeThrottling {
+    /// The time the realtime threads may run for within each period, or
+    /// `None` if they aren't throttled, from `sched_rt_runtime_us`.
+    pub runtime: Option<Duration>,
+    /// The period the runtime is accounted over, from `sched_rt_period_us`.
+    pub period: Duration,
+    /// The runtime of the `cpu` cgroup of the process, from its
+    /// `cpu.rt_runtime_us`, or `None` if the realtime threads aren't
+    /// accounted per cgroup. With the zero runtime, the threads of the
+    /// process can't switch to the realtime policies even when privileged.
+    pub cgroup_runtime: Option<Duration>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl RealtimeThrottling {
+    /// Returns whether the realtime threads are throttled.
+    pub fn is_throttled(&self) -> bool {
+        self.runtime.is_some()
+    }
+
+    /// Returns the share of the CPU time the realtime threads may use, which
+    /// is `0.95` by default, or `1.0` if they aren't throttled.
+    pub fn share(&self) -> f64 {
+        match self.runtime {
+            Some(runtime) if !self.period.is_zero() => {
+                runtime.as_secs_f64() / self.period.as_secs_f64()
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+/// Returns the budget of the realtime threads.
+///
+/// The throttling is a common reason for a `SCHED_FIFO` thread to stutter:
+/// once the realtime threads have used up the budget, they don't run until
+/// the next period, however high their priorities are.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let throttling = realtime_throttling().unwrap();
+/// if throttling.is_throttled() {
+///     println!("The realtime threads may use {}% of the CPU time", throttling.share() * 100.0);
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn realtime_throttling() -> Result<RealtimeThrottling, Error> {
+    let (runtime, period) = realtime_budget()?;
+    Ok(RealtimeThrottling {
+        runtime,
+        period,
+        cgroup_runtime: cgroup_realtime_runtime(),
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_micros(path: &str) -> Option<i64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn micros(micros: i64) -> Option<Duration> {
+    u64::try_from(micros).ok().map(Duration::from_micros)
+}
+
+/// Returns the system-wide runtime and period of the realtime threads, see
+/// [`RealtimeThrottling`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn realtime_budget() -> Result<(Option<Duration>, Duration), Error> {
+    let period = read_micros("/proc/sys/kernel/sched_rt_period_us")
+        .ok_or(Error::Ffi("Couldn't read the realtime throttling period."))?;
+    let runtime = read_micros("/proc/sys/kernel/sched_rt_runtime_us")
+        .ok_or(Error::Ffi("Couldn't read the realtime throttling runtime."))?;
+    Ok((micros(runtime), micros(period).unwrap_or_default()))
+}
+
+/// Returns the realtime runtime of the `cpu` cgroup of the process, see
+/// [`RealtimeThrottling::cgroup_runtime`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cgroup_realtime_runtime() -> Option<Duration> {
+    // The realtime runtime is only accounted per cgroup in the v1 hierarchy
+    // with `CONFIG_RT_GROUP_SCHED`.
+    let cgroups = std::fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
+    cgroups
+        .lines()
+        .find_map(|line| {
+            let mut fields = line.splitn(3, ':');
+            let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+            controllers
+                .split(',')
+                .any(|c| c == "cpu")
+                .then(|| format!("/sys/fs/cgroup/cpu{}/cpu.rt_runtime_us", path))
+        })
+        .and_then(|path| read_micros(&path))
+        .and_then(micros)
+}
+
+/// Logs the realtime throttling along with the result of switching a thread
+/// to a realtime policy: why the switch has failed if it is because of the
+/// cgroup, or, once per process, that the realtime threads are throttled.
+///
+/// The files are only read when there is something to report: the cgroup on
+/// `EPERM` and the budget until it has been reported. The default budget of
+/// 950ms per second is only logged at the debug level.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn report_realtime_throttling(ret: libc::c_int) {
+    static THROTTLING_REPORTED: std::sync::Once = std::sync::Once::new();
+    const DEFAULT_RUNTIME: Duration = Duration::from_millis(950);
+    const DEFAULT_PERIOD: Duration = Duration::from_secs(1);
+
+    if ret == libc::EPERM {
+        if cgroup_realtime_runtime() == Some(Duration::ZERO) {
+            log::warn!(
+                "Couldn't switch the thread to a realtime policy, as the cgroup of the \
+                 process has no realtime runtime (cpu.rt_runtime_us is 0)."
+            );
+        }
+        return;
+    }
+    if ret != 0 || THROTTLING_REPORTED.is_completed() {
+        return;
+    }
+    let throttling = match realtime_budget() {
+        Ok((runtime, period)) => RealtimeThrottling {
+            runtime,
+            period,
+            cgroup_runtime: None,
+        },
+        Err(_) => return,
+    };
+    if !throttling.is_throttled() {
+        return;
+    }
+    THROTTLING_REPORTED.call_once(|| {
+        let default =
+            throttling.runtime == Some(DEFAULT_RUNTIME) && throttling.period == DEFAULT_PERIOD;
+        let level = if default {
+            log::Level::Debug
+        } else {
+            log::Level::Warn
+        };
+        log::log!(
+            level,
+            "The realtime threads are throttled to {:.0}% of the CPU time, \
+             see /proc/sys/kernel/sched_rt_runtime_us.",
+            throttling.share() * 100.0
+        )
+    });
+}
+
+/// Locks all the pages of the process into the RAM, including the ones mapped
+/// later, like the stacks of the threads spawned afterwards, with
+/// `mlockall(MCL_CURRENT | MCL_FUTURE)`.
+///
+/// A realtime thread still misses its deadlines when its memory is paged out,
+/// as the page faults are served at the speed of the storage.
+///
+/// * May require privileges or a sufficient `RLIMIT_MEMLOCK` limit
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::*;
+///
+/// assert!(lock_memory().is_ok());
+/// // The realtime work.
+/// assert!(unlock_memory().is_ok());
+/// ```
+pub fn lock_memory() -> Result<(), Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "vxworks")] {
+            Err(Error::OS(libc::ENOSYS))
+        } else {
+            do_with_errno(|| unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) })
+                .map(|_| ())
+        }
+    }
+}
+
+/// Unlocks all the pages of the process locked by [`lock_memory`], with
+/// `munlockall`.
+pub fn unlock_memory() -> Result<(), Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "vxworks")] {
+            Err(Error::OS(libc::ENOSYS))
+        } else {
+            do_with_errno(|| unsafe { libc::munlockall() }).map(|_| ())
+        }
+    }
+}
+
+/// The scheduling class of the FreeBSD and DragonFly `rtprio(2)` interface.
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum RtprioClass {
+    /// `RTP_PRIO_REALTIME`: the thread runs before all the time-shared ones.
+    Realtime,
+    /// `RTP_PRIO_NORMAL`: the regular time-shared scheduling, controlled by the
+    /// niceness.
+    Normal,
+    /// `RTP_PRIO_IDLE`: the thread only runs when no other thread is runnable.
+    Idle,
+}
+
+/// The scheduling class and the level within it as used by the FreeBSD and
+/// DragonFly `rtprio(2)` interface, which is the way the kernel actually prioritizes the
+/// realtime and the idle threads. The POSIX realtime policies are mapped onto
+/// it by the kernel.
+///
+/// The level is in range of `[0; 31]`, where `0` is the highest priority,
+/// unlike the POSIX priorities. It is ignored for [`RtprioClass::Normal`].
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Rtprio {
+    /// The scheduling class.
+    pub class: RtprioClass,
+    /// The level within the class, where `0` is the highest priority.
+    pub level: u16,
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+impl Rtprio {
+    fn into_raw(self) -> Result<libc::rtprio, Error> {
+        let levels = libc::RTP_PRIO_MIN..=libc::RTP_PRIO_MAX;
+        if !levels.contains(&self.level) {
+            return Err(Error::PriorityNotInRange(
+                libc::RTP_PRIO_MIN as i32..=libc::RTP_PRIO_MAX as i32,
+            ));
+        }
+        let type_ = match self.class {
+            RtprioClass::Realtime => libc::RTP_PRIO_REALTIME,
+            RtprioClass::Normal => libc::RTP_PRIO_NORMAL,
+            RtprioClass::Idle => libc::RTP_PRIO_IDLE,
+        };
+        Ok(libc::rtprio {
+            type_,
+            prio: self.level,
+        })
+    }
+
+    fn from_raw(raw: libc::rtprio) -> Result<Self, Error> {
+        // `RTP_PRIO_FIFO`, missing in `libc`, is reported for `SCHED_FIFO`.
+        #[cfg(target_os = "freebsd")]
+        const RTP_PRIO_FIFO: libc::c_ushort = libc::RTP_PRIO_REALTIME | 8;
+        #[cfg(target_os = "dragonfly")]
+        const RTP_PRIO_FIFO: libc::c_ushort = libc::RTP_PRIO_REALTIME | 4;
+        let class = match raw.type_ {
+            libc::RTP_PRIO_REALTIME | RTP_PRIO_FIFO => RtprioClass::Realtime,
+            libc::RTP_PRIO_NORMAL => RtprioClass::Normal,
+            libc::RTP_PRIO_IDLE => RtprioClass::Idle,
+            _ => return Err(Error::Ffi("Unknown rtprio class.")),
+        };
+        Ok(Self {
+            class,
+            level: raw.prio,
+        })
+    }
+}
+
+/// Returns the id of the current thread as used by the FreeBSD kernel, which
+/// is what [`set_thread_rtprio`] accepts. Wraps `pthread_getthreadid_np`.
+#[cfg(target_os = "freebsd")]
+pub fn current_thread_lwpid() -> libc::lwpid_t {
+    unsafe { libc::pthread_getthreadid_np() }
+}
+
+/// Returns the id of the LWP of the current thread as used by the DragonFly
+/// kernel, which is what [`set_thread_rtprio`] accepts. Wraps `lwp_gettid`.
+#[cfg(target_os = "dragonfly")]
+pub fn current_thread_lwpid() -> libc::lwpid_t {
+    unsafe { lwp_gettid() }
+}
+
+/// The kernel id standing for the current thread.
+#[cfg(target_os = "freebsd")]
+const CURRENT_LWPID: libc::lwpid_t = 0;
+#[cfg(target_os = "dragonfly")]
+const CURRENT_LWPID: libc::lwpid_t = -1;
+
+/// Sets the `rtprio(2)` class and level of the thread with the kernel id, see
+/// [`current_thread_lwpid`]. The id `0` on FreeBSD and `-1` on DragonFly
+/// stands for the current thread.
+///
+/// On DragonFly, this is `lwp_rtprio`, which controls the LWP of the thread
+/// rather than the whole process.
+///
+/// Setting the realtime class requires the superuser privileges, as well as
+/// raising the level of the idle class.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::*;
+///
+/// let rtprio = Rtprio {
+///     class: RtprioClass::Idle,
+///     level: 31,
+/// };
+/// assert!(set_thread_rtprio(current_thread_lwpid(), rtprio).is_ok());
+/// ```
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub fn set_thread_rtprio(lwpid: libc::lwpid_t, rtprio: Rtprio) -> Result<(), Error> {
+    let mut raw = rtprio.into_raw()?;
+    do_with_errno(|| unsafe {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "freebsd")] {
+                libc::rtprio_thread(libc::RTP_SET, lwpid, &mut raw)
+            } else {
+                libc::lwp_rtprio(libc::RTP_SET, 0, lwpid, &mut raw)
+            }
+        }
+    })
+    .map(|_| ())
+}
+
+/// Returns the `rtprio(2)` class and level of the thread with the kernel id,
+/// see [`set_thread_rtprio`].
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub fn get_thread_rtprio(lwpid: libc::lwpid_t) -> Result<Rtprio, Error> {
+    let mut raw = libc::rtprio { type_: 0, prio: 0 };
+    do_with_errno(|| unsafe {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "freebsd")] {
+                libc::rtprio_thread(libc::RTP_LOOKUP, lwpid, &mut raw)
+            } else {
+                libc::lwp_rtprio(libc::RTP_LOOKUP, 0, lwpid, &mut raw)
+            }
+        }
+    })?;
+    Rtprio::from_raw(raw)
+}
+
+/// Sets the `rtprio(2)` class and level of the current thread.
+///
+/// This is a short-hand of the [`set_thread_rtprio`] function for the current
+/// thread.
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub fn set_current_thread_rtprio(rtprio: Rtprio) -> Result<(), Error> {
+    set_thread_rtprio(CURRENT_LWPID, rtprio)
+}
+
+/// Returns the `rtprio(2)` class and level of the current thread.
+///
+/// This is a short-hand of the [`get_thread_rtprio`] function for the current
+/// thread.
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub fn get_current_thread_rtprio() -> Result<Rtprio, Error> {
+    get_thread_rtprio(CURRENT_LWPID)
+}
+
+/// Returns the numerator and the denominator converting the mach absolute time
+/// units into nanoseconds.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[allow(deprecated)]
+fn mach_timebase() -> (u64, u64) {
+    let mut timebase = libc::mach_timebase_info { numer: 0, denom: 0 };
+    let ret = unsafe { libc::mach_timebase_info(&mut timebase) };
+    if ret != 0 || timebase.numer == 0 || timebase.denom == 0 {
+        // The timebase never fails in practice, and it is 1:1 on Intel.
+        return (1, 1);
+    }
+    (timebase.numer as u64, timebase.denom as u64)
+}
+
+/// Converts the duration into the mach absolute time units, used by the
+/// [`set_thread_time_constraint_policy`] function.
+///
+/// Returns an error if the duration doesn't fit into 32 bits of the units.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn duration_to_mach_time(duration: Duration) -> Result<u32, Error> {
+    let (numer, denom) = mach_timebase();
+    let units = duration.as_nanos() * denom as u128 / numer as u128;
+    u32::try_from(units).map_err(|_| Error::Priority("The duration is too long."))
+}
+
+/// Converts the mach absolute time units into the duration.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn mach_time_to_duration(units: u32) -> Duration {
+    let (numer, denom) = mach_timebase();
+    Duration::from_nanos(units as u64 * numer / denom)
+}
+
+/// Returns the Mach port of the thread, which identifies the thread in the
+/// Mach APIs, like `thread_policy_set`. Wraps `pthread_mach_thread_np`.
+///
+/// The port is owned by the thread and stays valid while the thread is alive.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let port = thread_mach_port(thread_native_id());
+/// assert_eq!(thread_from_mach_port(port), Some(thread_native_id()));
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn thread_mach_port(native: ThreadId) -> libc::mach_port_t {
+    unsafe { libc::pthread_mach_thread_np(native) }
+}
+
+/// Returns the thread having the Mach port, or `None` if there is no such
+/// thread among the threads of the process created with `pthread`. Wraps
+/// `pthread_from_mach_thread_np`.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn thread_from_mach_port(port: libc::mach_port_t) -> Option<ThreadId> {
+    let native = unsafe { libc::pthread_from_mach_thread_np(port) };
+    (native != 0 as ThreadId).then_some(native)
+}
+
+/// Returns the system-wide unique id of the thread, which is the one shown by
+/// the tools like Instruments and `spindump`. Wraps `pthread_threadid_np`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert_ne!(thread_global_id(thread_native_id()), Ok(0));
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn thread_global_id(native: ThreadId) -> Result<u64, Error> {
+    let mut id = 0;
+    match unsafe { libc::pthread_threadid_np(native, &mut id) } {
+        0 => Ok(id),
+        e => Err(Error::OS(e)),
+    }
+}
+
+/// Sets the time constraint policy for the thread, which is the way to get
+/// the real-time scheduling on macOS, for example, for the audio callbacks.
+///
+/// * `period` is the nominal amount of time between the separate processing
+///   arrivals, or zero if there is no inherent periodicity.
+/// * `computation` is the amount of computation time needed during each
+///   period.
+/// * `constraint` is the maximum amount of real time that may elapse from the
+///   start of the period to the end of the computation.
+/// * `preemptible` tells whether the computation may be interrupted.
+///
+/// The `computation` must not exceed the `constraint`. Check out
+/// `thread_policy_set` and the "Mach Scheduling and Thread Interfaces" Apple
+/// documentation for more info.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use thread_priority::*;
+///
+/// assert!(set_thread_time_constraint_policy(
+///     thread_native_id(),
+///     Duration::from_millis(10),
+///     Duration::from_millis(2),
+///     Duration::from_millis(5),
+///     true,
+/// )
+/// .is_ok());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_thread_time_constraint_policy(
+    native: ThreadId,
+    period: Duration,
+    computation: Duration,
+    constraint: Duration,
+    preemptible: bool,
+) -> Result<(), Error> {
+    if computation > constraint {
+        return Err(Error::Priority(
+            "The computation time must not exceed the constraint.",
+        ));
+    }
+    let policy = libc::thread_time_constraint_policy {
+        period: duration_to_mach_time(period)?,
+        computation: duration_to_mach_time(computation)?,
+        constraint: duration_to_mach_time(constraint)?,
+        preemptible: preemptible as libc::boolean_t,
+    };
+    thread_policy_set(
+        native,
+        libc::THREAD_TIME_CONSTRAINT_POLICY,
+        libc::THREAD_TIME_CONSTRAINT_POLICY_COUNT,
+        policy,
+    )
+}
+
+/// Sets the time constraint policy for the current thread.
+///
+/// This is a short-hand of the [`set_thread_time_constraint_policy`] function
+/// for the current thread.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_current_thread_time_constraint_policy(
+    period: Duration,
+    computation: Duration,
+    constraint: Duration,
+    preemptible: bool,
+) -> Result<(), Error> {
+    set_thread_time_constraint_policy(
+        thread_native_id(),
+        period,
+        computation,
+        constraint,
+        preemptible,
+    )
+}
+
+/// Resets the thread to the standard Mach policy, the time-shared scheduling
+/// of the threads without any special needs, undoing
+/// [`set_thread_time_constraint_policy`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(set_thread_standard_policy(thread_native_id()).is_ok());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_thread_standard_policy(native: ThreadId) -> Result<(), Error> {
+    thread_policy_set(
+        native,
+        libc::THREAD_STANDARD_POLICY,
+        libc::THREAD_STANDARD_POLICY_COUNT as libc::mach_msg_type_number_t,
+        libc::thread_standard_policy { no_data: 0 },
+    )
+}
+
+/// The extended policy of a Mach thread, see [`get_thread_extended_policy`].
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ThreadExtendedPolicy {
+    /// Whether the thread is time-shared with the others rather than having
+    /// a fixed priority.
+    pub timeshare: bool,
+    /// Whether the policy hasn't been set for the thread, so the defaults are
+    /// returned.
+    pub default: bool,
+}
+
+/// The precedence policy of a Mach thread, see
+/// [`get_thread_precedence_policy`].
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ThreadPrecedencePolicy {
+    /// The importance of the thread relative to the other threads of the
+    /// process.
+    pub importance: i32,
+    /// Whether the policy hasn't been set for the thread, so the defaults are
+    /// returned.
+    pub default: bool,
+}
+
+/// The time constraint policy of a Mach thread, see
+/// [`set_thread_time_constraint_policy`] for the meaning of the fields.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ThreadTimeConstraintPolicy {
+    /// The nominal amount of time between the separate processing arrivals.
+    pub period: Duration,
+    /// The amount of computation time needed during each period.
+    pub computation: Duration,
+    /// The maximum amount of real time from the start of the period to the end
+    /// of the computation.
+    pub constraint: Duration,
+    /// Whether the computation may be interrupted.
+    pub preemptible: bool,
+    /// Whether the policy hasn't been set for the thread, so the defaults are
+    /// returned.
+    pub default: bool,
+}
+
+/// Sets the Mach policy of the flavor.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+fn thread_policy_set<T>(
+    native: ThreadId,
+    flavor: libc::c_int,
+    count: libc::mach_msg_type_number_t,
+    mut policy: T,
+) -> Result<(), Error> {
+    let ret = unsafe {
+        libc::thread_policy_set(
+            thread_mach_port(native),
+            flavor as libc::thread_policy_flavor_t,
+            &mut policy as *mut T as libc::thread_policy_t,
+            count,
+        )
+    };
+    if ret == libc::KERN_SUCCESS {
+        Ok(())
+    } else {
+        Err(Error::OS(ret))
+    }
+}
+
+/// Reads the Mach policy of the flavor, returning it along with whether it is
+/// the default one.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+fn thread_policy_get<T>(
+    native: ThreadId,
+    flavor: libc::c_int,
+    count: libc::mach_msg_type_number_t,
+    mut policy: T,
+) -> Result<(T, bool), Error> {
+    let mut count = count;
+    // Tells the kernel to return the current policy rather than the default
+    // one, and receives whether the current one is the default.
+    let mut default: libc::boolean_t = 0;
+    let ret = unsafe {
+        libc::thread_policy_get(
+            thread_mach_port(native),
+            flavor as libc::thread_policy_flavor_t,
+            &mut policy as *mut T as libc::thread_policy_t,
+            &mut count,
+            &mut default,
+        )
+    };
+    if ret == libc::KERN_SUCCESS {
+        Ok((policy, default != 0))
+    } else {
+        Err(Error::OS(ret))
+    }
+}
+
+/// Returns the extended policy of the thread, which tells whether the thread
+/// is time-shared.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_thread_extended_policy(thread_native_id()).is_ok());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn get_thread_extended_policy(native: ThreadId) -> Result<ThreadExtendedPolicy, Error> {
+    let (policy, default) = thread_policy_get(
+        native,
+        libc::THREAD_EXTENDED_POLICY,
+        libc::THREAD_EXTENDED_POLICY_COUNT,
+        libc::thread_extended_policy { timeshare: 0 },
+    )?;
+    Ok(ThreadExtendedPolicy {
+        timeshare: policy.timeshare != 0,
+        default,
+    })
+}
+
+/// Returns the precedence policy of the thread, which is its importance
+/// relative to the other threads of the process.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_thread_precedence_policy(thread_native_id()).is_ok());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn get_thread_precedence_policy(native: ThreadId) -> Result<ThreadPrecedencePolicy, Error> {
+    let (policy, default) = thread_policy_get(
+        native,
+        libc::THREAD_PRECEDENCE_POLICY,
+        libc::THREAD_PRECEDENCE_POLICY_COUNT,
+        libc::thread_precedence_policy { importance: 0 },
+    )?;
+    Ok(ThreadPrecedencePolicy {
+        importance: policy.importance,
+        default,
+    })
+}
+
+/// Sets the importance of the thread relative to the other threads of the
+/// process using the precedence policy. The threads with a higher importance
+/// are preferred by the scheduler over the ones with a lower importance, which
+/// allows to order the non-realtime threads on Darwin, where the niceness
+/// applies to the whole process. The default importance is `0`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// std::thread::spawn(|| {
+///     assert!(set_current_thread_importance(-5).is_ok());
+///     let policy = get_thread_precedence_policy(thread_native_id()).unwrap();
+///     assert_eq!(policy.importance, -5);
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_thread_importance(native: ThreadId, importance: i32) -> Result<(), Error> {
+    thread_policy_set(
+        native,
+        libc::THREAD_PRECEDENCE_POLICY,
+        libc::THREAD_PRECEDENCE_POLICY_COUNT,
+        libc::thread_precedence_policy { importance },
+    )
+}
+
+/// Sets the importance of the current thread.
+///
+/// This is a short-hand of the [`set_thread_importance`] function for the
+/// current thread.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_current_thread_importance(importance: i32) -> Result<(), Error> {
+    set_thread_importance(thread_native_id(), importance)
+}
+
+/// Returns the time constraint policy of the thread, see
+/// [`set_thread_time_constraint_policy`].
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_thread_time_constraint_policy(thread_native_id()).is_ok());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn get_thread_time_constraint_policy(
+    native: ThreadId,
+) -> Result<ThreadTimeConstraintPolicy, Error> {
+    let (policy, default) = thread_policy_get(
+        native,
+        libc::THREAD_TIME_CONSTRAINT_POLICY,
+        libc::THREAD_TIME_CONSTRAINT_POLICY_COUNT,
+        libc::thread_time_constraint_policy {
+            period: 0,
+            computation: 0,
+            constraint: 0,
+            preemptible: 0,
+        },
+    )?;
+    Ok(ThreadTimeConstraintPolicy {
+        period: mach_time_to_duration(policy.period),
+        computation: mach_time_to_duration(policy.computation),
+        constraint: mach_time_to_duration(policy.constraint),
+        preemptible: policy.preemptible != 0,
+        default,
+    })
+}
+
+/// Sets the affinity tag of the thread. The threads sharing a tag are hinted to
+/// the scheduler to run on the processors sharing the L2 cache, while the
+/// threads with different tags are spread apart. This is the closest macOS has
+/// to the thread affinity. The tag `0` (`THREAD_AFFINITY_TAG_NULL`) removes the
+/// thread from its affinity set.
+///
+/// The tags are only meaningful within the process. The Apple Silicon machines
+/// don't support the affinity policy and return an error.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::*;
+///
+/// let first = std::thread::spawn(|| set_current_thread_affinity_tag(1));
+/// let second = std::thread::spawn(|| set_current_thread_affinity_tag(1));
+/// assert!(first.join().unwrap().is_ok());
+/// assert!(second.join().unwrap().is_ok());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_thread_affinity_tag(native: ThreadId, tag: u32) -> Result<(), Error> {
+    thread_policy_set(
+        native,
+        libc::THREAD_AFFINITY_POLICY,
+        libc::THREAD_AFFINITY_POLICY_COUNT,
+        libc::thread_affinity_policy {
+            affinity_tag: tag as libc::integer_t,
+        },
+    )
+}
+
+/// Sets the affinity tag of the current thread.
+///
+/// This is a short-hand of the [`set_thread_affinity_tag`] function for the
+/// current thread.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_current_thread_affinity_tag(tag: u32) -> Result<(), Error> {
+    set_thread_affinity_tag(thread_native_id(), tag)
+}
+
+/// Returns the affinity tag of the thread, which is `0` if the thread doesn't
+/// belong to any affinity set, see [`set_thread_affinity_tag`].
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn get_thread_affinity_tag(native: ThreadId) -> Result<u32, Error> {
+    let (policy, _) = thread_policy_get(
+        native,
+        libc::THREAD_AFFINITY_POLICY,
+        libc::THREAD_AFFINITY_POLICY_COUNT,
+        libc::thread_affinity_policy {
+            affinity_tag: libc::THREAD_AFFINITY_TAG_NULL,
+        },
+    )?;
+    Ok(policy.affinity_tag as u32)
+}
+
+/// The quality of service (QoS) class of a thread on Apple platforms, which
+/// tells the system the importance of the thread's work. The classes are
+/// listed from the highest to the lowest one, except for
+/// [`QosClass::Unspecified`].
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QosClass {
+    /// The work interacting with the user, like the UI updates.
+    UserInteractive,
+    /// The work requested by the user and awaited by them.
+    UserInitiated,
+    /// The default class.
+    Default,
+    /// The long-running work the user isn't waiting for immediately.
+    Utility,
+    /// The maintenance work invisible to the user.
+    Background,
+    /// The thread has no class set, for example, because it has been opted
+    /// out of the QoS by setting a POSIX priority.
+    Unspecified,
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl QosClass {
+    /// Returns the Mach base priority the threads of the class are scheduled
+    /// with, before applying the relative priority, or `None` for
+    /// [`QosClass::Unspecified`].
+    pub fn base_priority(self) -> Option<i32> {
+        match self {
+            QosClass::UserInteractive => Some(47),
+            QosClass::UserInitiated => Some(37),
+            QosClass::Default => Some(31),
+            QosClass::Utility => Some(20),
+            QosClass::Background => Some(4),
+            QosClass::Unspecified => None,
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl From<libc::qos_class_t> for QosClass {
+    fn from(class: libc::qos_class_t) -> Self {
+        match class {
+            libc::qos_class_t::QOS_CLASS_USER_INTERACTIVE => QosClass::UserInteractive,
+            libc::qos_class_t::QOS_CLASS_USER_INITIATED => QosClass::UserInitiated,
+            libc::qos_class_t::QOS_CLASS_DEFAULT => QosClass::Default,
+            libc::qos_class_t::QOS_CLASS_UTILITY => QosClass::Utility,
+            libc::qos_class_t::QOS_CLASS_BACKGROUND => QosClass::Background,
+            libc::qos_class_t::QOS_CLASS_UNSPECIFIED => QosClass::Unspecified,
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl From<QosClass> for libc::qos_class_t {
+    fn from(class: QosClass) -> Self {
+        match class {
+            QosClass::UserInteractive => libc::qos_class_t::QOS_CLASS_USER_INTERACTIVE,
+            QosClass::UserInitiated => libc::qos_class_t::QOS_CLASS_USER_INITIATED,
+            QosClass::Default => libc::qos_class_t::QOS_CLASS_DEFAULT,
+            QosClass::Utility => libc::qos_class_t::QOS_CLASS_UTILITY,
+            QosClass::Background => libc::qos_class_t::QOS_CLASS_BACKGROUND,
+            QosClass::Unspecified => libc::qos_class_t::QOS_CLASS_UNSPECIFIED,
+        }
+    }
+}
+
+/// Returns the QoS class of the thread along with its relative priority
+/// within the class, which is in range of `[-15; 0]`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (_class, relative_priority) = get_thread_qos_class(thread_native_id()).unwrap();
+/// assert!((-15..=0).contains(&relative_priority));
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn get_thread_qos_class(native: ThreadId) -> Result<(QosClass, i32), Error> {
+    let mut class = libc::qos_class_t::QOS_CLASS_UNSPECIFIED;
+    let mut relative_priority = 0;
+    let ret = unsafe { libc::pthread_get_qos_class_np(native, &mut class, &mut relative_priority) };
+    match ret {
+        0 => Ok((class.into(), relative_priority)),
+        e => Err(Error::OS(e)),
+    }
+}
+
+/// Returns the QoS class of the current thread along with its relative
+/// priority within the class.
+///
+/// This is a short-hand of the [`get_thread_qos_class`] function for the
+/// current thread.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn get_current_thread_qos_class() -> Result<(QosClass, i32), Error> {
+    get_thread_qos_class(thread_native_id())
+}
+
+/// Sets the QoS class of the current thread along with its relative priority
+/// within the class, which must be in range of `[-15; 0]`. The system only
+/// allows to change the class of the current thread.
+///
+/// Paired with [`get_current_thread_qos_class`], this allows to boost the
+/// thread temporarily and restore its class afterwards.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (class, relative_priority) = get_current_thread_qos_class().unwrap();
+/// assert!(set_current_thread_qos_class(QosClass::UserInteractive, 0).is_ok());
+/// // The time-sensitive work.
+/// if class != QosClass::Unspecified {
+///     assert!(set_current_thread_qos_class(class, relative_priority).is_ok());
+/// }
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_current_thread_qos_class(class: QosClass, relative_priority: i32) -> Result<(), Error> {
+    if !(-15..=0).contains(&relative_priority) {
+        return Err(Error::Priority(
+            "The QoS relative priority must be in range of [-15; 0].",
+        ));
+    }
+    match unsafe { libc::pthread_set_qos_class_self_np(class.into(), relative_priority) } {
+        0 => Ok(()),
+        e => Err(Error::OS(e)),
+    }
+}
+
+/// A guard restoring the QoS class of the current thread when dropped.
+///
+/// The guard is bound to the thread which created it and therefore can't be sent
+/// to other threads.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// {
+///     let _guard = QosClassGuard::raise(QosClass::UserInteractive, 0).unwrap();
+///     // The time-sensitive work.
+/// }
+/// // The previous QoS class is restored here.
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[derive(Debug)]
+pub struct QosClassGuard {
+    class: QosClass,
+    relative_priority: i32,
+    restored: bool,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl QosClassGuard {
+    /// Records the current thread's QoS class and sets the new one.
+    /// For more info read [`set_current_thread_qos_class`].
+    pub fn raise(class: QosClass, relative_priority: i32) -> Result<Self, Error> {
+        let (current, current_relative_priority) = get_current_thread_qos_class()?;
+        set_current_thread_qos_class(class, relative_priority)?;
+        Ok(Self {
+            class: current,
+            relative_priority: current_relative_priority,
+            restored: false,
+            _not_send: std::marker::PhantomData,
+        })
+    }
+
+    /// Restores the recorded QoS class, returning the result of doing so.
+    /// Dropping the guard does the same, but only logs the errors.
+    ///
+    /// The class can't be unset, so [`QosClass::Default`] is restored if the
+    /// thread had none.
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.restored = true;
+        self.restore_inner()
+    }
+
+    fn restore_inner(&self) -> Result<(), Error> {
+        match self.class {
+            QosClass::Unspecified => set_current_thread_qos_class(QosClass::Default, 0),
+            class => set_current_thread_qos_class(class, self.relative_priority),
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl crate::Promotion for QosClassGuard {
+    fn demote(self) -> Result<(), Error> {
+        self.restore()
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl Drop for QosClassGuard {
+    fn drop(&mut self) {
+        if self.restored {
+            return;
+        }
+        if let Err(e) = self.restore_inner() {
+            log::warn!(
+                "Couldn't restore the QoS class of the thread named {:?}: {:?}",
+                std::thread::current().name(),
+                e,
+            );
+        }
+    }
+}
+
+// These functions are missing in `libc`.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+extern "C" {
+    fn pthread_override_qos_class_start_np(
+        thread: libc::pthread_t,
+        class: libc::qos_class_t,
+        relative_priority: libc::c_int,
+    ) -> *mut libc::c_void;
+
+    fn pthread_override_qos_class_end_np(qos_override: *mut libc::c_void) -> libc::c_int;
+}
+
+/// A temporary raise of the QoS class of another thread, which lasts until the
+/// guard is dropped.
+///
+/// A thread waiting for the work of a thread with a lower QoS class can use the
+/// override to have the work done at its own class, avoiding the priority
+/// inversion. The override only raises the class of the target thread and is
+/// ignored if the thread already has a higher one.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (sender, receiver) = std::sync::mpsc::channel::<()>();
+/// let worker = std::thread::spawn(move || receiver.recv().unwrap());
+///
+/// let qos_override =
+///     QosOverride::start(worker.get_native_id(), QosClass::UserInitiated).unwrap();
+/// sender.send(()).unwrap();
+/// worker.join().unwrap();
+/// assert!(qos_override.end().is_ok());
+/// ```
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[derive(Debug)]
+pub struct QosOverride {
+    handle: *mut libc::c_void,
+    ended: bool,
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl QosOverride {
+    /// Raises the QoS class of the thread to the provided one until the
+    /// override is ended.
+    pub fn start(native: ThreadId, class: QosClass) -> Result<Self, Error> {
+        let handle = unsafe { pthread_override_qos_class_start_np(native, class.into(), 0) };
+        if handle.is_null() {
+            return Err(Error::OS(errno()));
+        }
+        Ok(Self {
+            handle,
+            ended: false,
+        })
+    }
+
+    /// Ends the override, returning the result of doing so. Dropping the
+    /// override does the same, but only logs the errors.
+    pub fn end(mut self) -> Result<(), Error> {
+        self.ended = true;
+        self.end_inner()
+    }
+
+    fn end_inner(&self) -> Result<(), Error> {
+        match unsafe { pthread_override_qos_class_end_np(self.handle) } {
+            0 => Ok(()),
+            e => Err(Error::OS(e)),
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl crate::Promotion for QosOverride {
+    fn demote(self) -> Result<(), Error> {
+        self.end()
+    }
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+impl Drop for QosOverride {
+    fn drop(&mut self) {
+        if self.ended {
+            return;
+        }
+        if let Err(e) = self.end_inner() {
+            log::warn!("Couldn't end the QoS override: {:?}", e);
+        }
+    }
 }
 
 impl TryFrom<u8> for ThreadPriority {
@@ -902,6 +5104,189 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn get_util_clamp_matches_scheduling_attributes() {
+        let attributes = get_thread_scheduling_attributes().unwrap();
+
+        assert_eq!(
+            get_thread_util_clamp(thread_native_id()),
+            Ok((attributes.sched_util_min, attributes.sched_util_max))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn set_util_clamp() {
+        let thread_id = thread_native_id();
+        let policy = thread_schedule_policy().unwrap();
+
+        assert!(set_thread_util_clamp(thread_id, 512, 256).is_err());
+        assert!(set_thread_util_clamp(thread_id, 0, 2048).is_err());
+
+        match set_thread_util_clamp(thread_id, 128, 512) {
+            // The kernel may be built without the utilization clamping support.
+            Err(Error::OS(libc::EOPNOTSUPP)) => {}
+            result => {
+                assert_eq!(result, Ok(()));
+                assert_eq!(get_thread_util_clamp(thread_id), Ok((128, 512)));
+            }
+        }
+        assert_eq!(thread_schedule_policy(), Ok(policy));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn parse_kernel_cpu_lists() {
+        assert_eq!(parse_cpu_list("0\n"), Some(vec![0]));
+        assert_eq!(parse_cpu_list("0-3,5,7-8"), Some(vec![0, 1, 2, 3, 5, 7, 8]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("a-b"), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn set_affinity_to_not_allowed_cpus() {
+        let allowed = get_allowed_cpus().unwrap();
+        let not_allowed = (0..libc::CPU_SETSIZE as usize)
+            .find(|core| !allowed.contains(core))
+            .unwrap();
+
+        assert_eq!(
+            set_thread_affinity(thread_native_id(), &[not_allowed]),
+            Err(Error::CpusNotAllowed(allowed))
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn validate_deadline_parameters() {
+        use std::time::Duration;
+
+        let valid = DeadlineParameters::new(
+            Duration::from_millis(1),
+            Duration::from_millis(10),
+            Duration::from_millis(100),
+        );
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(
+            DeadlineParameters {
+                period: Duration::ZERO,
+                ..valid
+            }
+            .validate(),
+            Ok(())
+        );
+        assert!(DeadlineParameters {
+            runtime: Duration::from_millis(20),
+            ..valid
+        }
+        .validate()
+        .is_err());
+        assert!(DeadlineParameters {
+            period: Duration::from_millis(5),
+            ..valid
+        }
+        .validate()
+        .is_err());
+        assert!(DeadlineParameters {
+            runtime: Duration::from_nanos(1),
+            ..valid
+        }
+        .validate()
+        .is_err());
+        assert!(valid.with_util_clamp(512, 256).validate().is_err());
+        assert!(valid.with_util_clamp(0, 2048).validate().is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn deadline_parameters_are_built_by_names() {
+        use std::time::Duration;
+
+        let parameters = DeadlineParameters::default()
+            .with_period(Duration::from_millis(100))
+            .with_runtime(Duration::from_millis(1))
+            .with_deadline(Duration::from_millis(10));
+        assert_eq!(
+            parameters,
+            DeadlineParameters::new(
+                Duration::from_millis(1),
+                Duration::from_millis(10),
+                Duration::from_millis(100),
+            )
+        );
+        assert_eq!(parameters.into_priority(), Ok(parameters.into()));
+        assert!(parameters
+            .with_deadline(Duration::from_millis(200))
+            .into_priority()
+            .is_err());
+        assert!(parameters
+            .with_period(Duration::from_secs(u64::MAX))
+            .into_priority()
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn util_clamp_flags_select_the_clamps() {
+        use std::time::Duration;
+
+        let parameters = DeadlineParameters::new(
+            Duration::from_millis(1),
+            Duration::from_millis(10),
+            Duration::from_millis(100),
+        );
+        let clamps = DeadlineFlags::UTIL_CLAMP_MIN | DeadlineFlags::UTIL_CLAMP_MAX;
+        assert_eq!(
+            parameters
+                .with_util_clamp(0, 512)
+                .to_sched_attr()
+                .map(|attr| attr.sched_flags),
+            Ok(clamps.bits())
+        );
+        assert_eq!(
+            parameters
+                .with_flags(DeadlineFlags::UTIL_CLAMP_MAX)
+                .with_util_clamp(0, 512)
+                .to_sched_attr()
+                .map(|attr| attr.sched_flags),
+            Ok(DeadlineFlags::UTIL_CLAMP_MAX.bits())
+        );
+        assert!(parameters
+            .with_flags(DeadlineFlags::UTIL_CLAMP_MIN)
+            .validate()
+            .is_err());
+        assert_eq!(
+            ThreadPriority::from(parameters.with_flags(clamps | DeadlineFlags::RECLAIM)),
+            ThreadPriority::Deadline {
+                runtime: parameters.runtime,
+                deadline: parameters.deadline,
+                period: parameters.period,
+                flags: DeadlineFlags::RECLAIM,
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn set_deadline_policy_using_parameters() {
+        use std::time::Duration;
+
+        let parameters = DeadlineParameters::new(
+            Duration::from_millis(2),
+            Duration::from_millis(20),
+            Duration::from_millis(200),
+        )
+        .with_flags(DeadlineFlags::RESET_ON_FORK);
+
+        assert_eq!(
+            set_thread_deadline_parameters(thread_native_id(), &parameters),
+            Ok(())
+        );
+        assert_eq!(get_current_thread_priority(), Ok(parameters.into()));
+    }
+
     #[test]
     #[cfg(target_os = "linux")]
     fn set_deadline_policy() {
@@ -930,5 +5315,15 @@ mod tests {
         assert_eq!(attributes.sched_deadline, 10 * 10_u64.pow(6));
         assert_eq!(attributes.sched_period, 100 * 10_u64.pow(6));
         assert_eq!(attributes.sched_flags, DeadlineFlags::RESET_ON_FORK.bits());
+
+        assert_eq!(
+            get_current_thread_priority(),
+            Ok(ThreadPriority::Deadline {
+                runtime: Duration::from_millis(1),
+                deadline: Duration::from_millis(10),
+                period: Duration::from_millis(100),
+                flags: DeadlineFlags::RESET_ON_FORK,
+            })
+        );
     }
 }
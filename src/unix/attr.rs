@@ -0,0 +1,146 @@
+//! This module provides wrappers for setting the scheduling attributes of
+//! `pthread_attr_t`, so that the threads created using these attributes
+//! start with the desired scheduling from the very beginning.
+//!
+//! The wrappers accept the crate's validated types, which is useful when
+//! building custom thread-creation layers on top of `pthread_create`.
+//!
+//! ```rust
+//! use thread_priority::unix::attr;
+//! use thread_priority::*;
+//!
+//! let mut pthread_attr = std::mem::MaybeUninit::<libc::pthread_attr_t>::uninit();
+//! assert_eq!(unsafe { libc::pthread_attr_init(pthread_attr.as_mut_ptr()) }, 0);
+//! let mut pthread_attr = unsafe { pthread_attr.assume_init() };
+//!
+//! assert!(attr::set_priority_and_policy(
+//!     &mut pthread_attr,
+//!     ThreadPriority::Max,
+//!     ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+//! )
+//! .is_ok());
+//!
+//! unsafe { libc::pthread_attr_destroy(&mut pthread_attr) };
+//! ```
+
+use super::{ScheduleParams, ThreadSchedulePolicy};
+use crate::{Error, ThreadPriority};
+
+/// Defines whether a thread inherits the scheduling of its creator.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum InheritSchedule {
+    /// The scheduling is inherited from the creating thread, and the
+    /// scheduling attributes are ignored.
+    Inherit,
+    /// The scheduling is taken from the scheduling attributes.
+    Explicit,
+}
+
+impl InheritSchedule {
+    fn to_posix(self) -> libc::c_int {
+        match self {
+            InheritSchedule::Inherit => libc::PTHREAD_INHERIT_SCHED,
+            InheritSchedule::Explicit => libc::PTHREAD_EXPLICIT_SCHED,
+        }
+    }
+}
+
+fn check(ret: libc::c_int) -> Result<(), Error> {
+    match ret {
+        0 => Ok(()),
+        e => Err(Error::OS(e)),
+    }
+}
+
+/// Sets whether the thread inherits the scheduling of its creator.
+/// Wraps `pthread_attr_setinheritsched`.
+///
+/// Note that the rest of the scheduling attributes are ignored unless
+/// [`InheritSchedule::Explicit`] is set.
+pub fn set_inherit_schedule(
+    attr: &mut libc::pthread_attr_t,
+    inherit: InheritSchedule,
+) -> Result<(), Error> {
+    check(unsafe { libc::pthread_attr_setinheritsched(attr, inherit.to_posix()) })
+}
+
+/// Sets the schedule policy. Wraps `pthread_attr_setschedpolicy`.
+///
+/// The `RealtimeThreadSchedulePolicy::Deadline` policy can't be set using the
+/// thread attributes, so an error is returned for it.
+pub fn set_schedule_policy(
+    attr: &mut libc::pthread_attr_t,
+    policy: ThreadSchedulePolicy,
+) -> Result<(), Error> {
+    #[cfg(target_os = "linux")]
+    if policy == ThreadSchedulePolicy::Realtime(super::RealtimeThreadSchedulePolicy::Deadline) {
+        return Err(Error::Priority(
+            "Deadline policy can't be set using the thread attributes.",
+        ));
+    }
+    check(unsafe { libc::pthread_attr_setschedpolicy(attr, policy.to_posix()) })
+}
+
+/// Sets the schedule parameters. Wraps `pthread_attr_setschedparam`.
+pub fn set_schedule_params(
+    attr: &mut libc::pthread_attr_t,
+    params: ScheduleParams,
+) -> Result<(), Error> {
+    let params = params.into_posix();
+    check(unsafe { libc::pthread_attr_setschedparam(attr, &params) })
+}
+
+/// Sets the explicit scheduling, the schedule policy and the priority
+/// converted for this policy.
+///
+/// The priority is converted the same way as in [`super::set_thread_priority_and_policy`].
+/// However, on the systems where the normal policies are prioritized using
+/// niceness, the niceness isn't a part of the thread attributes, so the static
+/// priority of `0` is always used for such policies, and the niceness has to be
+/// set from within the created thread.
+pub fn set_priority_and_policy(
+    attr: &mut libc::pthread_attr_t,
+    priority: ThreadPriority,
+    policy: ThreadSchedulePolicy,
+) -> Result<(), Error> {
+    let sched_priority = if matches!(policy, ThreadSchedulePolicy::Realtime(_))
+        || cfg!(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos"
+        )) {
+        priority.to_posix(policy)?
+    } else {
+        0
+    };
+
+    set_inherit_schedule(attr, InheritSchedule::Explicit)?;
+    set_schedule_policy(attr, policy)?;
+    set_schedule_params(attr, ScheduleParams { sched_priority })
+}
+
+/// Sets the QoS class and the relative priority within it, which must be in
+/// range of `[-15; 0]`. Wraps `pthread_attr_set_qos_class_np`.
+///
+/// Unlike [`super::set_current_thread_qos_class`], the class is in effect from
+/// the very first instruction of the created thread, which matters for the
+/// initial placement of the thread by the scheduler.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+pub fn set_qos_class(
+    attr: &mut libc::pthread_attr_t,
+    class: super::QosClass,
+    relative_priority: i32,
+) -> Result<(), Error> {
+    if !(-15..=0).contains(&relative_priority) {
+        return Err(Error::Priority(
+            "The QoS relative priority must be in range of [-15; 0].",
+        ));
+    }
+    check(unsafe { libc::pthread_attr_set_qos_class_np(attr, class.into(), relative_priority) })
+}
@@ -0,0 +1,191 @@
+//! This module allows to find the CPUs isolated from the scheduler with the
+//! `isolcpus` and `nohz_full` kernel parameters, and to dedicate one of them
+//! to a realtime thread.
+//!
+//! The isolated CPUs are read from `/sys/devices/system/cpu/isolated` and
+//! `/sys/devices/system/cpu/nohz_full`, or from `/proc/cmdline` when the
+//! kernel doesn't expose them.
+//!
+//! ```rust,no_run
+//! use thread_priority::unix::isolation::{isolated_cpus, pin_current_thread_to_isolated_core};
+//! use thread_priority::*;
+//!
+//! println!("{:?}", isolated_cpus().unwrap());
+//!
+//! let cpu = pin_current_thread_to_isolated_core(ThreadPriority::Max).unwrap();
+//! println!("The thread runs on the CPU {}", cpu);
+//! ```
+
+use std::sync::Mutex;
+
+use super::{
+    get_allowed_cpus, get_thread_affinity, parse_cpu_list, set_thread_affinity,
+    set_thread_priority_and_policy, thread_native_id, KernelThreadId, RealtimeThreadSchedulePolicy,
+    ThreadSchedulePolicy,
+};
+use crate::{Error, ThreadPriority};
+
+/// The CPUs isolated by the kernel parameters.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct IsolatedCpus {
+    /// The CPUs removed from the scheduler load balancing with `isolcpus`.
+    pub isolated: Vec<usize>,
+    /// The CPUs running without the scheduler tick with `nohz_full`.
+    pub nohz_full: Vec<usize>,
+}
+
+impl IsolatedCpus {
+    /// Parses the `isolcpus` and `nohz_full` parameters of a kernel command
+    /// line, like the contents of `/proc/cmdline`. The flags of `isolcpus`,
+    /// like `domain` or `managed_irq`, are skipped.
+    pub fn from_cmdline(cmdline: &str) -> Self {
+        let mut cpus = Self::default();
+        // The arguments after `--` are passed to the init process.
+        for parameter in cmdline.split_whitespace().take_while(|p| *p != "--") {
+            let (list, value) = match parameter.split_once('=') {
+                Some(("isolcpus", value)) => (&mut cpus.isolated, value),
+                Some(("nohz_full", value)) => (&mut cpus.nohz_full, value),
+                _ => continue,
+            };
+            let ranges: Vec<&str> = value
+                .split(',')
+                .filter(|range| range.starts_with(|c: char| c.is_ascii_digit()))
+                .collect();
+            *list = parse_cpu_list(&ranges.join(",")).unwrap_or_default();
+        }
+        cpus
+    }
+
+    /// Returns the CPUs isolated in either way, the ones isolated in both
+    /// first.
+    pub fn all(&self) -> Vec<usize> {
+        let mut both: Vec<usize> = self
+            .isolated
+            .iter()
+            .copied()
+            .filter(|cpu| self.nohz_full.contains(cpu))
+            .collect();
+        let either = self.isolated.iter().chain(&self.nohz_full);
+        for &cpu in either {
+            if !both.contains(&cpu) {
+                both.push(cpu);
+            }
+        }
+        both
+    }
+}
+
+/// Returns the CPUs isolated by the kernel parameters.
+///
+/// Each of the lists is read from sysfs, falling back to the kernel command
+/// line when the file is missing, for example, on the kernels built without
+/// `CONFIG_NO_HZ_FULL`.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::unix::isolation::isolated_cpus;
+///
+/// let cpus = isolated_cpus().unwrap();
+/// println!("{:?} are isolated", cpus.all());
+/// ```
+pub fn isolated_cpus() -> Result<IsolatedCpus, Error> {
+    let read_list = |path: &str| {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|list| parse_cpu_list(&list))
+    };
+    let sysfs = (
+        read_list("/sys/devices/system/cpu/isolated"),
+        read_list("/sys/devices/system/cpu/nohz_full"),
+    );
+    if let (Some(isolated), Some(nohz_full)) = sysfs {
+        return Ok(IsolatedCpus {
+            isolated,
+            nohz_full,
+        });
+    }
+
+    let cmdline = IsolatedCpus::from_cmdline(
+        &std::fs::read_to_string("/proc/cmdline")
+            .map_err(|_| Error::Ffi("Couldn't read the kernel command line."))?,
+    );
+    Ok(IsolatedCpus {
+        isolated: sysfs.0.unwrap_or(cmdline.isolated),
+        nohz_full: sysfs.1.unwrap_or(cmdline.nohz_full),
+    })
+}
+
+/// Returns the CPUs some thread other than the current one is pinned to
+/// alone, as far as the threads of the system can be listed.
+fn pinned_cpus() -> Vec<usize> {
+    let current = KernelThreadId::current().0.to_string();
+    let tasks = std::fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|process| process.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|process| std::fs::read_dir(process.path().join("task")).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|task| task.file_name().to_string_lossy() != current);
+
+    let mut cpus = Vec::new();
+    for task in tasks {
+        let status = std::fs::read_to_string(task.path().join("status")).unwrap_or_default();
+        let allowed = status
+            .lines()
+            .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+            .and_then(parse_cpu_list);
+        if let Some(&[cpu]) = allowed.as_deref() {
+            cpus.push(cpu);
+        }
+    }
+    cpus
+}
+
+/// Pins the current thread to a free isolated CPU and sets its priority with
+/// the [`RealtimeThreadSchedulePolicy::Fifo`] policy, returning the CPU.
+///
+/// * May require privileges
+///
+/// The CPUs isolated with both `isolcpus` and `nohz_full` are preferred, see
+/// [`IsolatedCpus::all`]. A CPU is free when it is allowed for the process
+/// and no other thread is pinned to it alone. The calls within the process
+/// are serialized, so that the concurrent ones choose different CPUs.
+///
+/// If the priority can't be set, the affinity of the thread is restored.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::unix::isolation::pin_current_thread_to_isolated_core;
+/// use thread_priority::*;
+///
+/// let cpu = pin_current_thread_to_isolated_core(ThreadPriority::Max).unwrap();
+/// assert_eq!(get_thread_affinity(thread_native_id()).unwrap(), vec![cpu]);
+/// ```
+pub fn pin_current_thread_to_isolated_core(priority: ThreadPriority) -> Result<usize, Error> {
+    static PINNING: Mutex<()> = Mutex::new(());
+    let _pinning = PINNING.lock().unwrap_or_else(|e| e.into_inner());
+
+    let allowed = get_allowed_cpus()?;
+    let pinned = pinned_cpus();
+    let cpu = isolated_cpus()?
+        .all()
+        .into_iter()
+        .find(|cpu| allowed.contains(cpu) && !pinned.contains(cpu))
+        .ok_or(Error::Priority(
+            "There is no free isolated CPU to pin the thread to.",
+        ))?;
+
+    let native = thread_native_id();
+    let previous = get_thread_affinity(native)?;
+    set_thread_affinity(native, &[cpu])?;
+    let policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    if let Err(e) = set_thread_priority_and_policy(native, priority, policy) {
+        let _ = set_thread_affinity(native, &previous);
+        return Err(e);
+    }
+    Ok(cpu)
+}
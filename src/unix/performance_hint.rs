@@ -0,0 +1,211 @@
+//! This module allows to report the work durations of a set of threads to the
+//! Android Dynamic Performance Framework (ADPF) through the
+//! `APerformanceHint` sessions, so that the system can adjust the CPU
+//! frequencies and the placement of the threads to meet the target duration,
+//! complementing their static priorities.
+//!
+//! The sessions are available on Android 13 (API level 33) and later. The
+//! functions are looked up at runtime, so that the applications supporting
+//! the older versions still load there and get an error instead.
+//!
+//! ```rust,no_run
+//! use std::time::{Duration, Instant};
+//! use thread_priority::unix::performance_hint::PerformanceHintSession;
+//! use thread_priority::*;
+//!
+//! let frame = Duration::from_micros(16_667);
+//! let session = PerformanceHintSession::new(&[KernelThreadId::current()], frame).unwrap();
+//! loop {
+//!     let start = Instant::now();
+//!     // Render the frame.
+//!     session.report_actual_duration(start.elapsed()).unwrap();
+//! }
+//! ```
+
+use std::time::Duration;
+
+use crate::{Error, KernelThreadId};
+
+type PerformanceHintManager = *mut libc::c_void;
+type PerformanceHintSessionHandle = *mut libc::c_void;
+
+/// The `libandroid` functions of the `APerformanceHint` API.
+#[derive(Copy, Clone)]
+struct Api {
+    get_manager: unsafe extern "C" fn() -> PerformanceHintManager,
+    get_preferred_update_rate_nanos: unsafe extern "C" fn(PerformanceHintManager) -> i64,
+    create_session: unsafe extern "C" fn(
+        PerformanceHintManager,
+        *const i32,
+        libc::size_t,
+        i64,
+    ) -> PerformanceHintSessionHandle,
+    update_target_work_duration:
+        unsafe extern "C" fn(PerformanceHintSessionHandle, i64) -> libc::c_int,
+    report_actual_work_duration:
+        unsafe extern "C" fn(PerformanceHintSessionHandle, i64) -> libc::c_int,
+    close_session: unsafe extern "C" fn(PerformanceHintSessionHandle),
+    /// Only available on Android 14 (API level 34) and later.
+    set_threads: Option<
+        unsafe extern "C" fn(PerformanceHintSessionHandle, *const i32, libc::size_t) -> libc::c_int,
+    >,
+}
+
+impl std::fmt::Debug for Api {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Api").finish_non_exhaustive()
+    }
+}
+
+impl Api {
+    fn load() -> Result<Self, Error> {
+        // The library is loaded by every application, so it is never closed.
+        let library = unsafe {
+            libc::dlopen(
+                b"libandroid.so\0".as_ptr() as *const libc::c_char,
+                libc::RTLD_NOW,
+            )
+        };
+        if library.is_null() {
+            return Err(Error::Ffi("Couldn't load libandroid."));
+        }
+        unsafe {
+            Ok(Self {
+                get_manager: symbol(library, b"APerformanceHint_getManager\0")?,
+                get_preferred_update_rate_nanos: symbol(
+                    library,
+                    b"APerformanceHint_getPreferredUpdateRateNanos\0",
+                )?,
+                create_session: symbol(library, b"APerformanceHint_createSession\0")?,
+                update_target_work_duration: symbol(
+                    library,
+                    b"APerformanceHint_updateTargetWorkDuration\0",
+                )?,
+                report_actual_work_duration: symbol(
+                    library,
+                    b"APerformanceHint_reportActualWorkDuration\0",
+                )?,
+                close_session: symbol(library, b"APerformanceHint_closeSession\0")?,
+                set_threads: symbol(library, b"APerformanceHint_setThreads\0").ok(),
+            })
+        }
+    }
+
+    fn manager(&self) -> Result<PerformanceHintManager, Error> {
+        let manager = unsafe { (self.get_manager)() };
+        if manager.is_null() {
+            return Err(Error::Ffi("The performance hints aren't supported."));
+        }
+        Ok(manager)
+    }
+}
+
+/// Looks up the function of the type `T` by its null-terminated name.
+unsafe fn symbol<T: Copy>(library: *mut libc::c_void, name: &[u8]) -> Result<T, Error> {
+    let symbol = libc::dlsym(library, name.as_ptr() as *const libc::c_char);
+    if symbol.is_null() {
+        return Err(Error::Ffi(
+            "The performance hints require Android 13 or later.",
+        ));
+    }
+    Ok(std::mem::transmute_copy(&symbol))
+}
+
+fn duration_nanos(duration: Duration) -> Result<i64, Error> {
+    match i64::try_from(duration.as_nanos()) {
+        Ok(nanos) if nanos > 0 => Ok(nanos),
+        _ => Err(Error::Priority(
+            "The work duration must be positive and fit into i64 nanoseconds.",
+        )),
+    }
+}
+
+fn thread_ids(threads: &[KernelThreadId]) -> Vec<i32> {
+    threads.iter().map(|thread| thread.0).collect()
+}
+
+/// Returns the interval the system prefers the actual durations to be
+/// reported at, see [`PerformanceHintSession::report_actual_duration`].
+pub fn preferred_update_rate() -> Result<Duration, Error> {
+    let api = Api::load()?;
+    let nanos = unsafe { (api.get_preferred_update_rate_nanos)(api.manager()?) };
+    u64::try_from(nanos)
+        .map(Duration::from_nanos)
+        .map_err(|_| Error::Ffi("The performance hints aren't supported."))
+}
+
+/// A performance hint session for a set of threads of the process, which is
+/// closed when dropped.
+///
+/// The threads of the session are expected to do a recurring piece of work,
+/// like rendering a frame, within the target duration.
+#[derive(Debug)]
+pub struct PerformanceHintSession {
+    session: PerformanceHintSessionHandle,
+    api: Api,
+}
+
+// The session isn't bound to the thread which has created it.
+unsafe impl Send for PerformanceHintSession {}
+
+impl PerformanceHintSession {
+    /// Creates a session for the threads, which are expected to complete
+    /// their work within the target duration.
+    ///
+    /// The threads must belong to the current process.
+    pub fn new(threads: &[KernelThreadId], target_duration: Duration) -> Result<Self, Error> {
+        let api = Api::load()?;
+        let target = duration_nanos(target_duration)?;
+        let threads = thread_ids(threads);
+        let session = unsafe {
+            (api.create_session)(api.manager()?, threads.as_ptr(), threads.len(), target)
+        };
+        if session.is_null() {
+            return Err(Error::Ffi("Couldn't create the performance hint session."));
+        }
+        Ok(Self { session, api })
+    }
+
+    /// Updates the duration the threads are expected to complete their work
+    /// within.
+    pub fn update_target_duration(&self, target_duration: Duration) -> Result<(), Error> {
+        let target = duration_nanos(target_duration)?;
+        match unsafe { (self.api.update_target_work_duration)(self.session, target) } {
+            0 => Ok(()),
+            e => Err(Error::OS(e)),
+        }
+    }
+
+    /// Reports the duration the threads have actually taken to complete
+    /// their work, see [`preferred_update_rate`].
+    pub fn report_actual_duration(&self, actual_duration: Duration) -> Result<(), Error> {
+        let actual = duration_nanos(actual_duration)?;
+        match unsafe { (self.api.report_actual_work_duration)(self.session, actual) } {
+            0 => Ok(()),
+            e => Err(Error::OS(e)),
+        }
+    }
+
+    /// Replaces the threads of the session.
+    ///
+    /// * Requires Android 14 (API level 34) or later
+    pub fn set_threads(&self, threads: &[KernelThreadId]) -> Result<(), Error> {
+        let set_threads = self.api.set_threads.ok_or(Error::Ffi(
+            "Changing the threads of the session requires Android 14 or later.",
+        ))?;
+        let threads = thread_ids(threads);
+        match unsafe { set_threads(self.session, threads.as_ptr(), threads.len()) } {
+            0 => Ok(()),
+            e => Err(Error::OS(e)),
+        }
+    }
+
+    /// Closes the session, the same as dropping it.
+    pub fn close(self) {}
+}
+
+impl Drop for PerformanceHintSession {
+    fn drop(&mut self) {
+        unsafe { (self.api.close_session)(self.session) }
+    }
+}
@@ -0,0 +1,151 @@
+//! This module allows to react on the CPU pressure reported by the Linux
+//! [pressure stall information](https://docs.kernel.org/accounting/psi.html)
+//! (PSI), for example, to demote the background threads while the system is
+//! overloaded.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use thread_priority::unix::pressure::{CpuPressureMonitor, read_cpu_pressure};
+//!
+//! println!("{:?}", read_cpu_pressure().unwrap());
+//!
+//! let _monitor = CpuPressureMonitor::start(50.0, Duration::from_secs(1), |high, pressure| {
+//!     println!("The CPU pressure is high: {} ({:?})", high, pressure);
+//! })
+//! .unwrap();
+//! ```
+
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::io_error;
+use crate::Error;
+
+/// The share of time some or all of the tasks were stalled.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PressureStall {
+    /// The percentage of time stalled over the last 10 seconds.
+    pub avg10: f32,
+    /// The percentage of time stalled over the last 60 seconds.
+    pub avg60: f32,
+    /// The percentage of time stalled over the last 300 seconds.
+    pub avg300: f32,
+    /// The total time stalled.
+    pub total: Duration,
+}
+
+/// The CPU pressure as reported in `/proc/pressure/cpu`.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct CpuPressure {
+    /// The time at least some of the runnable tasks were waiting for a CPU.
+    pub some: PressureStall,
+    /// The time all of the non-idle tasks were waiting for a CPU at once. Older
+    /// kernels don't report it, in which case it is zeroed.
+    pub full: PressureStall,
+}
+
+const CPU_PRESSURE_PATH: &str = "/proc/pressure/cpu";
+
+fn parse_pressure_stall(fields: &str) -> Result<PressureStall, Error> {
+    let mut stall = PressureStall::default();
+    for field in fields.split_whitespace() {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or(Error::Ffi("Can't parse the pressure stall information"))?;
+        let invalid = |_| Error::Ffi("Can't parse the pressure stall information");
+        match key {
+            "avg10" => stall.avg10 = value.parse().map_err(invalid)?,
+            "avg60" => stall.avg60 = value.parse().map_err(invalid)?,
+            "avg300" => stall.avg300 = value.parse().map_err(invalid)?,
+            "total" => {
+                stall.total = Duration::from_micros(
+                    value
+                        .parse()
+                        .map_err(|_| Error::Ffi("Can't parse the pressure stall information"))?,
+                )
+            }
+            _ => {}
+        }
+    }
+    Ok(stall)
+}
+
+/// Parses the contents of a pressure file, like `/proc/pressure/cpu`.
+pub fn parse_cpu_pressure(contents: &str) -> Result<CpuPressure, Error> {
+    let mut pressure = CpuPressure::default();
+    for line in contents.lines() {
+        match line.split_once(' ') {
+            Some(("some", fields)) => pressure.some = parse_pressure_stall(fields)?,
+            Some(("full", fields)) => pressure.full = parse_pressure_stall(fields)?,
+            _ => {}
+        }
+    }
+    Ok(pressure)
+}
+
+/// Reads the current CPU pressure from `/proc/pressure/cpu`. The kernel has to
+/// be built with `CONFIG_PSI`.
+pub fn read_cpu_pressure() -> Result<CpuPressure, Error> {
+    parse_cpu_pressure(&std::fs::read_to_string(CPU_PRESSURE_PATH).map_err(io_error)?)
+}
+
+/// A monitor periodically reading the CPU pressure and invoking the callback
+/// whenever the 10-second average of the `some` pressure crosses the threshold.
+/// The monitor is stopped when dropped.
+#[derive(Debug)]
+pub struct CpuPressureMonitor {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CpuPressureMonitor {
+    /// Starts monitoring the CPU pressure every `interval`. The callback receives
+    /// whether the pressure, in percent, is above the `threshold` now, along with
+    /// the pressure read. It is invoked on the first reading and then on every
+    /// crossing of the threshold, from the monitor's own thread.
+    ///
+    /// An error is returned if the pressure can't be read or the thread can't
+    /// be spawned.
+    pub fn start<F>(threshold: f32, interval: Duration, mut callback: F) -> Result<Self, Error>
+    where
+        F: FnMut(bool, &CpuPressure) + Send + 'static,
+    {
+        let initial = read_cpu_pressure()?;
+        let (stop, stopped) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("cpu-pressure".to_owned())
+            .spawn(move || {
+                let mut high = initial.some.avg10 > threshold;
+                callback(high, &initial);
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let pressure = match read_cpu_pressure() {
+                        Ok(pressure) => pressure,
+                        Err(e) => {
+                            log::warn!("Couldn't read the CPU pressure: {:?}", e);
+                            continue;
+                        }
+                    };
+                    if (pressure.some.avg10 > threshold) != high {
+                        high = !high;
+                        callback(high, &pressure);
+                    }
+                }
+            })
+            .map_err(io_error)?;
+
+        Ok(Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for CpuPressureMonitor {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
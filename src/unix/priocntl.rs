@@ -0,0 +1,257 @@
+//! This module allows to control the scheduling classes of the LWPs on illumos
+//! and Solaris directly through `priocntl(2)`: the time-sharing (`TS`), the
+//! fixed priority (`FX`) and the realtime (`RT`) ones.
+//!
+//! The [`crate::ThreadSchedulePolicy`] values are mapped onto these classes by
+//! `pthread_setschedparam`, while here the priorities are the ones of the
+//! classes themselves, like `[-60; 60]` for the time-sharing class, and are
+//! reported as such by [`SchedulingClass::priority_range`].
+//!
+//! The threads are the LWPs of the same id, so [`crate::thread_native_id`] can
+//! be used as the LWP id.
+//!
+//! ```rust,no_run
+//! use thread_priority::unix::priocntl::*;
+//!
+//! let range = SchedulingClass::FixedPriority.priority_range().unwrap();
+//! set_current_lwp_scheduling_class(SchedulingClass::FixedPriority, *range.start()).unwrap();
+//! assert_eq!(
+//!     get_current_lwp_scheduling_class(),
+//!     Ok((SchedulingClass::FixedPriority, *range.start()))
+//! );
+//! ```
+
+use super::errno;
+use crate::Error;
+
+const PC_VERSION: libc::c_int = 1;
+const PC_GETCID: libc::c_int = 0;
+const PC_SETPARMS: libc::c_int = 2;
+const PC_GETPARMS: libc::c_int = 3;
+const PC_CLNULL: libc::id_t = -1;
+const P_MYID: libc::id_t = -1;
+
+const PC_CLNMSZ: usize = 16;
+const PC_CLINFOSZ: usize = 8;
+const PC_CLPARMSZ: usize = 8;
+
+/// Keeps the user priority limit of the `TS` and `FX` classes as is.
+const NOCHANGE: i16 = -32768;
+/// Selects the default time quantum of the `FX` and `RT` classes.
+const TQDEF: libc::c_int = -2;
+
+/// The `pcinfo_t` structure.
+#[repr(C)]
+struct PcInfo {
+    pc_cid: libc::id_t,
+    pc_clname: [libc::c_char; PC_CLNMSZ],
+    pc_clinfo: [libc::c_int; PC_CLINFOSZ],
+}
+
+/// The `pcparms_t` structure.
+#[repr(C)]
+struct PcParms {
+    pc_cid: libc::id_t,
+    pc_clparms: [libc::c_int; PC_CLPARMSZ],
+}
+
+/// The `tsparms_t` structure.
+#[repr(C)]
+struct TsParms {
+    ts_uprilim: i16,
+    ts_upri: i16,
+}
+
+/// The `fxparms_t` structure.
+#[repr(C)]
+struct FxParms {
+    fx_uprilim: i16,
+    fx_upri: i16,
+    fx_tqsecs: libc::c_uint,
+    fx_tqnsecs: libc::c_int,
+}
+
+/// The `rtparms_t` structure.
+#[repr(C)]
+struct RtParms {
+    rt_pri: i16,
+    rt_tqsecs: libc::c_uint,
+    rt_tqnsecs: libc::c_int,
+}
+
+// This function is missing in `libc`, where `priocntl` is a macro calling it.
+extern "C" {
+    fn __priocntl(
+        version: libc::c_int,
+        idtype: libc::idtype_t,
+        id: libc::id_t,
+        cmd: libc::c_int,
+        arg: *mut libc::c_char,
+    ) -> libc::c_long;
+}
+
+fn priocntl<T>(
+    idtype: libc::idtype_t,
+    id: libc::id_t,
+    cmd: libc::c_int,
+    arg: &mut T,
+) -> Result<(), Error> {
+    let ret = unsafe { __priocntl(PC_VERSION, idtype, id, cmd, arg as *mut T as *mut _) };
+    if ret == -1 {
+        return Err(Error::OS(errno()));
+    }
+    Ok(())
+}
+
+/// A scheduling class of illumos and Solaris, see `priocntl(1)`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SchedulingClass {
+    /// The time-sharing (`TS`) class, where the scheduler adjusts the priority
+    /// of the thread around the user priority. This is the class of
+    /// [`crate::NormalThreadSchedulePolicy::Other`].
+    TimeSharing,
+    /// The fixed priority (`FX`) class, where the priority isn't adjusted by
+    /// the scheduler.
+    FixedPriority,
+    /// The realtime (`RT`) class, which runs before all the other classes.
+    /// This is the class of [`crate::RealtimeThreadSchedulePolicy::Fifo`] and
+    /// [`crate::RealtimeThreadSchedulePolicy::RoundRobin`].
+    ///
+    /// * Requires privileges
+    Realtime,
+}
+
+impl SchedulingClass {
+    /// Returns the name of the class, like `TS`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SchedulingClass::TimeSharing => "TS",
+            SchedulingClass::FixedPriority => "FX",
+            SchedulingClass::Realtime => "RT",
+        }
+    }
+
+    /// Returns the class id along with its maximum priority.
+    fn info(self) -> Result<(libc::id_t, i16), Error> {
+        let mut info = PcInfo {
+            pc_cid: 0,
+            pc_clname: [0; PC_CLNMSZ],
+            pc_clinfo: [0; PC_CLINFOSZ],
+        };
+        for (dst, src) in info.pc_clname.iter_mut().zip(self.name().bytes()) {
+            *dst = src as libc::c_char;
+        }
+        priocntl(libc::P_PID, 0, PC_GETCID, &mut info)?;
+        // All of `tsinfo_t`, `fxinfo_t` and `rtinfo_t` start with the maximum
+        // priority.
+        let max = unsafe { (info.pc_clinfo.as_ptr() as *const i16).read() };
+        Ok((info.pc_cid, max))
+    }
+
+    /// Returns the range of the priorities of the class: from the negative
+    /// maximum to the maximum for the time-sharing class, and from zero
+    /// otherwise. The higher value means the higher priority.
+    pub fn priority_range(self) -> Result<std::ops::RangeInclusive<i16>, Error> {
+        let (_, max) = self.info()?;
+        Ok(match self {
+            SchedulingClass::TimeSharing => -max..=max,
+            SchedulingClass::FixedPriority | SchedulingClass::Realtime => 0..=max,
+        })
+    }
+}
+
+/// Moves the LWP into the scheduling class, with the priority in the range
+/// of the class, see [`SchedulingClass::priority_range`]. The LWP id `-1`
+/// stands for the current thread.
+///
+/// The user priority limit of the time-sharing and fixed priority classes is
+/// kept, so raising the priority above it requires privileges.
+pub fn set_lwp_scheduling_class(
+    lwpid: libc::id_t,
+    class: SchedulingClass,
+    priority: i16,
+) -> Result<(), Error> {
+    let range = class.priority_range()?;
+    if !range.contains(&priority) {
+        return Err(Error::PriorityNotInRange(
+            *range.start() as i32..=*range.end() as i32,
+        ));
+    }
+    let (pc_cid, _) = class.info()?;
+    let mut parms = PcParms {
+        pc_cid,
+        pc_clparms: [0; PC_CLPARMSZ],
+    };
+    let clparms = parms.pc_clparms.as_mut_ptr();
+    unsafe {
+        match class {
+            SchedulingClass::TimeSharing => (clparms as *mut TsParms).write(TsParms {
+                ts_uprilim: NOCHANGE,
+                ts_upri: priority,
+            }),
+            SchedulingClass::FixedPriority => (clparms as *mut FxParms).write(FxParms {
+                fx_uprilim: NOCHANGE,
+                fx_upri: priority,
+                fx_tqsecs: 0,
+                fx_tqnsecs: TQDEF,
+            }),
+            SchedulingClass::Realtime => (clparms as *mut RtParms).write(RtParms {
+                rt_pri: priority,
+                rt_tqsecs: 0,
+                rt_tqnsecs: TQDEF,
+            }),
+        }
+    }
+    priocntl(libc::P_LWPID, lwpid, PC_SETPARMS, &mut parms)
+}
+
+/// Returns the scheduling class of the LWP along with its priority, see
+/// [`set_lwp_scheduling_class`].
+///
+/// The LWPs in the other classes, like the fair share one, result in
+/// [`Error::Ffi`].
+pub fn get_lwp_scheduling_class(lwpid: libc::id_t) -> Result<(SchedulingClass, i16), Error> {
+    let mut parms = PcParms {
+        pc_cid: PC_CLNULL,
+        pc_clparms: [0; PC_CLPARMSZ],
+    };
+    priocntl(libc::P_LWPID, lwpid, PC_GETPARMS, &mut parms)?;
+    let clparms = parms.pc_clparms.as_ptr();
+    for class in [
+        SchedulingClass::TimeSharing,
+        SchedulingClass::FixedPriority,
+        SchedulingClass::Realtime,
+    ] {
+        if class.info()?.0 != parms.pc_cid {
+            continue;
+        }
+        let priority = unsafe {
+            match class {
+                SchedulingClass::TimeSharing => (clparms as *const TsParms).read().ts_upri,
+                SchedulingClass::FixedPriority => (clparms as *const FxParms).read().fx_upri,
+                SchedulingClass::Realtime => (clparms as *const RtParms).read().rt_pri,
+            }
+        };
+        return Ok((class, priority));
+    }
+    Err(Error::Ffi("The LWP is in an unsupported scheduling class."))
+}
+
+/// Moves the current thread into the scheduling class.
+///
+/// This is a short-hand of the [`set_lwp_scheduling_class`] function for the
+/// current thread.
+pub fn set_current_lwp_scheduling_class(
+    class: SchedulingClass,
+    priority: i16,
+) -> Result<(), Error> {
+    set_lwp_scheduling_class(P_MYID, class, priority)
+}
+
+/// Returns the scheduling class of the current thread along with its priority.
+///
+/// This is a short-hand of the [`get_lwp_scheduling_class`] function for the
+/// current thread.
+pub fn get_current_lwp_scheduling_class() -> Result<(SchedulingClass, i16), Error> {
+    get_lwp_scheduling_class(P_MYID)
+}
@@ -0,0 +1,182 @@
+//! This module allows to enumerate the threads of the current process and to
+//! change the scheduling of all of them at once, including the threads spawned
+//! by the third-party libraries.
+//!
+//! The threads are listed from `/proc/<pid>/task`.
+//!
+//! ```rust
+//! use thread_priority::*;
+//!
+//! let threads = Process::current().threads().unwrap();
+//! assert!(threads.iter().any(|thread| thread.id == KernelThreadId::current()));
+//! ```
+
+use super::{io_error, KernelThreadId};
+use crate::{Error, InterpretedPriority, ThreadBuilder, ThreadPriority, ThreadSchedulePolicy};
+
+/// A thread of the process along with its name and scheduling.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ProcessThread {
+    /// The kernel-level id of the thread.
+    pub id: KernelThreadId,
+    /// The OS-level name of the thread, which is the one shown in debuggers
+    /// and tools like `top -H`, or `None` if it couldn't be read.
+    pub name: Option<String>,
+    /// The scheduling of the thread at the moment it was listed.
+    pub priority: InterpretedPriority,
+}
+
+/// A process, which is the current one unless created with
+/// [`Process::from_pid`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Process {
+    pid: libc::pid_t,
+}
+
+impl Default for Process {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+impl Process {
+    /// Returns the current process.
+    pub fn current() -> Self {
+        Self {
+            pid: unsafe { libc::getpid() },
+        }
+    }
+
+    /// Returns the process with the id. Listing the threads of another process
+    /// may require privileges, as well as changing their scheduling.
+    pub fn from_pid(pid: libc::pid_t) -> Self {
+        Self { pid }
+    }
+
+    /// Returns the id of the process.
+    pub fn pid(&self) -> libc::pid_t {
+        self.pid
+    }
+
+    /// Returns the ids of the threads of the process.
+    pub fn thread_ids(&self) -> Result<Vec<KernelThreadId>, Error> {
+        let mut ids = Vec::new();
+        let tasks = format!("/proc/{}/task", self.pid);
+        for entry in std::fs::read_dir(tasks).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            if let Some(id) = entry.file_name().to_str().and_then(|id| id.parse().ok()) {
+                ids.push(KernelThreadId(id));
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Returns the id of the main thread of the process, which is the same as
+    /// the id of the process itself.
+    pub fn main_thread_id(&self) -> KernelThreadId {
+        KernelThreadId(self.pid)
+    }
+
+    /// Returns the threads of the process with their current scheduling. The
+    /// threads which have exited while being listed are skipped.
+    pub fn threads(&self) -> Result<Vec<ProcessThread>, Error> {
+        let mut threads = Vec::new();
+        for id in self.thread_ids()? {
+            match id.interpreted_priority() {
+                Ok(priority) => threads.push(ProcessThread {
+                    id,
+                    name: self.thread_name(id),
+                    priority,
+                }),
+                Err(Error::OS(libc::ESRCH)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(threads)
+    }
+
+    /// Reads the name of the thread from `/proc/<pid>/task/<tid>/comm`.
+    fn thread_name(&self, id: KernelThreadId) -> Option<String> {
+        let comm = format!("/proc/{}/task/{}/comm", self.pid, id.0);
+        let name = std::fs::read_to_string(comm).ok()?;
+        Some(name.trim_end_matches('\n').to_owned())
+    }
+
+    /// Sets the priority and the policy of every thread of the process,
+    /// returning the number of threads changed.
+    ///
+    /// * May require privileges
+    ///
+    /// The threads which have exited in the meantime are skipped. The threads
+    /// spawned while this runs may be missed. If changing a thread fails, the
+    /// rest of the threads are still changed and the first error is returned.
+    pub fn set_priority_for_all(
+        &self,
+        priority: ThreadPriority,
+        policy: ThreadSchedulePolicy,
+    ) -> Result<usize, Error> {
+        let mut changed = 0;
+        let mut error = None;
+        for id in self.thread_ids()? {
+            match id.set_priority_and_policy(priority, policy) {
+                Ok(()) => changed += 1,
+                Err(Error::OS(libc::ESRCH)) => {}
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(changed),
+        }
+    }
+}
+
+/// Applies the affinity, the priority, the policy and the fallbacks of the
+/// builder to the main thread of the process. This allows the libraries, like
+/// plugins and engines, to tune the host's main thread from any thread, for
+/// example, during their initialization.
+///
+/// * May require privileges
+///
+/// When only the priority is set, the current policy of the main thread is
+/// kept. The other settings of the builder are ignored.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// std::thread::spawn(|| {
+///     let config = ThreadBuilder::default().priority(ThreadPriority::Min);
+///     assert!(configure_main_thread(&config).is_ok());
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+pub fn configure_main_thread(config: &ThreadBuilder) -> Result<(), Error> {
+    let main = Process::current().main_thread_id();
+    if let Some(cores) = &config.affinity {
+        main.set_affinity(cores)?;
+    }
+    let result = match (config.priority, config.policy) {
+        (Some(priority), Some(policy)) => main.set_priority_and_policy(priority, policy),
+        (Some(priority), None) => main
+            .interpreted_priority()
+            .and_then(|current| main.set_priority_and_policy(priority, current.policy)),
+        (None, Some(_)) => Err(Error::Priority(
+            "Setting the policy separately isn't currently supported.",
+        )),
+        (None, None) => Ok(()),
+    };
+    match result {
+        Err(e) => config
+            .fallbacks
+            .iter()
+            .find_map(|&(policy, priority)| main.set_priority_and_policy(priority, policy).ok())
+            .ok_or(e),
+        Ok(()) => Ok(()),
+    }
+}
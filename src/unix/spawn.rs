@@ -0,0 +1,94 @@
+//! This module provides wrappers for setting the scheduling attributes of
+//! `posix_spawnattr_t`, so that the processes started using `posix_spawn`
+//! get the desired scheduling atomically, without a window where the child
+//! runs with the scheduling of its parent.
+//!
+//! ```rust
+//! use thread_priority::unix::spawn;
+//! use thread_priority::*;
+//!
+//! let mut spawn_attr = std::mem::MaybeUninit::<libc::posix_spawnattr_t>::uninit();
+//! assert_eq!(unsafe { libc::posix_spawnattr_init(spawn_attr.as_mut_ptr()) }, 0);
+//! let mut spawn_attr = unsafe { spawn_attr.assume_init() };
+//!
+//! assert!(spawn::set_priority_and_policy(
+//!     &mut spawn_attr,
+//!     ThreadPriority::Max,
+//!     ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+//! )
+//! .is_ok());
+//!
+//! unsafe { libc::posix_spawnattr_destroy(&mut spawn_attr) };
+//! ```
+
+use super::{ScheduleParams, ThreadSchedulePolicy};
+use crate::{Error, ThreadPriority};
+
+fn check(ret: libc::c_int) -> Result<(), Error> {
+    match ret {
+        0 => Ok(()),
+        e => Err(Error::OS(e)),
+    }
+}
+
+/// Adds the flag to the ones already set, so that the attributes set
+/// separately don't override each other.
+fn add_flag(attr: &mut libc::posix_spawnattr_t, flag: libc::c_int) -> Result<(), Error> {
+    let mut flags: libc::c_short = 0;
+    check(unsafe { libc::posix_spawnattr_getflags(attr, &mut flags) })?;
+    check(unsafe { libc::posix_spawnattr_setflags(attr, flags | flag as libc::c_short) })
+}
+
+/// Sets the schedule policy and the `POSIX_SPAWN_SETSCHEDULER` flag, so that the
+/// policy is used. Wraps `posix_spawnattr_setschedpolicy`.
+///
+/// Note that the policy is applied together with the schedule parameters, so
+/// they should be set too, for example, using [`set_schedule_params`].
+///
+/// The `RealtimeThreadSchedulePolicy::Deadline` policy can't be set using the
+/// spawn attributes, so an error is returned for it.
+pub fn set_schedule_policy(
+    attr: &mut libc::posix_spawnattr_t,
+    policy: ThreadSchedulePolicy,
+) -> Result<(), Error> {
+    #[cfg(target_os = "linux")]
+    if policy == ThreadSchedulePolicy::Realtime(super::RealtimeThreadSchedulePolicy::Deadline) {
+        return Err(Error::Priority(
+            "Deadline policy can't be set using the spawn attributes.",
+        ));
+    }
+    check(unsafe { libc::posix_spawnattr_setschedpolicy(attr, policy.to_posix()) })?;
+    add_flag(attr, libc::POSIX_SPAWN_SETSCHEDULER)
+}
+
+/// Sets the schedule parameters and the `POSIX_SPAWN_SETSCHEDPARAM` flag, so
+/// that the parameters are used. Wraps `posix_spawnattr_setschedparam`.
+pub fn set_schedule_params(
+    attr: &mut libc::posix_spawnattr_t,
+    params: ScheduleParams,
+) -> Result<(), Error> {
+    let params = params.into_posix();
+    check(unsafe { libc::posix_spawnattr_setschedparam(attr, &params) })?;
+    add_flag(attr, libc::POSIX_SPAWN_SETSCHEDPARAM)
+}
+
+/// Sets the schedule policy and the priority converted for this policy.
+///
+/// The priority is converted the same way as in [`super::set_thread_priority_and_policy`].
+/// However, the niceness isn't a part of the spawn attributes, so the static
+/// priority of `0` is always used for the normal policies, and the niceness has
+/// to be set by the child process itself.
+pub fn set_priority_and_policy(
+    attr: &mut libc::posix_spawnattr_t,
+    priority: ThreadPriority,
+    policy: ThreadSchedulePolicy,
+) -> Result<(), Error> {
+    let sched_priority = if matches!(policy, ThreadSchedulePolicy::Realtime(_)) {
+        priority.to_posix(policy)?
+    } else {
+        0
+    };
+
+    set_schedule_policy(attr, policy)?;
+    set_schedule_params(attr, ScheduleParams { sched_priority })
+}
@@ -0,0 +1,124 @@
+//! This module allows the threads to join the `os_workgroup_t` workgroups on
+//! macOS 11 and later, for example, the workgroup of an audio device.
+//!
+//! The real-time audio threads need both the time constraint policy, see
+//! [`crate::set_current_thread_time_constraint_policy`], and the membership
+//! in the workgroup of the device they render for, so that the system can
+//! account for their deadline together with the audio I/O thread.
+//!
+//! The workgroup is obtained from Core Audio, for example, with the
+//! `kAudioDevicePropertyIOThreadOSWorkgroup` property of the device or the
+//! `kAudioOutputUnitProperty_OSWorkgroup` property of the output unit.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use thread_priority::unix::workgroup::{join_current_thread_workgroup, OsWorkgroup};
+//! use thread_priority::*;
+//!
+//! # let workgroup: OsWorkgroup = std::ptr::null_mut();
+//! std::thread::spawn(move || {
+//!     set_current_thread_time_constraint_policy(
+//!         Duration::from_millis(10),
+//!         Duration::from_millis(2),
+//!         Duration::from_millis(5),
+//!         true,
+//!     )
+//!     .unwrap();
+//!     // The workgroup is kept alive by the audio device.
+//!     let membership = unsafe { join_current_thread_workgroup(workgroup) }.unwrap();
+//!     // Render the audio.
+//!     membership.leave();
+//! });
+//! ```
+
+use crate::Error;
+
+/// The `os_workgroup_t` handle of a workgroup.
+pub type OsWorkgroup = *mut libc::c_void;
+
+/// The size of the opaque part of the `os_workgroup_join_token_s` structure.
+const JOIN_TOKEN_SIZE: usize = 36;
+
+/// The `os_workgroup_join_token_s` structure filled by `os_workgroup_join`
+/// and consumed by `os_workgroup_leave`.
+#[repr(C)]
+struct JoinToken {
+    sig: u32,
+    opaque: [libc::c_char; JOIN_TOKEN_SIZE],
+}
+
+impl std::fmt::Debug for JoinToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoinToken").finish_non_exhaustive()
+    }
+}
+
+// These functions are missing in `libc`.
+extern "C" {
+    fn os_workgroup_join(workgroup: OsWorkgroup, token: *mut JoinToken) -> libc::c_int;
+
+    fn os_workgroup_leave(workgroup: OsWorkgroup, token: *mut JoinToken);
+}
+
+/// The membership of the current thread in a workgroup, which is left when
+/// dropped. See [`join_current_thread_workgroup`].
+///
+/// A thread can only leave the workgroup it has joined itself, so the
+/// membership can't be sent to the other threads.
+#[derive(Debug)]
+pub struct WorkgroupMembership {
+    workgroup: OsWorkgroup,
+    // The token is boxed so that it keeps its address until the workgroup is
+    // left.
+    token: Box<JoinToken>,
+}
+
+impl WorkgroupMembership {
+    /// Returns the workgroup the current thread has joined.
+    pub fn workgroup(&self) -> OsWorkgroup {
+        self.workgroup
+    }
+
+    /// Leaves the workgroup, the same as dropping the membership.
+    pub fn leave(self) {}
+}
+
+impl crate::Promotion for WorkgroupMembership {
+    fn demote(self) -> Result<(), Error> {
+        self.leave();
+        Ok(())
+    }
+}
+
+impl Drop for WorkgroupMembership {
+    fn drop(&mut self) {
+        unsafe { os_workgroup_leave(self.workgroup, &mut *self.token) }
+    }
+}
+
+/// Joins the current thread to the workgroup until the returned membership is
+/// dropped.
+///
+/// A thread can only be a member of a single workgroup at a time, so joining
+/// another one fails with `EALREADY`. Joining a cancelled workgroup fails with
+/// `EINVAL`.
+///
+/// # Safety
+///
+/// The workgroup must be a valid `os_workgroup_t` and must stay alive until
+/// the membership is dropped.
+pub unsafe fn join_current_thread_workgroup(
+    workgroup: OsWorkgroup,
+) -> Result<WorkgroupMembership, Error> {
+    if workgroup.is_null() {
+        return Err(Error::Priority("The workgroup must not be null."));
+    }
+    let mut token = Box::new(JoinToken {
+        sig: 0,
+        opaque: [0; JOIN_TOKEN_SIZE],
+    });
+    match os_workgroup_join(workgroup, &mut *token) {
+        0 => Ok(WorkgroupMembership { workgroup, token }),
+        e => Err(Error::OS(e)),
+    }
+}
@@ -0,0 +1,155 @@
+//! This module defines the stub implementation used on the platforms without
+//! the thread scheduling, like `wasm32-unknown-unknown` and WASI.
+//!
+//! The crate compiles there, so that the code using it doesn't need to be
+//! gated, but all the scheduling operations fail with
+//! [`Error::UnsupportedPlatform`] and [`crate::capabilities`] reports nothing
+//! as supported. A backend registered with
+//! [`crate::backend::set_scheduling_backend`] can still schedule the threads
+//! spawned by the [`crate::ThreadBuilder`].
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{Error, ThreadPriority};
+
+/// An identifier of a thread, unique within the process. The platform has no
+/// native one, so it is assigned by the crate.
+pub type ThreadId = u64;
+
+/// Returns current thread id, assigned by the crate on the first call within
+/// the thread.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::thread_native_id;
+///
+/// assert_eq!(thread_native_id(), thread_native_id());
+/// ```
+pub fn thread_native_id() -> ThreadId {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+    thread_local! {
+        static ID: ThreadId = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    }
+
+    ID.with(|id| *id)
+}
+
+/// Sets the thread's priority. Always fails with
+/// [`Error::UnsupportedPlatform`].
+pub fn set_thread_priority(_native: ThreadId, _priority: ThreadPriority) -> Result<(), Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+/// Returns the thread's priority. Always fails with
+/// [`Error::UnsupportedPlatform`].
+pub fn get_thread_priority(_native: ThreadId) -> Result<ThreadPriority, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+/// Sets current thread's priority.
+/// For more info see [`set_thread_priority`].
+pub fn set_current_thread_priority(priority: ThreadPriority) -> Result<(), Error> {
+    set_thread_priority(thread_native_id(), priority)
+}
+
+/// Returns current thread's priority.
+/// For more info see [`get_thread_priority`].
+pub fn get_current_thread_priority() -> Result<ThreadPriority, Error> {
+    get_thread_priority(thread_native_id())
+}
+
+/// Sets current thread's priority and reads it back.
+/// For more info see [`set_thread_priority`].
+pub fn set_current_thread_priority_verified(
+    priority: ThreadPriority,
+) -> Result<ThreadPriority, Error> {
+    set_current_thread_priority(priority)?;
+    get_current_thread_priority()
+}
+
+/// Returns the thread's priority as a platform-independent score.
+/// Always fails with [`Error::UnsupportedPlatform`].
+pub fn effective_priority_score(_native: ThreadId) -> Result<u8, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+/// Checks whether the realtime priorities can be set, which they never can on
+/// this platform.
+pub fn can_set_realtime_priority() -> Result<bool, Error> {
+    Ok(false)
+}
+
+/// A guard restoring the current thread's priority when dropped.
+///
+/// The priority can't be read on this platform, so the guard can't be
+/// created.
+#[derive(Debug)]
+pub struct PriorityGuard {
+    priority: ThreadPriority,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl PriorityGuard {
+    /// Records the current thread's priority and sets the new one.
+    pub fn raise(priority: ThreadPriority) -> Result<Self, Error> {
+        let guard = Self::save()?;
+        set_current_thread_priority(priority)?;
+        Ok(guard)
+    }
+
+    /// Records the current thread's priority without changing it, so that it
+    /// is restored when the guard is dropped.
+    pub fn save() -> Result<Self, Error> {
+        Ok(Self {
+            priority: get_current_thread_priority()?,
+            _not_send: std::marker::PhantomData,
+        })
+    }
+
+    /// Restores the recorded priority, returning the result of doing so.
+    /// Dropping the guard does the same, but only logs the errors.
+    pub fn restore(self) -> Result<(), Error> {
+        let priority = self.priority;
+        std::mem::forget(self);
+        set_current_thread_priority(priority)
+    }
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        let _ = set_current_thread_priority(self.priority);
+    }
+}
+
+/// A helper trait for other threads to implement to be able to call methods
+/// on threads themselves.
+pub trait ThreadExt {
+    /// Gets the current thread's priority.
+    /// For more info read [`get_current_thread_priority`].
+    fn get_priority(&self) -> Result<ThreadPriority, Error> {
+        get_current_thread_priority()
+    }
+
+    /// Sets the current thread's priority.
+    /// For more info see [`ThreadPriority::set_for_current`].
+    fn set_priority(&self, priority: ThreadPriority) -> Result<(), Error> {
+        priority.set_for_current()
+    }
+
+    /// Returns the thread id assigned by the crate.
+    /// For more info read [`thread_native_id`].
+    fn get_native_id(&self) -> Result<ThreadId, Error>;
+}
+
+/// Auto-implementation of this trait for the [`std::thread::Thread`].
+impl ThreadExt for std::thread::Thread {
+    fn get_native_id(&self) -> Result<ThreadId, Error> {
+        if self.id() == std::thread::current().id() {
+            Ok(thread_native_id())
+        } else {
+            Err(Error::Priority("The `ThreadExt::get_native_id()` is currently limited to be called on the current thread."))
+        }
+    }
+}
@@ -4,29 +4,120 @@
 //! the windows threads, and this module provides
 //! better control over those.
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use winapi::ctypes::c_int;
-use winapi::shared::minwindef::DWORD;
+use winapi::shared::basetsd::DWORD_PTR;
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, FARPROC, FILETIME, LPCVOID, LPVOID, ULONG};
+use winapi::shared::winerror::{
+    ERROR_WORKING_SET_QUOTA, FACILITY_WIN32, HRESULT_CODE, HRESULT_FACILITY,
+};
+use winapi::um::avrt::{AvRevertMmThreadCharacteristics, AvSetMmThreadCharacteristicsW};
 use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::handleapi::{CloseHandle, DuplicateHandle};
+use winapi::um::libloaderapi::{GetModuleHandleW, GetProcAddress};
+use winapi::um::memoryapi::{VirtualLock, VirtualQuery, VirtualUnlock};
 use winapi::um::processthreadsapi::{
-    GetCurrentThread, GetThreadPriority, SetThreadIdealProcessor, SetThreadPriority,
-    SetThreadPriorityBoost,
+    GetCurrentProcess, GetCurrentProcessorNumberEx, GetCurrentThread, GetPriorityClass,
+    GetThreadIdealProcessorEx, GetThreadInformation, GetThreadPriority, GetThreadPriorityBoost,
+    GetThreadTimes, OpenProcessToken, OpenThread, SetPriorityClass, SetThreadIdealProcessor,
+    SetThreadIdealProcessorEx, SetThreadInformation, SetThreadPriority, SetThreadPriorityBoost,
+    ThreadMemoryPriority,
+};
+use winapi::um::processtopologyapi::{GetThreadGroupAffinity, SetThreadGroupAffinity};
+use winapi::um::realtimeapiset::QueryThreadCycleTime;
+use winapi::um::securitybaseapi::GetTokenInformation;
+use winapi::um::sysinfoapi::{GetLogicalProcessorInformationEx, GetSystemInfo, SYSTEM_INFO};
+use winapi::um::systemtopologyapi::GetNumaNodeProcessorMaskEx;
+use winapi::um::winbase::{
+    self, GetActiveProcessorCount, GetActiveProcessorGroupCount, GetMaximumProcessorCount,
+    GetProcessAffinityMask, GetProcessWorkingSetSize, LocalFree, LookupPrivilegeValueW,
+    SetProcessWorkingSetSize, SetThreadAffinityMask,
+};
+use winapi::um::winnt::{
+    self, RelationProcessorCore, GROUP_AFFINITY, HANDLE, HRESULT, LUID, MEMORY_BASIC_INFORMATION,
+    PCWSTR, PROCESSOR_NUMBER, PWSTR, SYSTEM_CPU_SET_INFORMATION,
+    SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX, TOKEN_PRIVILEGES,
 };
-use winapi::um::winbase;
-use winapi::um::winnt::HANDLE;
 
 use crate::{Error, ThreadPriority};
 
+pub mod power;
+pub mod process;
+pub use process::{configure_main_thread, Process, ProcessThread};
+
+// These functions are either missing in `winapi` or declared there with wrong types.
+#[link(name = "kernel32")]
+extern "system" {
+    fn SetProcessAffinityMask(process: HANDLE, mask: DWORD_PTR) -> BOOL;
+
+    fn GetSystemCpuSetInformation(
+        information: *mut SYSTEM_CPU_SET_INFORMATION,
+        buffer_length: ULONG,
+        returned_length: *mut ULONG,
+        process: HANDLE,
+        flags: ULONG,
+    ) -> BOOL;
+
+    fn GetThreadSelectedCpuSets(
+        thread: HANDLE,
+        cpu_set_ids: *mut ULONG,
+        cpu_set_id_count: ULONG,
+        required_id_count: *mut ULONG,
+    ) -> BOOL;
+}
+
+// These functions are only available since Windows 10 1607, so they are looked
+// up when called instead of being linked, see `kernel32_function`.
+type GetThreadDescriptionFn = unsafe extern "system" fn(HANDLE, *mut PWSTR) -> HRESULT;
+type SetThreadDescriptionFn = unsafe extern "system" fn(HANDLE, PCWSTR) -> HRESULT;
+
+/// Looks up the function exported by `kernel32.dll`. The name must be
+/// null-terminated. Returns `None` if the function is missing, which is the
+/// case for the functions added in the later versions of Windows.
+fn kernel32_function(name: &[u8]) -> Option<FARPROC> {
+    let module: Vec<u16> = "kernel32.dll"
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    unsafe {
+        let module = GetModuleHandleW(module.as_ptr());
+        if module.is_null() {
+            return None;
+        }
+        let function = GetProcAddress(module, name.as_ptr() as _);
+        (!function.is_null()).then_some(function)
+    }
+}
+
+/// Converts the failed `HRESULT` into an error holding the Win32 error code it
+/// wraps, like the one returned by `GetLastError`. The `HRESULT`s of the other
+/// facilities are kept as they are.
+fn hresult_error(result: HRESULT) -> Error {
+    if HRESULT_FACILITY(result) == FACILITY_WIN32 {
+        Error::OS(HRESULT_CODE(result))
+    } else {
+        Error::OS(result)
+    }
+}
+
 /// An alias type for specifying the ideal processor.
 /// Used in the WinAPI for affinity control.
 pub type IdealProcessor = DWORD;
 
 /// An alias type for a thread id.
+///
+/// This is a raw handle, which the crate neither duplicates nor closes, so that
+/// the same `ThreadId` is used by the functions on all the platforms. Use
+/// [`OwnedThreadId`] to get a handle which can be sent to other threads and is
+/// closed when dropped, and pass [`OwnedThreadId::as_raw`] to the functions.
 pub type ThreadId = HANDLE;
 
 /// The WinAPI priority representation. Check out MSDN for more info:
 /// <https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-setthreadpriority>
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WinAPIThreadPriority {
     /// Begin background processing mode. The system lowers the resource
     /// scheduling priorities of the thread so that it can perform background
@@ -100,6 +191,17 @@ impl std::convert::TryFrom<ThreadPriority> for WinAPIThreadPriority {
                     ))
                 }
             },
+            ThreadPriority::Fraction(fraction) => {
+                // The same range as the one from `Min` to `Max`.
+                const PRIORITIES: [WinAPIThreadPriority; 5] = [
+                    WinAPIThreadPriority::Lowest,
+                    WinAPIThreadPriority::BelowNormal,
+                    WinAPIThreadPriority::Normal,
+                    WinAPIThreadPriority::AboveNormal,
+                    WinAPIThreadPriority::Highest,
+                ];
+                PRIORITIES[fraction.interpolate(0, PRIORITIES.len() as i32 - 1) as usize]
+            }
             ThreadPriority::Max => WinAPIThreadPriority::Highest,
         })
     }
@@ -130,6 +232,194 @@ impl From<WinAPIThreadPriority> for crate::ThreadPriorityOsValue {
     }
 }
 
+impl ThreadPriority {
+    /// Checks whether this and the other priority result in the same WinAPI
+    /// priority, for example, `ThreadPriority::Crossplatform(23)` and
+    /// `ThreadPriority::Crossplatform(30)` are both
+    /// [`WinAPIThreadPriority::BelowNormal`].
+    ///
+    /// This allows to compare priorities without knowing the mapping
+    /// used on the platform.
+    ///
+    /// Only the mapping of Windows is used, so this doesn't tell whether the
+    /// priorities are equivalent on the other platforms.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// assert_eq!(
+    ///     ThreadPriority::Min.is_equivalent(ThreadPriority::Max),
+    ///     Ok(false)
+    /// );
+    /// ```
+    pub fn is_equivalent(self, other: ThreadPriority) -> Result<bool, Error> {
+        Ok(WinAPIThreadPriority::try_from(self)? == WinAPIThreadPriority::try_from(other)?)
+    }
+}
+
+/// The WinAPI process priority class. The thread priorities are relative to the
+/// priority class of their process. Check out MSDN for more info:
+/// <https://docs.microsoft.com/en-us/windows/win32/procthread/scheduling-priorities>
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ProcessPriorityClass {
+    /// Process whose threads run only when the system is idle.
+    Idle = winbase::IDLE_PRIORITY_CLASS,
+    /// Process that has priority above [`ProcessPriorityClass::Idle`] but below
+    /// [`ProcessPriorityClass::Normal`].
+    BelowNormal = winbase::BELOW_NORMAL_PRIORITY_CLASS,
+    /// Process with no special scheduling needs.
+    Normal = winbase::NORMAL_PRIORITY_CLASS,
+    /// Process that has priority above [`ProcessPriorityClass::Normal`] but below
+    /// [`ProcessPriorityClass::High`].
+    AboveNormal = winbase::ABOVE_NORMAL_PRIORITY_CLASS,
+    /// Process that performs time-critical tasks that must be executed immediately.
+    High = winbase::HIGH_PRIORITY_CLASS,
+    /// Process that has the highest possible priority. The threads of such a
+    /// process preempt the threads of all other processes, including the
+    /// operating system processes performing important tasks.
+    ///
+    /// # Warning
+    /// Requires the `SeIncreaseBasePriorityPrivilege` privilege, otherwise the
+    /// [`ProcessPriorityClass::High`] class is silently used instead.
+    Realtime = winbase::REALTIME_PRIORITY_CLASS,
+}
+
+impl std::convert::TryFrom<DWORD> for ProcessPriorityClass {
+    type Error = crate::Error;
+
+    fn try_from(priority_class: DWORD) -> Result<Self, Self::Error> {
+        Ok(match priority_class {
+            winbase::IDLE_PRIORITY_CLASS => ProcessPriorityClass::Idle,
+            winbase::BELOW_NORMAL_PRIORITY_CLASS => ProcessPriorityClass::BelowNormal,
+            winbase::NORMAL_PRIORITY_CLASS => ProcessPriorityClass::Normal,
+            winbase::ABOVE_NORMAL_PRIORITY_CLASS => ProcessPriorityClass::AboveNormal,
+            winbase::HIGH_PRIORITY_CLASS => ProcessPriorityClass::High,
+            winbase::REALTIME_PRIORITY_CLASS => ProcessPriorityClass::Realtime,
+            _ => return Err(Error::Priority("Priority class couldn't be parsed")),
+        })
+    }
+}
+
+/// Returns the priority class of the current process.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_process_priority_class().is_ok());
+/// ```
+pub fn get_process_priority_class() -> Result<ProcessPriorityClass, Error> {
+    unsafe {
+        let ret = GetPriorityClass(GetCurrentProcess());
+        if ret != 0 {
+            ProcessPriorityClass::try_from(ret)
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Sets the priority class of the current process.
+///
+/// * May require privileges
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let priority_class = get_process_priority_class().unwrap();
+/// assert!(set_process_priority_class(priority_class).is_ok());
+/// ```
+pub fn set_process_priority_class(priority_class: ProcessPriorityClass) -> Result<(), Error> {
+    unsafe {
+        if SetPriorityClass(GetCurrentProcess(), priority_class as DWORD) != 0 {
+            Ok(())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Checks whether the process holds the `SeIncreaseBasePriorityPrivilege`
+/// privilege, so that the scheduling strategy can be chosen without probing.
+/// Without it, the [`ProcessPriorityClass::Realtime`] is silently replaced
+/// with the [`ProcessPriorityClass::High`]. The privilege
+/// is usually held by the elevated processes only.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let priority_class = if can_set_realtime_priority().unwrap() {
+///     ProcessPriorityClass::Realtime
+/// } else {
+///     ProcessPriorityClass::High
+/// };
+/// ```
+pub fn can_set_realtime_priority() -> Result<bool, Error> {
+    let name: Vec<u16> = winnt::SE_INC_BASE_PRIORITY_NAME
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    unsafe {
+        let mut luid: LUID = std::mem::zeroed();
+        if LookupPrivilegeValueW(std::ptr::null(), name.as_ptr(), &mut luid) == 0 {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+
+        let mut token: HANDLE = std::ptr::null_mut();
+        if OpenProcessToken(GetCurrentProcess(), winnt::TOKEN_QUERY, &mut token) == 0 {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+        let mut length: DWORD = 0;
+        // The first call only reports the size of the buffer needed.
+        GetTokenInformation(
+            token,
+            winnt::TokenPrivileges,
+            std::ptr::null_mut(),
+            0,
+            &mut length,
+        );
+        let mut buffer = vec![0u64; (length as usize + 7) / 8];
+        let ret = GetTokenInformation(
+            token,
+            winnt::TokenPrivileges,
+            buffer.as_mut_ptr() as LPVOID,
+            length,
+            &mut length,
+        );
+        let error = GetLastError();
+        CloseHandle(token);
+        if ret == 0 {
+            return Err(Error::OS(error as i32));
+        }
+
+        let privileges = &*(buffer.as_ptr() as *const TOKEN_PRIVILEGES);
+        let privileges = std::slice::from_raw_parts(
+            privileges.Privileges.as_ptr(),
+            privileges.PrivilegeCount as usize,
+        );
+        Ok(privileges.iter().any(|privilege| {
+            privilege.Luid.LowPart == luid.LowPart && privilege.Luid.HighPart == luid.HighPart
+        }))
+    }
+}
+
 /// Sets thread's priority and schedule policy.
 ///
 /// * May require privileges
@@ -252,10 +542,269 @@ pub fn get_current_thread_priority() -> Result<ThreadPriority, Error> {
     }
 }
 
+/// Sets current thread's priority and reads it back, returning the priority
+/// actually applied, as the requested one may be adjusted by the system. For
+/// example, without the `SeIncreaseBasePriorityPrivilege` privilege, the
+/// realtime priorities can't be used.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(set_current_thread_priority_verified(ThreadPriority::Min).is_ok());
+/// ```
+pub fn set_current_thread_priority_verified(
+    priority: ThreadPriority,
+) -> Result<ThreadPriority, Error> {
+    set_current_thread_priority(priority)?;
+    get_current_thread_priority()
+}
+
+/// A guard raising the current thread's priority and restoring the previous
+/// priority when dropped.
+///
+/// The guard is bound to the thread which created it and therefore can't be sent
+/// to other threads.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// {
+///     let _guard = PriorityGuard::raise(ThreadPriority::Max).unwrap();
+///     // The critical section runs with the raised priority.
+/// }
+/// // The previous priority is restored here.
+/// ```
+#[derive(Debug)]
+pub struct PriorityGuard {
+    priority: ThreadPriority,
+    restored: bool,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl PriorityGuard {
+    /// Records the current thread's priority and sets the new one.
+    /// For more info read [`set_current_thread_priority`].
+    pub fn raise(priority: ThreadPriority) -> Result<Self, Error> {
+        let mut guard = Self::save()?;
+        if let Err(e) = set_current_thread_priority(priority) {
+            guard.restored = true;
+            return Err(e);
+        }
+        Ok(guard)
+    }
+
+    /// Records the current thread's priority without changing it, so that it is
+    /// restored when the guard is dropped, including during unwinding.
+    pub fn save() -> Result<Self, Error> {
+        Ok(Self {
+            priority: get_current_thread_priority()?,
+            restored: false,
+            _not_send: std::marker::PhantomData,
+        })
+    }
+
+    /// Restores the recorded priority, returning the result of doing so.
+    /// Dropping the guard does the same, but only logs the errors.
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.restored = true;
+        set_current_thread_priority(self.priority)
+    }
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        if self.restored {
+            return;
+        }
+        if let Err(e) = set_current_thread_priority(self.priority) {
+            log::warn!(
+                "Couldn't restore the priority of the thread named {:?}: {:?}",
+                std::thread::current().name(),
+                e,
+            );
+        }
+    }
+}
+
+/// A guard temporarily lowering the current thread's priority to
+/// [`WinAPIThreadPriority::Normal`] if it is higher, and restoring it when
+/// dropped.
+///
+/// This prevents a high-priority thread from starving the system while doing
+/// blocking or unbounded non-critical work, like logging or I/O. If the thread's
+/// priority isn't higher than normal, the guard does nothing.
+///
+/// The guard is bound to the thread which created it and therefore can't be sent
+/// to other threads.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// {
+///     let _guard = RealtimeRelinquishGuard::relinquish().unwrap();
+///     // The non-critical work runs with the normal priority.
+/// }
+/// // The previous priority is restored here.
+/// ```
+#[derive(Debug)]
+pub struct RealtimeRelinquishGuard {
+    guard: Option<PriorityGuard>,
+}
+
+impl RealtimeRelinquishGuard {
+    /// Records the current thread's priority and lowers it to the normal one if
+    /// it is higher.
+    pub fn relinquish() -> Result<Self, Error> {
+        // The priorities are signed, so they are compared as such rather than
+        // by the order of `WinAPIThreadPriority`, which follows the `DWORD`s.
+        let raised = match get_current_thread_priority()? {
+            ThreadPriority::Os(priority) => match WinAPIThreadPriority::try_from(priority.0) {
+                Ok(
+                    WinAPIThreadPriority::BackgroundModeBegin
+                    | WinAPIThreadPriority::BackgroundModeEnd,
+                )
+                | Err(_) => false,
+                Ok(_) => priority.0 as i32 > winbase::THREAD_PRIORITY_NORMAL as i32,
+            },
+            _ => false,
+        };
+        if !raised {
+            return Ok(Self { guard: None });
+        }
+        PriorityGuard::raise(ThreadPriority::Os(WinAPIThreadPriority::Normal.into()))
+            .map(|guard| Self { guard: Some(guard) })
+    }
+
+    /// Returns whether the priority was lowered, which is `false` when it
+    /// wasn't higher than normal.
+    pub fn is_relinquished(&self) -> bool {
+        self.guard.is_some()
+    }
+
+    /// Restores the previous priority, returning the result of doing so.
+    /// Dropping the guard does the same, but only logs the errors.
+    pub fn restore(mut self) -> Result<(), Error> {
+        self.guard.take().map_or(Ok(()), PriorityGuard::restore)
+    }
+}
+
+/// A real handle of a thread of the process, which is closed when dropped.
+///
+/// Unlike [`thread_native_id`], which returns a pseudo-handle valid only for the
+/// calling thread, this allows to change the priority, the boost, the ideal
+/// processor and so on of any thread of the process using the raw handle
+/// returned by [`OwnedThreadId::as_raw`]. The raw handle is borrowed from the
+/// owner, so it mustn't be used after the owner is dropped.
+///
+/// The functions of the crate keep taking the raw [`ThreadId`], so this is an
+/// addition to it rather than a replacement.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let main = Process::current().main_thread_id().unwrap();
+/// let thread = OwnedThreadId::from_os_id(main).unwrap();
+/// assert!(get_thread_priority(thread.as_raw()).is_ok());
+/// ```
+#[derive(Debug)]
+pub struct OwnedThreadId(ThreadId);
+
+// A thread handle isn't bound to the thread which opened it.
+unsafe impl Send for OwnedThreadId {}
+unsafe impl Sync for OwnedThreadId {}
+
+impl OwnedThreadId {
+    /// Opens the thread with the id, as returned by `GetCurrentThreadId` or
+    /// [`Process::thread_ids`], for querying and changing its scheduling.
+    ///
+    /// If there's an error, a result of
+    /// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+    pub fn from_os_id(id: u32) -> Result<Self, Error> {
+        let handle = unsafe {
+            OpenThread(
+                winnt::THREAD_SET_INFORMATION | winnt::THREAD_QUERY_INFORMATION,
+                FALSE,
+                id,
+            )
+        };
+        if handle.is_null() {
+            Err(Error::OS(unsafe { GetLastError() } as i32))
+        } else {
+            Ok(Self(handle))
+        }
+    }
+
+    /// Returns a real handle of the current thread, duplicated from the
+    /// pseudo-handle returned by [`thread_native_id`]. Unlike the pseudo-handle,
+    /// it keeps referring to the current thread when sent to or stored by other
+    /// threads.
+    ///
+    /// If there's an error, a result of
+    /// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+    pub fn current() -> Result<Self, Error> {
+        let mut handle = std::ptr::null_mut();
+        let ret = unsafe {
+            DuplicateHandle(
+                GetCurrentProcess(),
+                GetCurrentThread(),
+                GetCurrentProcess(),
+                &mut handle,
+                0,
+                FALSE,
+                winnt::DUPLICATE_SAME_ACCESS,
+            )
+        };
+        if ret == 0 {
+            Err(Error::OS(unsafe { GetLastError() } as i32))
+        } else {
+            Ok(Self(handle))
+        }
+    }
+
+    /// Returns the raw handle to pass to the functions of this crate. The
+    /// handle is valid as long as `self` is alive.
+    pub fn as_raw(&self) -> ThreadId {
+        self.0
+    }
+}
+
+impl Drop for OwnedThreadId {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+impl std::os::windows::io::AsRawHandle for OwnedThreadId {
+    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+        self.0 as _
+    }
+}
+
+impl From<OwnedThreadId> for std::os::windows::io::OwnedHandle {
+    /// Gives up the ownership of the handle without closing it.
+    fn from(thread: OwnedThreadId) -> Self {
+        use std::os::windows::io::FromRawHandle;
+
+        let thread = std::mem::ManuallyDrop::new(thread);
+        unsafe { Self::from_raw_handle(thread.0 as _) }
+    }
+}
+
 /// Returns current thread id, which is the current OS's native handle.
 /// It may or may not be equal or even related to rust's thread id,
 /// there is absolutely no guarantee for that.
 ///
+/// The handle is a pseudo-handle, which refers to whichever thread uses it, so
+/// it mustn't be sent to or stored for the other threads. Use
+/// [`OwnedThreadId::current`] for that instead.
+///
 /// # Usage
 ///
 /// ```rust
@@ -267,7 +816,34 @@ pub fn thread_native_id() -> ThreadId {
     unsafe { GetCurrentThread() }
 }
 
-/// Disables or enables the ability of the system to temporarily boost the priority of a thread.
+/// The times of a thread, see [`get_thread_times`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ThreadTimes {
+    /// The time the thread has spent executing in the kernel mode.
+    pub kernel: Duration,
+    /// The time the thread has spent executing in the user mode.
+    pub user: Duration,
+    /// The moment the thread was created.
+    pub creation: SystemTime,
+}
+
+impl ThreadTimes {
+    /// Returns the CPU time the thread has consumed in both the user and the
+    /// kernel modes.
+    pub fn cpu_time(&self) -> Duration {
+        self.kernel + self.user
+    }
+}
+
+/// Converts the `FILETIME` into the number of the 100-nanosecond intervals.
+fn filetime_ticks(time: &FILETIME) -> u64 {
+    (time.dwHighDateTime as u64) << 32 | time.dwLowDateTime as u64
+}
+
+/// Returns the CPU times of the thread along with its creation time, which
+/// allows to confirm that the demoted threads actually consume less CPU.
+///
+/// The handle must have the `THREAD_QUERY_LIMITED_INFORMATION` access right.
 ///
 /// If there's an error, a result of
 /// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
@@ -277,76 +853,1498 @@ pub fn thread_native_id() -> ThreadId {
 /// ```rust
 /// use thread_priority::*;
 ///
-/// let thread_id = thread_native_id();
-/// assert!(set_thread_priority_boost(thread_id, false).is_ok())
+/// let times = get_thread_times(thread_native_id()).unwrap();
+/// assert!(times.creation <= std::time::SystemTime::now());
 /// ```
-pub fn set_thread_priority_boost(native: ThreadId, enabled: bool) -> Result<(), Error> {
-    unsafe {
-        if SetThreadPriorityBoost(native, enabled as i32) != 0 {
-            Ok(())
-        } else {
-            Err(Error::OS(GetLastError() as i32))
-        }
+pub fn get_thread_times(native: ThreadId) -> Result<ThreadTimes, Error> {
+    let mut times: [FILETIME; 4] = unsafe { std::mem::zeroed() };
+    let [creation, exit, kernel, user] = &mut times;
+    if unsafe { GetThreadTimes(native, creation, exit, kernel, user) } == 0 {
+        return Err(Error::OS(unsafe { GetLastError() } as i32));
     }
+    // The times are in the 100-nanosecond intervals, the creation time is
+    // counted since January 1, 1601 (UTC).
+    const UNIX_EPOCH_TICKS: u64 = 116_444_736_000_000_000;
+    let creation = filetime_ticks(creation);
+    let creation = if creation >= UNIX_EPOCH_TICKS {
+        UNIX_EPOCH + Duration::from_nanos((creation - UNIX_EPOCH_TICKS) * 100)
+    } else {
+        UNIX_EPOCH - Duration::from_nanos((UNIX_EPOCH_TICKS - creation) * 100)
+    };
+    Ok(ThreadTimes {
+        kernel: Duration::from_nanos(filetime_ticks(kernel) * 100),
+        user: Duration::from_nanos(filetime_ticks(user) * 100),
+        creation,
+    })
 }
 
-/// Disables or enables the ability of the system to temporarily boost the priority of a current thread.
+/// Returns the number of the CPU clock cycles the thread has consumed in both
+/// the user and the kernel modes, which is more precise than the CPU times of
+/// [`get_thread_times`] and suits the high-resolution accounting.
+///
+/// The cycles are counted by the processor timestamp counter and therefore
+/// can't be reliably converted into time.
 ///
 /// If there's an error, a result of
 /// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
 ///
-/// This is a short-hand of the `set_thread_priority_boost` function for the current thread.
-pub fn set_current_thread_priority_boost(enabled: bool) -> Result<(), Error> {
-    set_thread_priority_boost(thread_native_id(), enabled)
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_thread_cycle_time(thread_native_id()).is_ok());
+/// ```
+pub fn get_thread_cycle_time(native: ThreadId) -> Result<u64, Error> {
+    let mut cycles = 0;
+    if unsafe { QueryThreadCycleTime(native, &mut cycles) } != 0 {
+        Ok(cycles)
+    } else {
+        Err(Error::OS(unsafe { GetLastError() } as i32))
+    }
 }
 
-/// Sets a preferred processor for a thread. The system schedules threads on their preferred
-/// processors whenever possible.
-///
-/// On a system with more than 64 processors, this function sets the preferred processor to a
-/// logical processor in the processor group to which the calling thread is assigned. Use the
-/// `SetThreadIdealProcessorEx` function to specify a processor group and preferred processor.
+/// Returns the CPU time the current thread has consumed so far in both the
+/// user and the kernel modes, which doesn't include the time it was waiting or
+/// preempted.
 ///
 /// If there's an error, a result of
 /// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
-/// On success, the function returns a previously assigned processor.
 ///
-/// # Note
-/// The processor number starts with zero.
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_current_thread_cpu_time().is_ok());
+/// ```
+pub fn get_current_thread_cpu_time() -> Result<Duration, Error> {
+    get_thread_times(thread_native_id()).map(|times| times.cpu_time())
+}
+
+/// Returns the CPU time the thread has consumed so far in both the user and
+/// the kernel modes, see [`get_thread_times`].
+///
+/// The handle must have the `THREAD_QUERY_LIMITED_INFORMATION` access right.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
 ///
 /// # Usage
 ///
 /// ```rust
 /// use thread_priority::*;
 ///
-/// let thread_id = thread_native_id();
-/// assert!(set_thread_ideal_processor(thread_id, 0).is_ok())
+/// let native = thread_native_id();
+/// assert!(thread_cpu_time(native).unwrap() <= get_current_thread_cpu_time().unwrap());
 /// ```
-pub fn set_thread_ideal_processor(
-    native: ThreadId,
-    ideal_processor: IdealProcessor,
-) -> Result<IdealProcessor, Error> {
-    unsafe {
-        let ret = SetThreadIdealProcessor(native, ideal_processor);
-        if ret == IdealProcessor::max_value() - 1 {
-            Err(Error::OS(GetLastError() as i32))
-        } else {
-            Ok(ret)
-        }
-    }
+pub fn thread_cpu_time(native: ThreadId) -> Result<Duration, Error> {
+    get_thread_times(native).map(|times| times.cpu_time())
 }
 
-/// Sets a preferred processor for a current thread. The system schedules threads on their preferred
-/// processors whenever possible.
+/// The resource usage of a thread, see [`get_current_thread_rusage`].
 ///
-/// This is a short-hand of the `set_thread_ideal_processor` function for the current thread.
-pub fn set_current_thread_ideal_processor(
-    ideal_processor: IdealProcessor,
-) -> Result<IdealProcessor, Error> {
-    set_thread_ideal_processor(thread_native_id(), ideal_processor)
+/// Unlike on unix, the context switches and the memory usage aren't
+/// accounted per thread, so only the CPU times are available.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ThreadResourceUsage {
+    /// The time the thread has spent executing in the user mode.
+    pub user_time: Duration,
+    /// The time the thread has spent executing in the kernel mode.
+    pub system_time: Duration,
 }
 
-impl std::convert::TryFrom<u32> for crate::ThreadPriorityOsValue {
+impl ThreadResourceUsage {
+    /// Returns the CPU time the thread has consumed in both the user and the
+    /// kernel modes.
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+}
+
+/// Returns the resource usage of the current thread, the analogue of
+/// `getrusage(RUSAGE_THREAD)` built from the CPU times of
+/// [`get_thread_times`].
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let usage = get_current_thread_rusage().unwrap();
+/// println!("The thread has run for {:?}", usage.cpu_time());
+/// ```
+pub fn get_current_thread_rusage() -> Result<ThreadResourceUsage, Error> {
+    let times = get_thread_times(thread_native_id())?;
+    Ok(ThreadResourceUsage {
+        user_time: times.user,
+        system_time: times.kernel,
+    })
+}
+
+/// Returns the OS-level name (description) of the thread, which is the one shown in
+/// debuggers and tracing tools.
+///
+/// This is the same as [`get_thread_description`], named consistently with the
+/// other platforms.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread = std::thread::Builder::new()
+///     .name("MyThread".to_owned())
+///     .spawn(|| get_thread_name(thread_native_id()))
+///     .unwrap();
+/// assert_eq!(thread.join().unwrap(), Ok("MyThread".to_owned()));
+/// ```
+pub fn get_thread_name(native: ThreadId) -> Result<String, Error> {
+    get_thread_description(native)
+}
+
+/// Returns the description of the thread, which is the name shown in debuggers
+/// and tracing tools, like WinDbg and WPA.
+///
+/// Fails with [`Error::UnsupportedPlatform`] before Windows 10 1607. If there's
+/// an error, the Win32 error code wrapped by the `HRESULT` of
+/// [`GetThreadDescription`](https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-getthreaddescription)
+/// is returned, or the `HRESULT` itself if it doesn't wrap one.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// assert!(set_thread_description(thread_id, "MyThread").is_ok());
+/// assert_eq!(get_thread_description(thread_id), Ok("MyThread".to_owned()));
+/// ```
+pub fn get_thread_description(native: ThreadId) -> Result<String, Error> {
+    let get_thread_description =
+        kernel32_function(b"GetThreadDescription\0").ok_or(Error::UnsupportedPlatform)?;
+    unsafe {
+        let get_thread_description: GetThreadDescriptionFn =
+            std::mem::transmute(get_thread_description);
+        let mut description = std::ptr::null_mut();
+        let ret = get_thread_description(native, &mut description);
+        if ret < 0 {
+            return Err(hresult_error(ret));
+        }
+        let len = (0..).take_while(|&i| *description.offset(i) != 0).count();
+        let name = String::from_utf16_lossy(std::slice::from_raw_parts(description, len));
+        LocalFree(description as _);
+        Ok(name)
+    }
+}
+
+/// Sets the description of the thread, which is the name shown in debuggers
+/// and tracing tools, like WinDbg and WPA.
+///
+/// The description must not contain null characters. Fails with
+/// [`Error::UnsupportedPlatform`] before Windows 10 1607. If there's an error,
+/// the Win32 error code wrapped by the `HRESULT` of
+/// [`SetThreadDescription`](https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-setthreaddescription)
+/// is returned, or the `HRESULT` itself if it doesn't wrap one.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(set_thread_description(thread_native_id(), "MyThread").is_ok());
+/// ```
+pub fn set_thread_description(native: ThreadId, description: &str) -> Result<(), Error> {
+    if description.contains('\0') {
+        return Err(Error::Priority(
+            "The thread description must not contain null characters.",
+        ));
+    }
+    let set_thread_description =
+        kernel32_function(b"SetThreadDescription\0").ok_or(Error::UnsupportedPlatform)?;
+    let description: Vec<u16> = description
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let ret = unsafe {
+        let set_thread_description: SetThreadDescriptionFn =
+            std::mem::transmute(set_thread_description);
+        set_thread_description(native, description.as_ptr())
+    };
+    if ret < 0 {
+        Err(hresult_error(ret))
+    } else {
+        Ok(())
+    }
+}
+
+/// Disables or enables the ability of the system to temporarily boost the priority of a thread.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// assert!(set_thread_priority_boost(thread_id, false).is_ok())
+/// ```
+pub fn set_thread_priority_boost(native: ThreadId, enabled: bool) -> Result<(), Error> {
+    unsafe {
+        // The function accepts whether the boost should be disabled.
+        if SetThreadPriorityBoost(native, !enabled as i32) != 0 {
+            Ok(())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Disables or enables the ability of the system to temporarily boost the priority of a current thread.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// This is a short-hand of the `set_thread_priority_boost` function for the current thread.
+pub fn set_current_thread_priority_boost(enabled: bool) -> Result<(), Error> {
+    set_thread_priority_boost(thread_native_id(), enabled)
+}
+
+/// Returns whether the ability of the system to temporarily boost the priority of a thread is
+/// enabled.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let enabled = get_thread_priority_boost(thread_id).unwrap();
+/// assert!(set_thread_priority_boost(thread_id, !enabled).is_ok());
+/// assert_eq!(get_thread_priority_boost(thread_id), Ok(!enabled));
+/// assert!(set_thread_priority_boost(thread_id, enabled).is_ok());
+/// ```
+pub fn get_thread_priority_boost(native: ThreadId) -> Result<bool, Error> {
+    let mut disabled: BOOL = FALSE;
+    unsafe {
+        // The function returns whether the boost is disabled.
+        if GetThreadPriorityBoost(native, &mut disabled) != 0 {
+            Ok(disabled == FALSE)
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Returns whether the ability of the system to temporarily boost the priority of a current
+/// thread is enabled.
+///
+/// This is a short-hand of the `get_thread_priority_boost` function for the current thread.
+pub fn get_current_thread_priority_boost() -> Result<bool, Error> {
+    get_thread_priority_boost(thread_native_id())
+}
+
+/// The `THREAD_BASIC_INFORMATION` structure returned by `NtQueryInformationThread`.
+#[repr(C)]
+#[allow(dead_code)]
+struct ThreadBasicInformation {
+    exit_status: i32,
+    teb_base_address: LPVOID,
+    client_id: [HANDLE; 2],
+    affinity_mask: usize,
+    priority: i32,
+    base_priority: i32,
+}
+
+/// The `ThreadBasicInformation` value of the `THREADINFOCLASS` enumeration.
+const THREAD_BASIC_INFORMATION_CLASS: c_int = 0;
+
+#[link(name = "ntdll")]
+extern "system" {
+    fn NtQueryInformationThread(
+        thread: HANDLE,
+        information_class: c_int,
+        information: LPVOID,
+        information_length: ULONG,
+        return_length: *mut ULONG,
+    ) -> i32;
+
+    fn RtlNtStatusToDosError(status: i32) -> ULONG;
+}
+
+/// The priority of a thread as seen by the scheduler, in the range `0..=31`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ThreadDynamicPriority {
+    /// The base priority, computed from the priority class of the process and
+    /// the priority of the thread.
+    pub base: i32,
+    /// The current priority, which the system may have temporarily raised above
+    /// the base one, for example, after the thread was woken up by an I/O
+    /// completion or got the foreground window's input.
+    pub current: i32,
+}
+
+impl ThreadDynamicPriority {
+    /// Returns how much the current priority is raised above the base one.
+    pub fn boost(&self) -> i32 {
+        self.current - self.base
+    }
+
+    /// Returns `true` if the system has boosted the priority of the thread.
+    pub fn is_boosted(&self) -> bool {
+        self.boost() > 0
+    }
+}
+
+/// Returns the base and the current dynamic priority of the thread, telling
+/// whether the system has temporarily boosted it.
+///
+/// If there's an error, the `NTSTATUS` of
+/// [`NtQueryInformationThread`](https://docs.microsoft.com/en-us/windows/win32/api/winternl/nf-winternl-ntqueryinformationthread)
+/// converted to a system error code is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let priority = get_thread_dynamic_priority(thread_native_id()).unwrap();
+/// assert!(priority.current >= priority.base);
+/// ```
+pub fn get_thread_dynamic_priority(native: ThreadId) -> Result<ThreadDynamicPriority, Error> {
+    let mut information = std::mem::MaybeUninit::<ThreadBasicInformation>::zeroed();
+    unsafe {
+        let status = NtQueryInformationThread(
+            native,
+            THREAD_BASIC_INFORMATION_CLASS,
+            information.as_mut_ptr() as LPVOID,
+            std::mem::size_of::<ThreadBasicInformation>() as ULONG,
+            std::ptr::null_mut(),
+        );
+        if status < 0 {
+            return Err(Error::OS(RtlNtStatusToDosError(status) as i32));
+        }
+        let information = information.assume_init();
+        Ok(ThreadDynamicPriority {
+            base: information.base_priority,
+            current: information.priority,
+        })
+    }
+}
+
+/// Returns the base and the current dynamic priority of the current thread.
+///
+/// This is a short-hand of the `get_thread_dynamic_priority` function for the current thread.
+pub fn get_current_thread_dynamic_priority() -> Result<ThreadDynamicPriority, Error> {
+    get_thread_dynamic_priority(thread_native_id())
+}
+
+impl ThreadDynamicPriority {
+    /// Returns the score of the scheduling strength in the `[0; 100]` range,
+    /// comparable across the platforms, see [`effective_priority_score`].
+    pub fn effective_score(&self) -> u8 {
+        // The priorities up to 15 are of the normal classes, while the higher
+        // ones are of the realtime class.
+        match self.current.clamp(0, 31) {
+            p @ 0..=15 => (p * 50 / 15) as u8,
+            p => (50 + (p - 15) * 50 / 16) as u8,
+        }
+    }
+}
+
+/// Returns the score of the thread's scheduling strength in the `[0; 100]`
+/// range, computed from its current dynamic priority, which accounts for the
+/// priority class of the process, the priority of the thread and the boost.
+/// This allows to compare the threads across the platforms:
+///
+/// * `0` is the idle scheduling;
+/// * `1..=50` are the normal priority classes;
+/// * `51..=100` is the realtime priority class.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let score = effective_priority_score(thread_native_id()).unwrap();
+/// assert!(score <= 100);
+/// ```
+pub fn effective_priority_score(native: ThreadId) -> Result<u8, Error> {
+    get_thread_dynamic_priority(native).map(|priority| priority.effective_score())
+}
+
+/// A registration of the current thread within a task of the Multimedia Class
+/// Scheduler Service (MMCSS), which raises the priority of the thread according
+/// to the task's configuration, like `"Audio"` or `"Pro Audio"`. The tasks are
+/// listed in the
+/// `HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Windows NT\CurrentVersion\Multimedia\SystemProfile\Tasks`
+/// registry key.
+///
+/// The thread leaves the task when the registration is dropped. The registration
+/// is bound to the thread which created it and therefore can't be sent to other
+/// threads.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust,no_run
+/// use thread_priority::*;
+///
+/// {
+///     let _task = MmcssTask::join("Pro Audio").unwrap();
+///     // The audio processing runs with the priority of the task.
+/// }
+/// // The thread has left the task here.
+/// ```
+#[derive(Debug)]
+pub struct MmcssTask {
+    handle: HANDLE,
+    left: bool,
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl MmcssTask {
+    /// Makes the current thread join the task.
+    pub fn join(task: &str) -> Result<Self, Error> {
+        let task: Vec<u16> = task.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut task_index: DWORD = 0;
+        unsafe {
+            let handle = AvSetMmThreadCharacteristicsW(task.as_ptr(), &mut task_index);
+            if handle.is_null() {
+                Err(Error::OS(GetLastError() as i32))
+            } else {
+                Ok(Self {
+                    handle,
+                    left: false,
+                    _not_send: std::marker::PhantomData,
+                })
+            }
+        }
+    }
+
+    /// Makes the current thread leave the task, returning the result of doing
+    /// so. Dropping the task does the same, but only logs the errors.
+    pub fn leave(mut self) -> Result<(), Error> {
+        self.left = true;
+        self.leave_inner()
+    }
+
+    fn leave_inner(&self) -> Result<(), Error> {
+        if unsafe { AvRevertMmThreadCharacteristics(self.handle) } == 0 {
+            return Err(Error::OS(unsafe { GetLastError() } as i32));
+        }
+        Ok(())
+    }
+}
+
+impl crate::Promotion for MmcssTask {
+    fn demote(self) -> Result<(), Error> {
+        self.leave()
+    }
+}
+
+impl Drop for MmcssTask {
+    fn drop(&mut self) {
+        if self.left {
+            return;
+        }
+        if let Err(e) = self.leave_inner() {
+            log::warn!(
+                "Couldn't leave the MMCSS task for the thread named {:?}: {:?}",
+                std::thread::current().name(),
+                e,
+            );
+        }
+    }
+}
+
+/// The memory priority of a thread. It determines how long the pages the thread
+/// uses stay in the working set of the process before being trimmed. Check out MSDN
+/// for more info:
+/// <https://docs.microsoft.com/en-us/windows/win32/api/processthreadsapi/nf-processthreadsapi-setthreadinformation>
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum MemoryPriority {
+    /// The lowest memory priority.
+    VeryLow = winnt::MEMORY_PRIORITY_VERY_LOW,
+    /// Low memory priority.
+    Low = winnt::MEMORY_PRIORITY_LOW,
+    /// Medium memory priority.
+    Medium = winnt::MEMORY_PRIORITY_MEDIUM,
+    /// Below normal memory priority.
+    BelowNormal = winnt::MEMORY_PRIORITY_BELOW_NORMAL,
+    /// Normal memory priority, the default one for all the threads.
+    Normal = winnt::MEMORY_PRIORITY_NORMAL,
+}
+
+impl std::convert::TryFrom<ULONG> for MemoryPriority {
+    type Error = crate::Error;
+
+    fn try_from(priority: ULONG) -> Result<Self, Self::Error> {
+        Ok(match priority {
+            winnt::MEMORY_PRIORITY_VERY_LOW => MemoryPriority::VeryLow,
+            winnt::MEMORY_PRIORITY_LOW => MemoryPriority::Low,
+            winnt::MEMORY_PRIORITY_MEDIUM => MemoryPriority::Medium,
+            winnt::MEMORY_PRIORITY_BELOW_NORMAL => MemoryPriority::BelowNormal,
+            winnt::MEMORY_PRIORITY_NORMAL => MemoryPriority::Normal,
+            _ => return Err(Error::Priority("Memory priority couldn't be parsed")),
+        })
+    }
+}
+
+/// Sets the memory priority of the thread.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// assert!(set_thread_memory_priority(thread_id, MemoryPriority::Low).is_ok());
+/// assert!(set_thread_memory_priority(thread_id, MemoryPriority::Normal).is_ok());
+/// ```
+pub fn set_thread_memory_priority(native: ThreadId, priority: MemoryPriority) -> Result<(), Error> {
+    let mut priority = priority as ULONG;
+    unsafe {
+        if SetThreadInformation(
+            native,
+            ThreadMemoryPriority,
+            &mut priority as *mut ULONG as LPVOID,
+            std::mem::size_of::<ULONG>() as DWORD,
+        ) != 0
+        {
+            Ok(())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Returns the memory priority of the thread.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert_eq!(
+///     get_thread_memory_priority(thread_native_id()),
+///     Ok(MemoryPriority::Normal)
+/// );
+/// ```
+pub fn get_thread_memory_priority(native: ThreadId) -> Result<MemoryPriority, Error> {
+    let mut priority: ULONG = 0;
+    unsafe {
+        if GetThreadInformation(
+            native,
+            ThreadMemoryPriority,
+            &mut priority as *mut ULONG as LPVOID,
+            std::mem::size_of::<ULONG>() as DWORD,
+        ) != 0
+        {
+            MemoryPriority::try_from(priority)
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Locks the pages into the working set of the process with `VirtualLock`,
+/// growing the working set with `SetProcessWorkingSetSize` if it is too small.
+fn lock_pages(address: LPVOID, size: usize) -> Result<(), Error> {
+    unsafe {
+        if VirtualLock(address, size) != 0 {
+            return Ok(());
+        }
+        if GetLastError() != ERROR_WORKING_SET_QUOTA {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+
+        let process = GetCurrentProcess();
+        let (mut minimum, mut maximum) = (0, 0);
+        if GetProcessWorkingSetSize(process, &mut minimum, &mut maximum) == 0 {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+        // The range may span one more page at each of its ends.
+        let mut info: SYSTEM_INFO = std::mem::zeroed();
+        GetSystemInfo(&mut info);
+        let grow = size + 2 * info.dwPageSize as usize;
+        if SetProcessWorkingSetSize(process, minimum + grow, maximum + grow) == 0 {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+        if VirtualLock(address, size) != 0 {
+            Ok(())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Locks the memory into the working set of the process, so that accessing it
+/// never causes a page fault, growing the working set if it is too small to
+/// hold the locked pages.
+///
+/// A realtime thread still misses its deadlines when its memory is paged out,
+/// as the page faults are served at the speed of the storage. The memory stays
+/// locked until it is unlocked or freed.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let buffer = vec![0u8; 1 << 16];
+/// assert!(lock_memory_range(&buffer).is_ok());
+/// // The realtime work.
+/// assert!(unlock_memory_range(&buffer).is_ok());
+/// ```
+pub fn lock_memory_range(memory: &[u8]) -> Result<(), Error> {
+    lock_pages(memory.as_ptr() as LPVOID, memory.len())
+}
+
+/// Unlocks the memory locked by [`lock_memory_range`].
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+pub fn unlock_memory_range(memory: &[u8]) -> Result<(), Error> {
+    if unsafe { VirtualUnlock(memory.as_ptr() as LPVOID, memory.len()) } != 0 {
+        Ok(())
+    } else {
+        Err(Error::OS(unsafe { GetLastError() } as i32))
+    }
+}
+
+/// Locks the stack of the current thread into the working set of the process,
+/// see [`lock_memory_range`].
+///
+/// Unlike on Unix, the future allocations of the process can't be locked, so
+/// only the part of the stack already in use is locked and the buffers the
+/// thread works with have to be locked with [`lock_memory_range`].
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(lock_memory().is_ok());
+/// ```
+pub fn lock_memory() -> Result<(), Error> {
+    let marker = 0u8;
+    unsafe {
+        let mut info: MEMORY_BASIC_INFORMATION = std::mem::zeroed();
+        if VirtualQuery(
+            &marker as *const u8 as LPCVOID,
+            &mut info,
+            std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        ) == 0
+        {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+        // The region is the committed part of the stack, from the guard page
+        // up to the base of the stack.
+        lock_pages(info.BaseAddress, info.RegionSize)
+    }
+}
+
+/// Sets a preferred processor for a thread. The system schedules threads on their preferred
+/// processors whenever possible.
+///
+/// On a system with more than 64 processors, this function sets the preferred processor to a
+/// logical processor in the processor group to which the calling thread is assigned. Use
+/// [`set_thread_ideal_processor_ex`] to specify a processor group and preferred processor.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+/// On success, the function returns a previously assigned processor.
+///
+/// If the processor isn't within the process affinity, [`Error::CpusNotAllowed`]
+/// listing the allowed processors is returned.
+///
+/// # Note
+/// The processor number starts with zero.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// assert!(set_thread_ideal_processor(thread_id, 0).is_ok())
+/// ```
+pub fn set_thread_ideal_processor(
+    native: ThreadId,
+    ideal_processor: IdealProcessor,
+) -> Result<IdealProcessor, Error> {
+    validate_allowed_cpus(&[ideal_processor as usize])?;
+    unsafe {
+        let ret = SetThreadIdealProcessor(native, ideal_processor);
+        if ret == IdealProcessor::max_value() - 1 {
+            Err(Error::OS(GetLastError() as i32))
+        } else {
+            Ok(ret)
+        }
+    }
+}
+
+/// Sets a preferred processor for a current thread. The system schedules threads on their preferred
+/// processors whenever possible.
+///
+/// This is a short-hand of the `set_thread_ideal_processor` function for the current thread.
+pub fn set_current_thread_ideal_processor(
+    ideal_processor: IdealProcessor,
+) -> Result<IdealProcessor, Error> {
+    set_thread_ideal_processor(thread_native_id(), ideal_processor)
+}
+
+/// A logical processor addressed by its processor group and its number within
+/// that group, which makes all the processors of the machines with more than
+/// 64 of them addressable.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ProcessorNumber {
+    /// The processor group.
+    pub group: u16,
+    /// The number of the logical processor within the group.
+    pub number: u8,
+}
+
+impl From<PROCESSOR_NUMBER> for ProcessorNumber {
+    fn from(processor: PROCESSOR_NUMBER) -> Self {
+        Self {
+            group: processor.Group,
+            number: processor.Number,
+        }
+    }
+}
+
+impl From<ProcessorNumber> for PROCESSOR_NUMBER {
+    fn from(processor: ProcessorNumber) -> Self {
+        Self {
+            Group: processor.group,
+            Number: processor.number,
+            Reserved: 0,
+        }
+    }
+}
+
+/// Sets a preferred processor for a thread, which may be in any processor group, returning the
+/// previously assigned one. The system schedules threads on their preferred processors whenever
+/// possible.
+///
+/// Unlike [`set_thread_ideal_processor`], which is confined to the processor group the thread is
+/// assigned to, this works on the machines with more than 64 processors. The processor isn't
+/// validated against the process affinity, as the latter only covers a single group.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let previous = get_thread_ideal_processor_ex(thread_id).unwrap();
+/// let first = ProcessorNumber { group: previous.group, number: 0 };
+/// assert_eq!(set_thread_ideal_processor_ex(thread_id, first), Ok(previous));
+/// assert_eq!(get_thread_ideal_processor_ex(thread_id), Ok(first));
+/// ```
+pub fn set_thread_ideal_processor_ex(
+    native: ThreadId,
+    ideal_processor: ProcessorNumber,
+) -> Result<ProcessorNumber, Error> {
+    let mut ideal_processor = PROCESSOR_NUMBER::from(ideal_processor);
+    unsafe {
+        let mut previous: PROCESSOR_NUMBER = std::mem::zeroed();
+        if SetThreadIdealProcessorEx(native, &mut ideal_processor, &mut previous) != 0 {
+            Ok(previous.into())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Sets a preferred processor for a current thread, which may be in any processor group.
+///
+/// This is a short-hand of the `set_thread_ideal_processor_ex` function for the current thread.
+pub fn set_current_thread_ideal_processor_ex(
+    ideal_processor: ProcessorNumber,
+) -> Result<ProcessorNumber, Error> {
+    set_thread_ideal_processor_ex(thread_native_id(), ideal_processor)
+}
+
+/// Returns the preferred processor of a thread along with its processor group, see
+/// [`set_thread_ideal_processor_ex`].
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(get_thread_ideal_processor_ex(thread_native_id()).is_ok());
+/// ```
+pub fn get_thread_ideal_processor_ex(native: ThreadId) -> Result<ProcessorNumber, Error> {
+    unsafe {
+        let mut processor: PROCESSOR_NUMBER = std::mem::zeroed();
+        if GetThreadIdealProcessorEx(native, &mut processor) != 0 {
+            Ok(processor.into())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Returns the preferred processor of a current thread along with its processor group.
+///
+/// This is a short-hand of the `get_thread_ideal_processor_ex` function for the current thread.
+pub fn get_current_thread_ideal_processor_ex() -> Result<ProcessorNumber, Error> {
+    get_thread_ideal_processor_ex(thread_native_id())
+}
+
+/// Returns the processor group of the NUMA node along with the affinity mask
+/// of its processors within that group.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (group, mask) = get_numa_node_group_affinity(0).unwrap();
+/// assert_ne!(mask, 0);
+/// ```
+pub fn get_numa_node_group_affinity(node: u16) -> Result<(u16, usize), Error> {
+    unsafe {
+        let mut affinity: GROUP_AFFINITY = std::mem::zeroed();
+        if GetNumaNodeProcessorMaskEx(node, &mut affinity) == 0 {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+        if affinity.Mask == 0 {
+            return Err(Error::Priority("The NUMA node has no processors."));
+        }
+        Ok((affinity.Group, affinity.Mask))
+    }
+}
+
+/// Sets a preferred processor for a thread within the NUMA node, returning the
+/// processor chosen, see [`set_thread_ideal_processor_ex`].
+///
+/// The preferred processor of the thread is kept if it is already within the
+/// node. Otherwise, the processors of the node are handed out in turns, so
+/// that the threads preferring the same node are spread across it.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let processor = set_thread_ideal_numa_node(thread_id, 0).unwrap();
+/// assert_eq!(get_thread_ideal_processor_ex(thread_id), Ok(processor));
+/// ```
+pub fn set_thread_ideal_numa_node(native: ThreadId, node: u16) -> Result<ProcessorNumber, Error> {
+    static NEXT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    let (group, mask) = get_numa_node_group_affinity(node)?;
+    let current = get_thread_ideal_processor_ex(native)?;
+    if current.group == group && mask & (1 << current.number) != 0 {
+        return Ok(current);
+    }
+
+    let processors: Vec<u8> = (0..usize::BITS as u8)
+        .filter(|number| mask & (1 << number) != 0)
+        .collect();
+    let next = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let processor = ProcessorNumber {
+        group,
+        number: processors[next % processors.len()],
+    };
+    set_thread_ideal_processor_ex(native, processor)?;
+    Ok(processor)
+}
+
+/// Sets a preferred processor for a current thread within the NUMA node.
+///
+/// This is a short-hand of the `set_thread_ideal_numa_node` function for the current thread.
+pub fn set_current_thread_ideal_numa_node(node: u16) -> Result<ProcessorNumber, Error> {
+    set_thread_ideal_numa_node(thread_native_id(), node)
+}
+
+/// Converts the list of processor indices into an affinity mask.
+fn affinity_mask_from_cores(cores: &[usize]) -> Result<DWORD_PTR, Error> {
+    cores.iter().try_fold(0, |mask, &core| {
+        if core < DWORD_PTR::BITS as usize {
+            Ok(mask | (1 << core))
+        } else {
+            Err(Error::Priority(
+                "The processor index doesn't fit into the affinity mask.",
+            ))
+        }
+    })
+}
+
+/// Converts the affinity mask into the list of processor indices.
+fn cores_from_affinity_mask(mask: DWORD_PTR) -> Vec<usize> {
+    (0..DWORD_PTR::BITS as usize)
+        .filter(|core| mask & (1 << core) != 0)
+        .collect()
+}
+
+/// Checks that all the processors requested are within the process affinity.
+fn validate_allowed_cpus(cores: &[usize]) -> Result<(), Error> {
+    let allowed = get_process_affinity()?;
+    if cores.iter().all(|core| allowed.contains(core)) {
+        Ok(())
+    } else {
+        Err(Error::CpusNotAllowed(allowed))
+    }
+}
+
+/// Sets the processor affinity of the thread within its processor group,
+/// confining it to the provided processors.
+///
+/// If any of the processors isn't within the process affinity,
+/// [`Error::CpusNotAllowed`] listing the allowed processors is returned.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let cores = get_thread_affinity(thread_id).unwrap();
+/// assert!(set_thread_affinity(thread_id, &cores[..1]).is_ok());
+/// assert!(set_thread_affinity(thread_id, &cores).is_ok());
+/// ```
+pub fn set_thread_affinity(native: ThreadId, cores: &[usize]) -> Result<(), Error> {
+    validate_allowed_cpus(cores)?;
+    let mask = affinity_mask_from_cores(cores)?;
+    unsafe {
+        if SetThreadAffinityMask(native, mask) != 0 {
+            Ok(())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Moves the thread to the processor group and confines it to the provided
+/// processors within that group.
+///
+/// Unlike [`set_thread_affinity`], this allows to use the processors beyond the
+/// first 64 on the systems with several processor groups. See
+/// [`processor_topology`] for the groups available.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let cores = get_thread_affinity(thread_id).unwrap();
+/// assert!(set_thread_group_affinity(thread_id, 0, &cores).is_ok());
+/// ```
+pub fn set_thread_group_affinity(
+    native: ThreadId,
+    group: u16,
+    cores: &[usize],
+) -> Result<(), Error> {
+    let mask = affinity_mask_from_cores(cores)?;
+    set_thread_group_affinity_mask(native, group, mask).map(|_| ())
+}
+
+/// Moves the thread to the processor group and sets its raw affinity mask
+/// within that group, returning the previous group and mask. Bit `n` of the
+/// mask stands for the processor `n` of the group.
+///
+/// This makes all the processors of the machines with more than 64 of them
+/// addressable, unlike [`set_thread_affinity_mask`] and
+/// [`set_thread_ideal_processor`], which are confined to the current group of
+/// the thread.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let (group, mask) = get_thread_group_affinity(thread_id).unwrap();
+/// assert_eq!(
+///     set_thread_group_affinity_mask(thread_id, group, mask),
+///     Ok((group, mask))
+/// );
+/// ```
+pub fn set_thread_group_affinity_mask(
+    native: ThreadId,
+    group: u16,
+    mask: usize,
+) -> Result<(u16, usize), Error> {
+    unsafe {
+        let mut affinity: GROUP_AFFINITY = std::mem::zeroed();
+        affinity.Mask = mask;
+        affinity.Group = group;
+        let mut previous: GROUP_AFFINITY = std::mem::zeroed();
+        if SetThreadGroupAffinity(native, &affinity, &mut previous) != 0 {
+            Ok((previous.Group, previous.Mask))
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Returns the processor group of the thread along with its raw affinity mask
+/// within that group, see [`set_thread_group_affinity_mask`].
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (group, mask) = get_thread_group_affinity(thread_native_id()).unwrap();
+/// assert_ne!(mask, 0);
+/// ```
+pub fn get_thread_group_affinity(native: ThreadId) -> Result<(u16, usize), Error> {
+    unsafe {
+        let mut affinity: GROUP_AFFINITY = std::mem::zeroed();
+        if GetThreadGroupAffinity(native, &mut affinity) != 0 {
+            Ok((affinity.Group, affinity.Mask))
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Returns the processor affinity of the thread as the list of processors
+/// within its processor group the thread may run on.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(!get_thread_affinity(thread_native_id()).unwrap().is_empty());
+/// ```
+pub fn get_thread_affinity(native: ThreadId) -> Result<Vec<usize>, Error> {
+    get_thread_affinity_mask(native).map(cores_from_affinity_mask)
+}
+
+/// Returns the processor the current thread is running on, within its
+/// processor group, as the affinity functions number the processors. The
+/// thread may be moved to another processor right after the call, unless it is
+/// confined to a single one.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let cores = get_thread_affinity(thread_native_id()).unwrap();
+/// assert!(cores.contains(&current_cpu().unwrap()));
+/// ```
+pub fn current_cpu() -> Result<usize, Error> {
+    let mut processor = PROCESSOR_NUMBER {
+        Group: 0,
+        Number: 0,
+        Reserved: 0,
+    };
+    unsafe { GetCurrentProcessorNumberEx(&mut processor) };
+    Ok(processor.Number as usize)
+}
+
+/// Sets the raw processor affinity mask of the thread within its processor
+/// group, returning the previous mask. Bit `n` of the mask stands for the
+/// processor `n`.
+///
+/// Unlike [`set_thread_affinity`], the mask isn't validated against the process
+/// affinity, which suits the hard pinning scenarios, like latency benchmarking,
+/// restoring the exact previous mask afterwards.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let thread_id = thread_native_id();
+/// let mask = get_thread_affinity_mask(thread_id).unwrap();
+/// let lowest = mask & mask.wrapping_neg();
+/// assert_eq!(set_thread_affinity_mask(thread_id, lowest), Ok(mask));
+/// assert_eq!(set_thread_affinity_mask(thread_id, mask), Ok(lowest));
+/// ```
+pub fn set_thread_affinity_mask(native: ThreadId, mask: usize) -> Result<usize, Error> {
+    unsafe {
+        match SetThreadAffinityMask(native, mask) {
+            0 => Err(Error::OS(GetLastError() as i32)),
+            previous => Ok(previous),
+        }
+    }
+}
+
+/// Returns the raw processor affinity mask of the thread within its processor
+/// group, see [`set_thread_affinity_mask`].
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert_ne!(get_thread_affinity_mask(thread_native_id()), Ok(0));
+/// ```
+pub fn get_thread_affinity_mask(native: ThreadId) -> Result<usize, Error> {
+    get_thread_group_affinity(native).map(|(_, mask)| mask)
+}
+
+/// Sets the processor affinity of the whole process.
+///
+/// On a system with more than 64 processors, the affinity is limited to a single
+/// processor group.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let cores = get_process_affinity().unwrap();
+/// assert!(set_process_affinity(&cores).is_ok());
+/// ```
+pub fn set_process_affinity(cores: &[usize]) -> Result<(), Error> {
+    let mask = affinity_mask_from_cores(cores)?;
+    unsafe {
+        if SetProcessAffinityMask(GetCurrentProcess(), mask) != 0 {
+            Ok(())
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// Returns the processor affinity of the process.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert!(!get_process_affinity().unwrap().is_empty());
+/// ```
+pub fn get_process_affinity() -> Result<Vec<usize>, Error> {
+    let mut process_mask: DWORD_PTR = 0;
+    let mut system_mask: DWORD_PTR = 0;
+    unsafe {
+        if GetProcessAffinityMask(GetCurrentProcess(), &mut process_mask, &mut system_mask) != 0 {
+            Ok(cores_from_affinity_mask(process_mask))
+        } else {
+            Err(Error::OS(GetLastError() as i32))
+        }
+    }
+}
+
+/// A processor group. Systems with more than 64 logical processors split them
+/// into several groups, and most of the affinity-related WinAPI functions only
+/// operate within a single group.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ProcessorGroup {
+    /// The group number.
+    pub group: u16,
+    /// The number of active logical processors in the group.
+    pub active_processors: u32,
+    /// The maximum number of logical processors the group may have.
+    pub maximum_processors: u32,
+}
+
+/// A physical processor core.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ProcessorCore {
+    /// The group the core belongs to.
+    pub group: u16,
+    /// The logical processors of the core within its group, the same
+    /// representation as used by [`get_thread_affinity`] and [`set_thread_affinity`].
+    pub processors: Vec<usize>,
+    /// The efficiency class of the core. On hybrid processors, the cores with a
+    /// higher class are more performant, while the cores with the lowest class
+    /// are the most power-efficient ones. On other processors it is always `0`.
+    pub efficiency_class: u8,
+    /// Whether the core has several logical processors (simultaneous multithreading).
+    pub smt: bool,
+}
+
+/// The processor topology of the system.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ProcessorTopology {
+    /// The processor groups of the system.
+    pub groups: Vec<ProcessorGroup>,
+    /// The physical processor cores of the system.
+    pub cores: Vec<ProcessorCore>,
+}
+
+impl ProcessorTopology {
+    /// Returns the total number of active logical processors in all the groups.
+    pub fn active_processor_count(&self) -> u32 {
+        self.groups.iter().map(|g| g.active_processors).sum()
+    }
+
+    /// Returns whether the logical processor with the provided number exists
+    /// within the group, so that it can be used as an ideal processor or in an
+    /// affinity mask.
+    pub fn contains(&self, group: u16, processor: usize) -> bool {
+        self.cores
+            .iter()
+            .any(|core| core.group == group && core.processors.contains(&processor))
+    }
+}
+
+/// Returns the processor topology: processor groups, their sizes and the
+/// physical cores with their efficiency classes.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let topology = processor_topology().unwrap();
+/// assert!(topology.active_processor_count() > 0);
+/// assert!(topology.contains(0, 0));
+/// ```
+pub fn processor_topology() -> Result<ProcessorTopology, Error> {
+    // Corresponds to `LTP_PC_SMT`.
+    const SMT_FLAG: u8 = 0x1;
+
+    let groups = unsafe {
+        (0..GetActiveProcessorGroupCount())
+            .map(|group| ProcessorGroup {
+                group,
+                active_processors: GetActiveProcessorCount(group),
+                maximum_processors: GetMaximumProcessorCount(group),
+            })
+            .collect()
+    };
+
+    let mut cores = Vec::new();
+    unsafe {
+        let mut length: DWORD = 0;
+        // The first call only reports the size of the buffer needed.
+        GetLogicalProcessorInformationEx(RelationProcessorCore, std::ptr::null_mut(), &mut length);
+        let mut buffer = vec![0u64; (length as usize + 7) / 8];
+        // `winapi` declares the buffer as the non-extended structure.
+        if GetLogicalProcessorInformationEx(
+            RelationProcessorCore,
+            buffer.as_mut_ptr() as *mut _,
+            &mut length,
+        ) == 0
+        {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+
+        let start = buffer.as_ptr() as *const u8;
+        let mut offset = 0;
+        while offset < length as usize {
+            let info = &*(start.add(offset) as *const SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX);
+            let processor = info.u.Processor();
+            let masks = std::slice::from_raw_parts(
+                processor.GroupMask.as_ptr(),
+                processor.GroupCount as usize,
+            );
+            for mask in masks {
+                cores.push(ProcessorCore {
+                    group: mask.Group,
+                    processors: cores_from_affinity_mask(mask.Mask),
+                    efficiency_class: processor.EfficiencyClass,
+                    smt: processor.Flags & SMT_FLAG != 0,
+                });
+            }
+            offset += info.Size as usize;
+        }
+    }
+
+    Ok(ProcessorTopology { groups, cores })
+}
+
+/// A CPU set, which is the way Windows 10 and later describe the logical
+/// processors to select for the threads, see
+/// <https://docs.microsoft.com/en-us/windows/win32/procthread/cpu-sets>.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CpuSet {
+    /// The id of the CPU set, which isn't the same as the processor number.
+    pub id: u32,
+    /// The processor group of the logical processor.
+    pub group: u16,
+    /// The number of the logical processor within its group.
+    pub logical_processor_index: u8,
+    /// The index of the physical core the logical processor belongs to.
+    pub core_index: u8,
+    /// The index of the last level cache shared by the logical processor.
+    pub last_level_cache_index: u8,
+    /// The NUMA node of the logical processor.
+    pub numa_node_index: u8,
+    /// The efficiency class of the core, see [`ProcessorCore::efficiency_class`].
+    pub efficiency_class: u8,
+    /// Whether the logical processor is parked to save power.
+    pub parked: bool,
+    /// Whether the CPU set is allocated to a process exclusively.
+    pub allocated: bool,
+    /// Whether the CPU set is allocated to the current process exclusively.
+    pub allocated_to_current_process: bool,
+    /// Whether the logical processor is reserved for the realtime threads.
+    pub realtime: bool,
+}
+
+/// Returns the CPU sets of the system.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let cpu_sets = system_cpu_sets().unwrap();
+/// assert!(!cpu_sets.is_empty());
+/// ```
+pub fn system_cpu_sets() -> Result<Vec<CpuSet>, Error> {
+    let mut cpu_sets = Vec::new();
+    unsafe {
+        let mut length: ULONG = 0;
+        // The first call only reports the size of the buffer needed.
+        GetSystemCpuSetInformation(std::ptr::null_mut(), 0, &mut length, GetCurrentProcess(), 0);
+        let mut buffer = vec![0u64; (length as usize + 7) / 8];
+        if GetSystemCpuSetInformation(
+            buffer.as_mut_ptr() as *mut SYSTEM_CPU_SET_INFORMATION,
+            length,
+            &mut length,
+            GetCurrentProcess(),
+            0,
+        ) == 0
+        {
+            return Err(Error::OS(GetLastError() as i32));
+        }
+
+        let start = buffer.as_ptr() as *const u8;
+        let mut offset = 0;
+        while offset < length as usize {
+            let info = &*(start.add(offset) as *const SYSTEM_CPU_SET_INFORMATION);
+            if info.Type == winnt::CpuSetInformation {
+                let cpu_set = &info.CpuSet;
+                cpu_sets.push(CpuSet {
+                    id: cpu_set.Id,
+                    group: cpu_set.Group,
+                    logical_processor_index: cpu_set.LogicalProcessorIndex,
+                    core_index: cpu_set.CoreIndex,
+                    last_level_cache_index: cpu_set.LastLevelCacheIndex,
+                    numa_node_index: cpu_set.NumaNodeIndex,
+                    efficiency_class: cpu_set.EfficiencyClass,
+                    parked: cpu_set.Parked() != 0,
+                    allocated: cpu_set.Allocated() != 0,
+                    allocated_to_current_process: cpu_set.AllocatedToTargetProcess() != 0,
+                    realtime: cpu_set.RealTime() != 0,
+                });
+            }
+            offset += info.Size as usize;
+        }
+    }
+    Ok(cpu_sets)
+}
+
+/// Returns the CPU sets explicitly selected for the thread. An empty list means
+/// no CPU sets are selected, so the thread may run on any of the CPU sets
+/// allowed for the process.
+///
+/// If there's an error, a result of
+/// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// assert_eq!(get_thread_selected_cpu_sets(thread_native_id()), Ok(vec![]));
+/// ```
+pub fn get_thread_selected_cpu_sets(native: ThreadId) -> Result<Vec<CpuSet>, Error> {
+    let mut ids: Vec<ULONG> = Vec::new();
+    loop {
+        let mut required: ULONG = 0;
+        let ret = unsafe {
+            GetThreadSelectedCpuSets(native, ids.as_mut_ptr(), ids.len() as ULONG, &mut required)
+        };
+        if ret != 0 {
+            ids.truncate(required as usize);
+            break;
+        }
+        let error = unsafe { GetLastError() };
+        if error != winapi::shared::winerror::ERROR_INSUFFICIENT_BUFFER {
+            return Err(Error::OS(error as i32));
+        }
+        // The selection may change between the calls, so it is retried.
+        ids.resize(required as usize, 0);
+    }
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(system_cpu_sets()?
+        .into_iter()
+        .filter(|cpu_set| ids.contains(&cpu_set.id))
+        .collect())
+}
+
+impl std::convert::TryFrom<u32> for crate::ThreadPriorityOsValue {
     type Error = ();
 
     fn try_from(value: u32) -> Result<Self, Self::Error> {
@@ -427,6 +2425,18 @@ pub trait ThreadExt {
     fn set_priority_boost(&self, enabled: bool) -> Result<(), Error> {
         set_current_thread_priority_boost(enabled)
     }
+
+    /// Returns whether current thread's priority boost is enabled.
+    /// For more info see [`get_current_thread_priority_boost`].
+    ///
+    /// ```rust
+    /// use thread_priority::*;
+    ///
+    /// assert!(std::thread::current().get_priority_boost().is_ok());
+    /// ```
+    fn get_priority_boost(&self) -> Result<bool, Error> {
+        get_current_thread_priority_boost()
+    }
 }
 
 /// Auto-implementation of this trait for the [`std::thread::Thread`].
@@ -439,3 +2449,105 @@ impl ThreadExt for std::thread::Thread {
         }
     }
 }
+
+/// Allows controlling the scheduling of a spawned thread from the thread
+/// owning its [`std::thread::JoinHandle`], for example from a supervisor or a pool.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let (sender, receiver) = std::sync::mpsc::channel::<()>();
+/// let join_handle = std::thread::spawn(move || receiver.recv());
+///
+/// assert!(join_handle.set_priority(ThreadPriority::Min).is_ok());
+/// assert!(join_handle.get_priority().is_ok());
+///
+/// sender.send(()).unwrap();
+/// join_handle.join().unwrap().unwrap();
+/// ```
+pub trait JoinHandleExt {
+    /// Returns the windows handle of the spawned thread.
+    fn get_native_id(&self) -> ThreadId;
+
+    /// Gets the spawned thread's priority.
+    /// For more info read [`get_thread_priority`].
+    fn get_priority(&self) -> Result<ThreadPriority, Error> {
+        get_thread_priority(self.get_native_id())
+    }
+
+    /// Sets the spawned thread's priority.
+    /// For more info read [`set_thread_priority`].
+    fn set_priority(&self, priority: ThreadPriority) -> Result<(), Error> {
+        set_thread_priority(self.get_native_id(), priority)
+    }
+
+    /// Sets the spawned thread's ideal processor.
+    /// For more info read [`set_thread_ideal_processor`].
+    fn set_ideal_processor(
+        &self,
+        ideal_processor: IdealProcessor,
+    ) -> Result<IdealProcessor, Error> {
+        set_thread_ideal_processor(self.get_native_id(), ideal_processor)
+    }
+
+    /// Sets the spawned thread's priority boost.
+    /// For more info read [`set_thread_priority_boost`].
+    fn set_priority_boost(&self, enabled: bool) -> Result<(), Error> {
+        set_thread_priority_boost(self.get_native_id(), enabled)
+    }
+
+    /// Returns whether the spawned thread's priority boost is enabled.
+    /// For more info read [`get_thread_priority_boost`].
+    fn get_priority_boost(&self) -> Result<bool, Error> {
+        get_thread_priority_boost(self.get_native_id())
+    }
+
+    /// Gets the spawned thread's processor affinity.
+    /// For more info read [`get_thread_affinity`].
+    fn get_affinity(&self) -> Result<Vec<usize>, Error> {
+        get_thread_affinity(self.get_native_id())
+    }
+
+    /// Sets the spawned thread's processor affinity.
+    /// For more info read [`set_thread_affinity`].
+    fn set_affinity(&self, cores: &[usize]) -> Result<(), Error> {
+        set_thread_affinity(self.get_native_id(), cores)
+    }
+}
+
+/// Auto-implementation of this trait for the [`std::thread::JoinHandle`].
+impl<T> JoinHandleExt for std::thread::JoinHandle<T> {
+    fn get_native_id(&self) -> ThreadId {
+        std::os::windows::io::AsRawHandle::as_raw_handle(self) as ThreadId
+    }
+}
+
+/// Allows starting the child processes with the scheduling chosen up front.
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// let status = std::process::Command::new("cmd")
+///     .args(["/C", "exit"])
+///     .priority_class(ProcessPriorityClass::BelowNormal)
+///     .status()
+///     .unwrap();
+/// assert!(status.success());
+/// ```
+pub trait CommandExt {
+    /// Creates the child process in the priority class, so that it never runs
+    /// with the priority class of its parent.
+    ///
+    /// This sets the process creation flags, replacing the ones set with
+    /// [`std::os::windows::process::CommandExt::creation_flags`] before.
+    ///
+    /// * The [`ProcessPriorityClass::Realtime`] class requires privileges
+    fn priority_class(&mut self, priority_class: ProcessPriorityClass) -> &mut Self;
+}
+
+/// Auto-implementation of this trait for the [`std::process::Command`].
+impl CommandExt for std::process::Command {
+    fn priority_class(&mut self, priority_class: ProcessPriorityClass) -> &mut Self {
+        std::os::windows::process::CommandExt::creation_flags(self, priority_class as DWORD)
+    }
+}
@@ -0,0 +1,226 @@
+//! This module allows to adapt the thread priorities to the power state of the
+//! system, for example, relaxing the priorities of the background threads when
+//! the battery saver is turned on and reasserting them once it is turned off.
+//!
+//! Only the power saving state (`GUID_POWER_SAVING_STATUS`) is watched. The
+//! foreground and the session changes, like the display being turned off or
+//! the user switching to another session, aren't handled.
+//!
+//! ```rust,no_run
+//! use std::os::windows::io::AsRawHandle;
+//! use std::sync::Arc;
+//! use thread_priority::windows::power::{PowerAwareThreads, PowerSavingMonitor};
+//! use thread_priority::*;
+//!
+//! let thread = std::thread::spawn(|| {
+//!     // Some background work.
+//! });
+//!
+//! let threads = Arc::new(PowerAwareThreads::default());
+//! threads.register(
+//!     thread.as_raw_handle() as ThreadId,
+//!     ThreadPriority::Os(WinAPIThreadPriority::Normal.into()),
+//!     ThreadPriority::Os(WinAPIThreadPriority::Lowest.into()),
+//! );
+//! let _monitor = PowerSavingMonitor::watch(threads).unwrap();
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use winapi::shared::guiddef::GUID;
+use winapi::shared::minwindef::{DWORD, ULONG};
+use winapi::um::powersetting::{
+    PowerSettingRegisterNotification, PowerSettingUnregisterNotification,
+};
+use winapi::um::powrprof::{DEVICE_NOTIFY_CALLBACK, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS};
+use winapi::um::winnt::{GUID_POWER_SAVING_STATUS, PVOID};
+use winapi::um::winuser::{HPOWERNOTIFY, PBT_POWERSETTINGCHANGE, POWERBROADCAST_SETTING};
+
+use super::{set_thread_priority, ThreadId};
+use crate::{Error, ThreadPriority};
+
+#[derive(Debug)]
+struct RegisteredThread {
+    native: ThreadId,
+    normal: ThreadPriority,
+    power_saving: ThreadPriority,
+}
+
+// The thread handles are only used to set the priorities, which may be done
+// from any thread.
+unsafe impl Send for RegisteredThread {}
+
+/// A set of threads with the priorities to use depending on whether the power
+/// saving is on.
+///
+/// The threads have to be identified by their real handles, like the ones
+/// returned by [`std::os::windows::io::AsRawHandle::as_raw_handle`], as the
+/// pseudo handle returned by [`super::thread_native_id`] can only be used by the
+/// thread itself. The handles must stay valid while the threads are registered.
+#[derive(Debug, Default)]
+pub struct PowerAwareThreads {
+    threads: Mutex<Vec<RegisteredThread>>,
+}
+
+impl PowerAwareThreads {
+    /// Registers the thread with the priorities to use when the power saving is
+    /// off and on respectively.
+    pub fn register(&self, native: ThreadId, normal: ThreadPriority, power_saving: ThreadPriority) {
+        self.threads
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(RegisteredThread {
+                native,
+                normal,
+                power_saving,
+            });
+    }
+
+    /// Unregisters the thread, leaving its current priority as is.
+    pub fn unregister(&self, native: ThreadId) {
+        self.threads
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|thread| thread.native != native);
+    }
+
+    /// Sets the priorities of all the registered threads according to the power
+    /// saving state. All the threads are attempted, and the first error, if any,
+    /// is returned.
+    pub fn apply(&self, power_saving: bool) -> Result<(), Error> {
+        let threads = self.threads.lock().unwrap_or_else(|e| e.into_inner());
+        let mut result = Ok(());
+        for thread in threads.iter() {
+            let priority = if power_saving {
+                thread.power_saving
+            } else {
+                thread.normal
+            };
+            let ret = set_thread_priority(thread.native, priority);
+            if result.is_ok() {
+                result = ret;
+            }
+        }
+        result
+    }
+}
+
+type Callback = Box<dyn Fn(bool) + Send + Sync>;
+
+/// A subscription to the changes of the power saving state (the battery saver)
+/// of the system. The subscription is cancelled when dropped.
+///
+/// The callback is invoked with the current state right after subscribing and
+/// then on every change, from a thread of the system. Its panics are caught
+/// and logged, as they can't unwind into the system.
+pub struct PowerSavingMonitor {
+    registration: HPOWERNOTIFY,
+    // Referenced by the registration, so it must outlive it.
+    _callback: Box<Callback>,
+}
+
+// The registration handle is only used to unregister, which may be done from
+// any thread.
+unsafe impl Send for PowerSavingMonitor {}
+
+impl std::fmt::Debug for PowerSavingMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PowerSavingMonitor")
+            .field("registration", &self.registration)
+            .finish()
+    }
+}
+
+unsafe extern "system" fn power_setting_callback(
+    context: PVOID,
+    notification_type: ULONG,
+    setting: PVOID,
+) -> ULONG {
+    if notification_type as usize != PBT_POWERSETTINGCHANGE || setting.is_null() {
+        return 0;
+    }
+    let setting = setting as *const POWERBROADCAST_SETTING;
+    if !is_same_guid(&(*setting).PowerSetting, &GUID_POWER_SAVING_STATUS)
+        || ((*setting).DataLength as usize) < std::mem::size_of::<DWORD>()
+    {
+        return 0;
+    }
+    // The data extends past the declared one-byte array.
+    let state = std::ptr::read_unaligned(std::ptr::addr_of!((*setting).Data) as *const DWORD);
+    let callback = &*(context as *const Callback);
+    // Unwinding out of an `extern "system"` function is undefined behavior.
+    let power_saving = state != 0;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| callback(power_saving)));
+    if result.is_err() {
+        log::error!(
+            "The callback has panicked for the power saving state {}.",
+            power_saving
+        );
+    }
+    0
+}
+
+fn is_same_guid(first: &GUID, second: &GUID) -> bool {
+    first.Data1 == second.Data1
+        && first.Data2 == second.Data2
+        && first.Data3 == second.Data3
+        && first.Data4 == second.Data4
+}
+
+impl PowerSavingMonitor {
+    /// Subscribes to the changes of the power saving state.
+    ///
+    /// If there's an error, the error code returned by
+    /// `PowerSettingRegisterNotification` is returned.
+    pub fn start<F: Fn(bool) + Send + Sync + 'static>(callback: F) -> Result<Self, Error> {
+        let callback: Box<Callback> = Box::new(Box::new(callback));
+        let mut parameters = DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+            // `winapi` declares the callback as a pointer to a function pointer.
+            Callback: power_setting_callback as *const () as _,
+            Context: &*callback as *const Callback as PVOID,
+        };
+        let mut registration: HPOWERNOTIFY = std::ptr::null_mut();
+        let ret = unsafe {
+            PowerSettingRegisterNotification(
+                &GUID_POWER_SAVING_STATUS,
+                DEVICE_NOTIFY_CALLBACK,
+                &mut parameters as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS as _,
+                &mut registration,
+            )
+        };
+        if ret == 0 {
+            Ok(Self {
+                registration,
+                _callback: callback,
+            })
+        } else {
+            Err(Error::OS(ret as i32))
+        }
+    }
+
+    /// Subscribes to the changes of the power saving state, applying the
+    /// priorities of the threads on each of them. The errors are logged.
+    pub fn watch(threads: Arc<PowerAwareThreads>) -> Result<Self, Error> {
+        Self::start(move |power_saving| {
+            if let Err(e) = threads.apply(power_saving) {
+                log::warn!(
+                    "Couldn't apply the priorities for the power saving state {}: {:?}",
+                    power_saving,
+                    e,
+                );
+            }
+        })
+    }
+}
+
+impl Drop for PowerSavingMonitor {
+    fn drop(&mut self) {
+        let ret = unsafe { PowerSettingUnregisterNotification(self.registration) };
+        if ret != 0 {
+            log::warn!(
+                "Couldn't unsubscribe from the power saving state changes: {:?}",
+                Error::OS(ret as i32),
+            );
+        }
+    }
+}
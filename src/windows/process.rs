@@ -0,0 +1,214 @@
+//! This module allows to enumerate the threads of the current process and to
+//! change the priority of all of them at once, including the threads spawned
+//! by the third-party libraries.
+//!
+//! The threads are listed using a Toolhelp32 snapshot.
+//!
+//! ```rust
+//! use thread_priority::*;
+//!
+//! let threads = Process::current().threads().unwrap();
+//! assert!(!threads.is_empty());
+//! ```
+
+use winapi::shared::minwindef::{DWORD, FALSE};
+use winapi::shared::winerror::ERROR_NO_MORE_FILES;
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::processthreadsapi::{GetCurrentProcessId, OpenThread};
+use winapi::um::tlhelp32::{
+    CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+};
+use winapi::um::winnt::{
+    HANDLE, THREAD_QUERY_INFORMATION, THREAD_QUERY_LIMITED_INFORMATION, THREAD_SET_INFORMATION,
+    THREAD_SET_LIMITED_INFORMATION,
+};
+
+use crate::{Error, ThreadBuilder, ThreadPriority};
+
+/// The error `OpenThread` fails with when the thread has exited.
+const ERROR_INVALID_PARAMETER: i32 = winapi::shared::winerror::ERROR_INVALID_PARAMETER as i32;
+
+/// A thread of the process along with its description and priority.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ProcessThread {
+    /// The system-wide id of the thread, as returned by `GetCurrentThreadId`.
+    pub id: DWORD,
+    /// The description of the thread, which is the name shown in debuggers
+    /// and tracing tools, or `None` if it couldn't be read, for example,
+    /// before Windows 10 1607. See `get_thread_description`.
+    pub name: Option<String>,
+    /// The priority of the thread at the moment it was listed.
+    pub priority: ThreadPriority,
+}
+
+/// The current process.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Process {
+    _private: (),
+}
+
+/// An owned handle closed when dropped.
+struct OwnedHandle(HANDLE);
+
+impl Drop for OwnedHandle {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+impl OwnedHandle {
+    fn open_thread(id: DWORD, access: DWORD) -> Result<Self, Error> {
+        let handle = unsafe { OpenThread(access, FALSE, id) };
+        if handle.is_null() {
+            Err(Error::OS(unsafe { GetLastError() } as i32))
+        } else {
+            Ok(Self(handle))
+        }
+    }
+}
+
+impl Process {
+    /// Returns the current process.
+    pub fn current() -> Self {
+        Self::default()
+    }
+
+    /// Returns the ids of the threads of the process.
+    ///
+    /// If there's an error, a result of
+    /// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+    pub fn thread_ids(&self) -> Result<Vec<DWORD>, Error> {
+        let process_id = unsafe { GetCurrentProcessId() };
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) };
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err(Error::OS(unsafe { GetLastError() } as i32));
+        }
+        let snapshot = OwnedHandle(snapshot);
+
+        let mut ids = Vec::new();
+        let mut entry: THREADENTRY32 = unsafe { std::mem::zeroed() };
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as DWORD;
+        let mut ret = unsafe { Thread32First(snapshot.0, &mut entry) };
+        while ret != 0 {
+            if entry.th32OwnerProcessID == process_id {
+                ids.push(entry.th32ThreadID);
+            }
+            ret = unsafe { Thread32Next(snapshot.0, &mut entry) };
+        }
+        match unsafe { GetLastError() } {
+            ERROR_NO_MORE_FILES => Ok(ids),
+            e => Err(Error::OS(e as i32)),
+        }
+    }
+
+    /// Returns the id of the main thread of the process, which is the thread
+    /// created first among the running ones.
+    ///
+    /// If there's an error, a result of
+    /// [`GetLastError`](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-getlasterror) is returned.
+    pub fn main_thread_id(&self) -> Result<DWORD, Error> {
+        let mut main = None;
+        for id in self.thread_ids()? {
+            let thread = match OwnedHandle::open_thread(id, THREAD_QUERY_LIMITED_INFORMATION) {
+                Ok(thread) => thread,
+                Err(Error::OS(e)) if e == ERROR_INVALID_PARAMETER => continue,
+                Err(e) => return Err(e),
+            };
+            let created = crate::get_thread_times(thread.0)?.creation;
+            if main.map_or(true, |(_, earliest)| created < earliest) {
+                main = Some((id, created));
+            }
+        }
+        main.map(|(id, _)| id)
+            .ok_or(Error::Priority("The process has no threads."))
+    }
+
+    /// Returns the threads of the process with their current priorities. The
+    /// threads which have exited while being listed are skipped.
+    pub fn threads(&self) -> Result<Vec<ProcessThread>, Error> {
+        let mut threads = Vec::new();
+        for id in self.thread_ids()? {
+            let thread = match OwnedHandle::open_thread(id, THREAD_QUERY_LIMITED_INFORMATION) {
+                Ok(thread) => thread,
+                // The thread has exited.
+                Err(Error::OS(e)) if e == ERROR_INVALID_PARAMETER => continue,
+                Err(e) => return Err(e),
+            };
+            threads.push(ProcessThread {
+                id,
+                name: crate::get_thread_description(thread.0).ok(),
+                priority: crate::get_thread_priority(thread.0)?,
+            });
+        }
+        Ok(threads)
+    }
+
+    /// Sets the priority of every thread of the process, returning the number
+    /// of threads changed.
+    ///
+    /// The threads which have exited in the meantime are skipped. The threads
+    /// spawned while this runs may be missed. If changing a thread fails, the
+    /// rest of the threads are still changed and the first error is returned.
+    pub fn set_priority_for_all(&self, priority: ThreadPriority) -> Result<usize, Error> {
+        let mut changed = 0;
+        let mut error = None;
+        for id in self.thread_ids()? {
+            let result = OwnedHandle::open_thread(id, THREAD_SET_LIMITED_INFORMATION)
+                .and_then(|thread| crate::set_thread_priority(thread.0, priority));
+            match result {
+                Ok(()) => changed += 1,
+                Err(Error::OS(e)) if e == ERROR_INVALID_PARAMETER => {}
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(changed),
+        }
+    }
+}
+
+/// Applies the priority, the boost and the affinity of the builder to the main
+/// thread of the process. This allows the libraries, like plugins and engines,
+/// to tune the host's main thread from any thread, for example, during their
+/// initialization.
+///
+/// The other settings of the builder are ignored, as they can only be applied
+/// from within the thread itself.
+///
+/// # Usage
+///
+/// ```rust
+/// use thread_priority::*;
+///
+/// std::thread::spawn(|| {
+///     let config = ThreadBuilder::default().priority(ThreadPriority::Min);
+///     assert!(configure_main_thread(&config).is_ok());
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+pub fn configure_main_thread(config: &ThreadBuilder) -> Result<(), Error> {
+    let main = OwnedHandle::open_thread(
+        Process::current().main_thread_id()?,
+        THREAD_SET_INFORMATION | THREAD_QUERY_INFORMATION,
+    )?;
+    match (config.priority, config.winapi_priority) {
+        (_, Some(priority)) => crate::set_winapi_thread_priority(main.0, priority)?,
+        (Some(priority), None) => crate::set_thread_priority(main.0, priority)?,
+        (None, None) => {}
+    }
+    if let Some(boost) = config.boost {
+        crate::set_thread_priority_boost(main.0, boost)?;
+    }
+    if let Some(cores) = &config.affinity {
+        match config.affinity_group {
+            Some(group) => crate::set_thread_group_affinity(main.0, group, cores)?,
+            None => crate::set_thread_affinity(main.0, cores)?,
+        }
+    }
+    Ok(())
+}
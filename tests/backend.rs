@@ -0,0 +1,196 @@
+#![cfg(any(unix, windows))]
+use std::sync::{Arc, Mutex};
+
+use thread_priority::backend::*;
+use thread_priority::testing::{FakeScheduler, SchedulingOperation};
+use thread_priority::*;
+
+/// Serializes the tests, as the backend is global.
+static BACKEND: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Default)]
+struct RecordingBackend {
+    priorities: Arc<Mutex<Vec<ThreadPriority>>>,
+}
+
+impl SchedulingBackend for RecordingBackend {
+    fn thread_native_id(&self) -> ThreadId {
+        NativeBackend.thread_native_id()
+    }
+
+    fn set_thread_priority(
+        &self,
+        _native: ThreadId,
+        priority: ThreadPriority,
+    ) -> Result<(), Error> {
+        self.priorities.lock().unwrap().push(priority);
+        Ok(())
+    }
+
+    fn get_thread_priority(&self, native: ThreadId) -> Result<ThreadPriority, Error> {
+        NativeBackend.get_thread_priority(native)
+    }
+
+    #[cfg(unix)]
+    fn set_thread_priority_and_policy(
+        &self,
+        _native: ThreadId,
+        _priority: ThreadPriority,
+        _policy: ThreadSchedulePolicy,
+    ) -> Result<(), Error> {
+        Err(Error::Priority("Policies aren't supported."))
+    }
+
+    #[cfg(unix)]
+    fn thread_schedule_policy(&self, native: ThreadId) -> Result<ThreadSchedulePolicy, Error> {
+        NativeBackend.thread_schedule_policy(native)
+    }
+}
+
+#[test]
+fn registered_backend_is_used_by_the_builder() {
+    let _backend = BACKEND.lock().unwrap_or_else(|e| e.into_inner());
+    let backend = RecordingBackend::default();
+    set_scheduling_backend(backend.clone());
+
+    assert_eq!(ThreadPriority::Max.set_for_current(), Ok(()));
+    let result = ThreadBuilder::default()
+        .priority(ThreadPriority::Min)
+        .spawn(|result| result)
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        *backend.priorities.lock().unwrap(),
+        vec![ThreadPriority::Max, ThreadPriority::Min]
+    );
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        windows
+    ))]
+    assert!(ThreadBuilder::default()
+        .affinity(&[0])
+        .spawn(|result| result)
+        .unwrap()
+        .join()
+        .unwrap()
+        .is_err());
+
+    reset_scheduling_backend();
+    assert!(ThreadBuilder::default()
+        .priority(ThreadPriority::Min)
+        .spawn(|result| result)
+        .unwrap()
+        .join()
+        .unwrap()
+        .is_ok());
+}
+
+#[test]
+fn fake_scheduler_should_record_the_builder_operations() {
+    let _backend = BACKEND.lock().unwrap_or_else(|e| e.into_inner());
+    let scheduler = FakeScheduler::new();
+    set_scheduling_backend(scheduler.clone());
+
+    let spawn = || {
+        ThreadBuilder::default()
+            .priority(ThreadPriority::Max)
+            .spawn(|result| (result, thread_native_id()))
+            .unwrap()
+            .join()
+            .unwrap()
+    };
+    let (result, native) = spawn();
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        scheduler.operations(),
+        vec![SchedulingOperation::SetPriority {
+            native,
+            priority: ThreadPriority::Max,
+        }]
+    );
+    assert_eq!(
+        scheduler.get_thread_priority(native),
+        Ok(ThreadPriority::Max)
+    );
+
+    scheduler.clear();
+    scheduler.fail_next(Error::OS(1));
+    let (result, native) = spawn();
+    reset_scheduling_backend();
+    assert_eq!(result, Err(Error::OS(1)));
+    assert_eq!(scheduler.operations().len(), 1);
+    assert!(scheduler.get_thread_priority(native).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn fake_scheduler_should_record_the_builder_fallbacks() {
+    let _backend = BACKEND.lock().unwrap_or_else(|e| e.into_inner());
+    let scheduler = FakeScheduler::new();
+    set_scheduling_backend(scheduler.clone());
+
+    let fifo = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    scheduler.fail_next(Error::OS(libc::EPERM));
+    let (result, native) = ThreadBuilder::default()
+        .policy(fifo)
+        .priority(ThreadPriority::Max)
+        .fallback(normal, ThreadPriority::Min)
+        .spawn(|result| (result, thread_native_id()))
+        .unwrap()
+        .join()
+        .unwrap();
+    reset_scheduling_backend();
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        scheduler.operations(),
+        vec![
+            SchedulingOperation::SetPriorityAndPolicy {
+                native,
+                priority: ThreadPriority::Max,
+                policy: fifo,
+            },
+            SchedulingOperation::SetPriorityAndPolicy {
+                native,
+                priority: ThreadPriority::Min,
+                policy: normal,
+            },
+        ]
+    );
+    assert_eq!(
+        scheduler.get_thread_priority(native),
+        Ok(ThreadPriority::Min)
+    );
+    assert_eq!(scheduler.thread_schedule_policy(native), Ok(normal));
+}
+
+#[test]
+fn fake_scheduler_should_record_the_retuned_thread_operations() {
+    let _backend = BACKEND.lock().unwrap_or_else(|e| e.into_inner());
+    let scheduler = FakeScheduler::new();
+    set_scheduling_backend(scheduler.clone());
+
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let mut thread = ThreadBuilder::default()
+        .spawn_managed(move || receiver.recv().unwrap())
+        .unwrap();
+    let native = thread.native_id();
+    let result = thread.retune(ThreadBuilder::default().priority(ThreadPriority::Max));
+    reset_scheduling_backend();
+    sender.send(()).unwrap();
+    thread.join().unwrap();
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        scheduler.operations(),
+        vec![SchedulingOperation::SetPriority {
+            native,
+            priority: ThreadPriority::Max,
+        }]
+    );
+}
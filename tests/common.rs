@@ -1,3 +1,4 @@
+#![cfg(any(unix, windows))]
 use rstest::rstest;
 
 #[rstest]
@@ -37,3 +38,407 @@ fn should_be_impossible_to_get_other_thread_native_id_via_threadext() {
     });
     another_thread.join().unwrap();
 }
+
+#[cfg(any(
+    windows,
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+))]
+#[rstest]
+fn should_be_possible_to_get_thread_name() {
+    let thread = std::thread::Builder::new()
+        .name("NamedThread".to_owned())
+        .spawn(|| thread_priority::get_thread_name(thread_priority::thread_native_id()))
+        .unwrap();
+    assert_eq!(thread.join().unwrap(), Ok("NamedThread".to_owned()));
+}
+
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+#[rstest]
+fn should_be_possible_to_reset_the_same_process_affinity() {
+    let cores = thread_priority::get_process_affinity().unwrap();
+    assert!(!cores.is_empty());
+    assert_eq!(thread_priority::set_process_affinity(&cores), Ok(()));
+    assert_eq!(thread_priority::get_process_affinity(), Ok(cores));
+}
+
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+#[rstest]
+fn should_be_possible_to_snapshot_and_restore_thread_affinity() {
+    let thread_id = thread_priority::thread_native_id();
+    let cores = thread_priority::get_thread_affinity(thread_id).unwrap();
+    assert!(!cores.is_empty());
+
+    assert_eq!(
+        thread_priority::set_thread_affinity(thread_id, &cores[..1]),
+        Ok(())
+    );
+    assert_eq!(
+        thread_priority::get_thread_affinity(thread_id),
+        Ok(cores[..1].to_vec())
+    );
+
+    assert_eq!(
+        thread_priority::set_thread_affinity(thread_id, &cores),
+        Ok(())
+    );
+    assert_eq!(thread_priority::get_thread_affinity(thread_id), Ok(cores));
+}
+
+#[rstest]
+fn should_be_possible_to_spawn_with_boost_configured(#[values(false, true)] boost: bool) {
+    let thread = thread_priority::ThreadBuilder::default()
+        .boost(boost)
+        .spawn(|result| result)
+        .unwrap();
+    assert_eq!(thread.join().unwrap(), Ok(()));
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    windows
+))]
+#[rstest]
+fn should_be_possible_to_spawn_with_affinity() {
+    let cores = thread_priority::get_thread_affinity(thread_priority::thread_native_id()).unwrap();
+    let core = cores[0];
+    let thread = thread_priority::ThreadBuilder::default()
+        .affinity(&[core])
+        .spawn(|result| {
+            result
+                .map(|_| thread_priority::get_thread_affinity(thread_priority::thread_native_id()))
+        })
+        .unwrap();
+    assert_eq!(thread.join().unwrap().unwrap(), Ok(vec![core]));
+}
+
+#[rstest]
+fn should_restore_the_priority_of_the_pool_workers_after_a_panic() {
+    let pool = thread_priority::pool::ThreadPool::new(
+        1,
+        thread_priority::ThreadBuilder::default().restore_priority(true),
+    )
+    .unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    let before = sender.clone();
+    pool.execute(move || {
+        before
+            .send(thread_priority::get_current_thread_priority())
+            .unwrap()
+    });
+    pool.execute(|| {
+        thread_priority::set_current_thread_priority(thread_priority::ThreadPriority::Min).unwrap();
+        panic!("The priority must be restored.");
+    });
+    pool.execute(move || {
+        sender
+            .send(thread_priority::get_current_thread_priority())
+            .unwrap()
+    });
+    pool.join();
+
+    let before = receiver.recv().unwrap().unwrap();
+    assert_ne!(before, thread_priority::ThreadPriority::Min);
+    assert_eq!(receiver.recv().unwrap(), Ok(before));
+}
+
+#[rstest]
+fn should_be_possible_to_spawn_and_wait_for_priority() {
+    let thread = thread_priority::ThreadBuilder::default()
+        .priority(thread_priority::ThreadPriority::Min)
+        .spawn_wait(|| 42)
+        .unwrap();
+    assert_eq!(thread.join().unwrap(), 42);
+}
+
+#[rstest]
+fn should_be_possible_to_convert_error_into_io_error() {
+    let error: std::io::Error = thread_priority::Error::OS(1).into();
+    assert_eq!(error.raw_os_error(), Some(1));
+
+    let error: std::io::Error = thread_priority::Error::PriorityNotInRange(0..=0).into();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(
+        error.to_string(),
+        thread_priority::Error::PriorityNotInRange(0..=0).to_string()
+    );
+}
+
+#[rstest]
+fn should_be_possible_to_set_and_verify_priority() {
+    std::thread::spawn(|| {
+        let applied = thread_priority::set_current_thread_priority_verified(
+            thread_priority::ThreadPriority::Min,
+        )
+        .unwrap();
+        assert_eq!(
+            thread_priority::set_current_thread_priority_verified(applied),
+            Ok(applied)
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[rstest]
+#[case(0, true)]
+#[case(99, true)]
+#[case(100, cfg!(target_os = "vxworks"))]
+fn should_validate_deserialized_priority_value(#[case] value: u8, #[case] valid: bool) {
+    use serde::de::{value::Error, Deserialize, IntoDeserializer};
+
+    let deserializer: serde::de::value::U8Deserializer<Error> = value.into_deserializer();
+    assert_eq!(
+        thread_priority::ThreadPriorityValue::deserialize(deserializer).is_ok(),
+        valid
+    );
+}
+
+#[rstest]
+fn should_be_possible_to_execute_jobs_in_pool() {
+    let pool = thread_priority::pool::ThreadPool::new(
+        3,
+        thread_priority::ThreadBuilder::default()
+            .name("Worker")
+            .priority(thread_priority::ThreadPriority::Min),
+    )
+    .unwrap();
+    assert_eq!(pool.size(), 3);
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    pool.execute(|| panic!("The workers must survive the panics."));
+    for _ in 0..9 {
+        let sender = sender.clone();
+        pool.execute(move || {
+            sender
+                .send(std::thread::current().name().unwrap().to_owned())
+                .unwrap()
+        });
+    }
+    pool.join();
+
+    let names: Vec<String> = receiver.try_iter().collect();
+    assert_eq!(names.len(), 9);
+    assert!(names
+        .iter()
+        .all(|name| ["Worker-0", "Worker-1", "Worker-2"].contains(&name.as_str())));
+}
+
+#[rstest]
+fn background_chunked_work_runs_to_completion() {
+    use std::time::Duration;
+    use thread_priority::ChunkBudget;
+
+    let mut chunks = 0;
+    let handle = thread_priority::run_background_chunked(
+        move || {
+            chunks += 1;
+            std::thread::sleep(Duration::from_micros(10));
+            (chunks == 100).then_some(chunks)
+        },
+        ChunkBudget::new(Duration::ZERO).pause(Duration::from_micros(1)),
+    )
+    .unwrap();
+    assert_eq!(handle.join().unwrap(), 100);
+}
+
+#[rstest]
+fn schedule_plan_lists_and_applies_the_builder_operations() {
+    use thread_priority::plan::PlannedAction;
+    use thread_priority::{ThreadBuilder, ThreadPriority};
+
+    let native = thread_priority::thread_native_id();
+    let plan = ThreadBuilder::default().plan_for(native).unwrap();
+    assert!(plan.actions().is_empty());
+    assert_eq!(plan.apply(), Ok(()));
+
+    std::thread::spawn(|| {
+        let native = thread_priority::thread_native_id();
+        let plan = ThreadBuilder::default()
+            .priority(ThreadPriority::Min)
+            .plan_for(native)
+            .unwrap();
+        assert_eq!(plan.target(), native);
+        assert_eq!(
+            plan.actions(),
+            &[PlannedAction::SetPriority {
+                priority: ThreadPriority::Min
+            }]
+        );
+        assert_eq!(plan.actions()[0].to_string(), "set the priority to min");
+        assert_eq!(plan.apply(), Ok(()));
+    })
+    .join()
+    .unwrap();
+}
+
+#[rstest]
+#[case(0.0, "0%")]
+#[case(0.125, "12.5%")]
+#[case(0.07, "7%")]
+#[case(0.57, "57%")]
+#[case(1.0, "100%")]
+fn fraction_priority_round_trips_through_percentages(#[case] fraction: f64, #[case] text: &str) {
+    use thread_priority::ThreadPriority;
+
+    let priority = ThreadPriority::from_fraction(fraction).unwrap();
+    assert_eq!(priority.to_string(), text);
+    assert_eq!(text.parse(), Ok(priority));
+}
+
+#[rstest]
+#[case(-0.1)]
+#[case(1.01)]
+#[case(f64::NAN)]
+fn fraction_priority_must_be_within_the_unit_range(#[case] fraction: f64) {
+    assert!(thread_priority::ThreadPriority::from_fraction(fraction).is_err());
+}
+
+#[rstest]
+fn capabilities_report_the_priority_as_supported() {
+    let capabilities = thread_priority::capabilities();
+    assert!(capabilities.priority);
+    assert_eq!(capabilities.policy, cfg!(unix));
+    assert_eq!(
+        capabilities.realtime_priority,
+        thread_priority::can_set_realtime_priority().unwrap()
+    );
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    windows
+))]
+#[rstest]
+fn current_cpu_is_within_the_affinity() {
+    std::thread::spawn(|| {
+        let native = thread_priority::thread_native_id();
+        let cores = thread_priority::get_thread_affinity(native).unwrap();
+        thread_priority::set_thread_affinity(native, &cores[..1]).unwrap();
+        assert_eq!(thread_priority::current_cpu(), Ok(cores[0]));
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+#[rstest]
+fn topology_covers_the_thread_affinity() {
+    use thread_priority::topology::{cpu_topology, CoreKind};
+
+    let topology = cpu_topology().unwrap();
+    let cores = thread_priority::get_thread_affinity(thread_priority::thread_native_id()).unwrap();
+    assert!(cores
+        .iter()
+        .all(|core| topology.cpus().iter().any(|cpu| cpu.id == *core)));
+    assert_eq!(
+        topology.cores().iter().map(Vec::len).sum::<usize>(),
+        topology.cpus().len()
+    );
+    assert_eq!(
+        topology.is_hybrid(),
+        topology.cpus_of_kind(CoreKind::Uniform).is_empty()
+    );
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+#[rstest]
+fn thread_is_bound_to_numa_node() {
+    use thread_priority::topology::{cpu_topology, set_current_thread_numa_node};
+
+    let native = thread_priority::thread_native_id();
+    let cores = thread_priority::get_thread_affinity(native).unwrap();
+    let topology = cpu_topology().unwrap();
+    let node = topology
+        .cpus()
+        .iter()
+        .find(|cpu| cores.contains(&cpu.id))
+        .unwrap()
+        .numa_node;
+
+    std::thread::spawn(move || {
+        match set_current_thread_numa_node(node) {
+            Ok(()) => {
+                let native = thread_priority::thread_native_id();
+                let bound = thread_priority::get_thread_affinity(native).unwrap();
+                assert!(bound.iter().all(|core| topology
+                    .cpus()
+                    .iter()
+                    .any(|cpu| cpu.id == *core && cpu.numa_node == node)));
+            }
+            // The memory policy may be forbidden in the containers.
+            Err(error) => assert!(matches!(error, thread_priority::Error::OS(_))),
+        }
+        assert!(set_current_thread_numa_node(1000).is_err());
+    })
+    .join()
+    .unwrap();
+}
+
+#[rstest]
+fn audio_thread_is_promoted_and_demoted() {
+    use thread_priority::audio::promote_current_thread_to_real_time;
+
+    std::thread::spawn(|| {
+        assert!(promote_current_thread_to_real_time(512, 0).is_err());
+
+        let before = thread_priority::get_current_thread_priority().unwrap();
+        if let Ok(handle) = promote_current_thread_to_real_time(0, 48_000) {
+            assert_eq!(handle.demote(), Ok(()));
+            assert_eq!(thread_priority::get_current_thread_priority(), Ok(before));
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+#[rstest]
+fn memory_is_locked_by_builder() {
+    let locked = thread_priority::ThreadBuilder::default()
+        .lock_memory(true)
+        .spawn(|result| result)
+        .unwrap()
+        .join()
+        .unwrap();
+    // Locking the memory may be forbidden by the limits, which the builder
+    // has to report.
+    if let Err(error) = &locked {
+        assert!(matches!(error, thread_priority::Error::OS(_)));
+    }
+    #[cfg(unix)]
+    if locked.is_ok() {
+        assert_eq!(thread_priority::unlock_memory(), Ok(()));
+    }
+}
+
+#[cfg(any(
+    windows,
+    target_os = "linux",
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[test]
+fn thread_cpu_time_increases() {
+    let native = thread_priority::thread_native_id();
+    let before = thread_priority::thread_cpu_time(native).unwrap();
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_millis(50) {}
+    assert!(thread_priority::thread_cpu_time(native).unwrap() > before);
+}
@@ -0,0 +1,87 @@
+#![cfg(any(unix, windows))]
+//! The global defaults are shared by the whole process, so these tests run in
+//! a process of their own.
+
+use thread_priority::config::*;
+use thread_priority::pool::ThreadPool;
+use thread_priority::*;
+
+/// Serializes the tests, as each of them changes the global defaults.
+static DEFAULTS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn settings_are_resolved_by_precedence() {
+    let _defaults = DEFAULTS.lock().unwrap_or_else(|e| e.into_inner());
+    set_global_defaults(
+        ThreadBuilder::default()
+            .name("Global")
+            .stack_size(1usize << 20)
+            .priority(ThreadPriority::Max),
+    );
+
+    let pool = ThreadBuilder::default()
+        .name("Pool")
+        .priority(ThreadPriority::Min);
+    let thread = ThreadBuilder::default().name("Thread");
+    let resolved = resolve(Some(&pool), &thread);
+
+    assert_eq!(resolved.origin(Setting::Name), Some(ConfigLayer::Thread));
+    assert_eq!(resolved.origin(Setting::Priority), Some(ConfigLayer::Pool));
+    assert_eq!(
+        resolved.origin(Setting::StackSize),
+        Some(ConfigLayer::Global)
+    );
+    assert_eq!(resolved.origin(Setting::Boost), None);
+    assert_eq!(resolved.origins().len(), 3);
+    assert_eq!(
+        resolved.builder(),
+        &ThreadBuilder::default()
+            .name("Thread")
+            .stack_size(1usize << 20)
+            .priority(ThreadPriority::Min)
+    );
+
+    let resolved = resolve(None, &thread);
+    assert_eq!(
+        resolved.origin(Setting::Priority),
+        Some(ConfigLayer::Global)
+    );
+
+    set_global_defaults(ThreadBuilder::default());
+    assert_eq!(global_defaults(), ThreadBuilder::default());
+    assert_eq!(
+        resolve(None, &thread).origins(),
+        &[(Setting::Name, ConfigLayer::Thread)]
+    );
+}
+
+#[test]
+fn pool_workers_use_the_global_defaults() {
+    let _defaults = DEFAULTS.lock().unwrap_or_else(|e| e.into_inner());
+    set_global_defaults(
+        ThreadBuilder::default()
+            .name("Worker")
+            .priority(ThreadPriority::Min),
+    );
+
+    let pool = ThreadPool::new(2, ThreadBuilder::default()).unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    for _ in 0..2 {
+        let sender = sender.clone();
+        pool.execute(move || {
+            let name = std::thread::current().name().map(str::to_owned);
+            sender.send(name).unwrap();
+        });
+    }
+    let worker = pool.worker_config(1).unwrap();
+    assert_eq!(worker.origin(Setting::Name), Some(ConfigLayer::Thread));
+    assert_eq!(worker.origin(Setting::Priority), Some(ConfigLayer::Global));
+    assert!(pool.worker_config(2).is_none());
+    pool.join();
+
+    let mut names: Vec<_> = receiver.try_iter().flatten().collect();
+    names.sort();
+    assert!(names.iter().all(|name| name.starts_with("Worker-")));
+
+    set_global_defaults(ThreadBuilder::default());
+}
@@ -0,0 +1,87 @@
+#![cfg(target_os = "linux")]
+//! Changing the scheduling of the whole process affects the threads of the
+//! other tests, so these tests run in a process of their own.
+
+use thread_priority::*;
+
+/// Serializes the tests, as each of them changes the scheduling of the others.
+static PROCESS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn set_priority_for_all_process_threads() {
+    let _process = PROCESS.lock().unwrap_or_else(|e| e.into_inner());
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let (started_sender, started) = std::sync::mpsc::channel::<()>();
+    let thread = std::thread::Builder::new()
+        .name("ListedThread".to_owned())
+        .spawn(move || {
+            started_sender.send(()).unwrap();
+            receiver.recv().unwrap();
+            unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) }
+        })
+        .unwrap();
+    // The name is set by the thread itself once it starts.
+    started.recv().unwrap();
+
+    let process = Process::current();
+    let threads = process.threads().unwrap();
+    assert!(threads.len() >= 2);
+    assert!(threads
+        .iter()
+        .any(|thread| thread.id == KernelThreadId::current()));
+    assert!(threads
+        .iter()
+        .any(|thread| thread.name.as_deref() == Some("ListedThread")));
+
+    // Threads of the other tests may come and go, hence no exact count.
+    let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let current = KernelThreadId::current();
+    let niceness = current.niceness().unwrap();
+    let priority = current
+        .interpreted_priority()
+        .unwrap()
+        .crossplatform
+        .unwrap();
+    assert!(
+        process
+            .set_priority_for_all(ThreadPriority::Crossplatform(priority), normal)
+            .unwrap()
+            >= 2
+    );
+
+    sender.send(()).unwrap();
+    assert_eq!(thread.join().unwrap(), niceness);
+}
+
+#[test]
+fn configure_the_main_thread() {
+    let _process = PROCESS.lock().unwrap_or_else(|e| e.into_inner());
+    let main = Process::current().main_thread_id();
+    assert_ne!(main, KernelThreadId::current());
+
+    let config = ThreadBuilder::default()
+        .policy(ThreadSchedulePolicy::Normal(
+            NormalThreadSchedulePolicy::Other,
+        ))
+        .priority(ThreadPriority::Min);
+    assert_eq!(configure_main_thread(&config), Ok(()));
+    assert_eq!(
+        main.interpreted_priority()
+            .map(|priority| priority.effective_score()),
+        Ok(1)
+    );
+}
+
+#[test]
+fn process_from_pid_lists_the_same_threads() {
+    let _process = PROCESS.lock().unwrap_or_else(|e| e.into_inner());
+    let current = Process::current();
+    let process = Process::from_pid(current.pid());
+    assert_eq!(process, current);
+    assert_eq!(process.main_thread_id(), current.main_thread_id());
+    assert!(process
+        .thread_ids()
+        .unwrap()
+        .contains(&KernelThreadId::current()));
+    assert!(Process::from_pid(-1).thread_ids().is_err());
+}
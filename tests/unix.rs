@@ -1,4 +1,4 @@
-#![cfg(not(windows))]
+#![cfg(unix)]
 
 use rstest::rstest;
 use std::convert::TryInto;
@@ -48,7 +48,9 @@ fn get_and_set_priority_with_normal_policies(
     target_os = "openbsd",
     target_os = "vxworks",
     target_os = "freebsd",
-    target_os = "netbsd"
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
 ))]
 #[rstest]
 fn get_and_set_priority_with_normal_policies(
@@ -121,7 +123,9 @@ fn set_priority_with_normal_policy_but_with_invalid_value(#[case] policy: Thread
     target_os = "openbsd",
     target_os = "vxworks",
     target_os = "freebsd",
-    target_os = "netbsd"
+    target_os = "netbsd",
+    target_os = "illumos",
+    target_os = "solaris"
 ))]
 #[test]
 // In macOS the SCHED_OTHER policy allows having a non-zero priority value,
@@ -216,3 +220,1258 @@ fn get_and_set_priority_with_realtime_policy_requires_capabilities(
         })
     );
 }
+
+#[test]
+fn set_spawned_thread_priority_via_join_handle_requires_capabilities() {
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let join_handle = std::thread::spawn(move || {
+        receiver.recv().unwrap();
+        thread_schedule_policy()
+    });
+
+    assert_eq!(
+        join_handle.set_priority_and_policy(
+            realtime_policy,
+            ThreadPriority::Crossplatform(23u8.try_into().unwrap())
+        ),
+        Ok(())
+    );
+    assert_eq!(join_handle.get_schedule_policy(), Ok(realtime_policy));
+    assert_eq!(
+        join_handle.get_priority(),
+        Ok(ThreadPriority::Crossplatform(23u8.try_into().unwrap()))
+    );
+
+    sender.send(()).unwrap();
+    assert_eq!(join_handle.join().unwrap(), Ok(realtime_policy));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn priority_guard_restores_previous_scheduling_requires_capabilities() {
+    let normal_policy = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+
+    std::thread::spawn(move || {
+        set_current_thread_priority(ThreadPriority::Crossplatform(30u8.try_into().unwrap()))
+            .unwrap();
+        let nice = unsafe { libc::getpriority(0, 0) };
+
+        let guard = PriorityGuard::raise(ThreadPriority::Max, realtime_policy).unwrap();
+        assert_eq!(thread_schedule_policy(), Ok(realtime_policy));
+        drop(guard);
+
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+        assert_eq!(unsafe { libc::getpriority(0, 0) }, nice);
+
+        let guard = PriorityGuard::raise(ThreadPriority::Min, realtime_policy).unwrap();
+        assert_eq!(guard.restore(), Ok(()));
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+        assert_eq!(unsafe { libc::getpriority(0, 0) }, nice);
+
+        let guard = PriorityGuard::raise(ThreadPriority::Max, realtime_policy).unwrap();
+        assert_eq!(demote(guard), Ok(()));
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+        assert_eq!(unsafe { libc::getpriority(0, 0) }, nice);
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn priority_guard_restores_scheduling_on_panic_requires_capabilities() {
+    let normal_policy = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+
+    std::thread::spawn(move || {
+        let result = std::panic::catch_unwind(|| {
+            let _guard = PriorityGuard::save().unwrap();
+            set_thread_priority_and_policy(
+                thread_native_id(),
+                ThreadPriority::Max,
+                realtime_policy,
+            )
+            .unwrap();
+            assert_eq!(thread_schedule_policy(), Ok(realtime_policy));
+            panic!("The scheduling must be restored during unwinding.");
+        });
+        assert!(result.is_err());
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn realtime_relinquish_guard_restores_realtime_scheduling_requires_capabilities() {
+    let normal_policy = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+
+    std::thread::spawn(move || {
+        let guard = RealtimeRelinquishGuard::relinquish().unwrap();
+        assert!(!guard.is_relinquished());
+        assert_eq!(guard.restore(), Ok(()));
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+
+        set_thread_priority_and_policy(thread_native_id(), ThreadPriority::Max, realtime_policy)
+            .unwrap();
+        let guard = RealtimeRelinquishGuard::relinquish().unwrap();
+        assert!(guard.is_relinquished());
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+        drop(guard);
+        assert_eq!(thread_schedule_policy(), Ok(realtime_policy));
+
+        let guard = RealtimeRelinquishGuard::relinquish().unwrap();
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+        assert_eq!(guard.restore(), Ok(()));
+        assert_eq!(thread_schedule_policy(), Ok(realtime_policy));
+    })
+    .join()
+    .unwrap();
+}
+
+#[rstest]
+#[case(libc::EPERM, ErrorKind::PermissionDenied)]
+#[case(libc::EINVAL, ErrorKind::InvalidParameter)]
+#[case(libc::ENOSYS, ErrorKind::UnsupportedOnPlatform)]
+#[case(libc::ESRCH, ErrorKind::ThreadGone)]
+#[case(libc::EIO, ErrorKind::Other)]
+fn classify_os_errors(#[case] code: libc::c_int, #[case] kind: ErrorKind) {
+    assert_eq!(Error::OS(code).kind(), kind);
+    assert_eq!(Error::OS(code).raw_os_error(), Some(code));
+}
+
+#[rstest]
+#[case("min", ThreadPriority::Min)]
+#[case("max", ThreadPriority::Max)]
+#[case("57", ThreadPriority::Crossplatform(57u8.try_into().unwrap()))]
+#[cfg_attr(
+    target_os = "linux",
+    case(
+        "deadline:500us/20ms/1s",
+        ThreadPriority::Deadline {
+            runtime: std::time::Duration::from_micros(500),
+            deadline: std::time::Duration::from_millis(20),
+            period: std::time::Duration::from_secs(1),
+            flags: DeadlineFlags::empty(),
+        }
+    )
+)]
+#[cfg_attr(
+    target_os = "linux",
+    case(
+        "deadline:10ms/20ms/100ms/RESET_ON_FORK | RECLAIM",
+        ThreadPriority::Deadline {
+            runtime: std::time::Duration::from_millis(10),
+            deadline: std::time::Duration::from_millis(20),
+            period: std::time::Duration::from_millis(100),
+            flags: DeadlineFlags::RESET_ON_FORK | DeadlineFlags::RECLAIM,
+        }
+    )
+)]
+fn parse_and_display_priority(#[case] text: &str, #[case] priority: ThreadPriority) {
+    assert_eq!(text.parse(), Ok(priority));
+    assert_eq!(priority.to_string(), text);
+}
+
+#[test]
+fn parse_and_display_os_priority_and_policy() {
+    let policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin);
+
+    assert_eq!(
+        "os:3".parse::<ThreadPriority>().unwrap().to_string(),
+        "os:3"
+    );
+    assert_eq!(policy.to_string(), "rr");
+    assert_eq!("RR".parse(), Ok(policy));
+    assert!("unknown".parse::<ThreadSchedulePolicy>().is_err());
+    assert_eq!(
+        parse_policy_and_priority("rr:max"),
+        Ok((policy, ThreadPriority::Max))
+    );
+    assert!(parse_policy_and_priority("rr").is_err());
+    assert!(parse_policy_and_priority("rr:100").is_err());
+}
+
+#[test]
+fn get_interpreted_priority() {
+    let normal_policy = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let priority = ThreadPriority::Crossplatform(30u8.try_into().unwrap());
+
+    std::thread::spawn(move || {
+        set_thread_priority_and_policy(thread_native_id(), priority, normal_policy).unwrap();
+
+        let interpreted = get_current_thread_interpreted_priority().unwrap();
+        assert_eq!(interpreted.policy, normal_policy);
+        assert_eq!(Ok(interpreted.raw), priority.to_posix(normal_policy));
+        assert!(interpreted
+            .crossplatform
+            .map(ThreadPriority::Crossplatform)
+            .unwrap()
+            .is_equivalent_for_policy(priority, normal_policy)
+            .unwrap());
+    })
+    .join()
+    .unwrap();
+}
+
+#[rstest]
+#[cfg_attr(
+    any(target_os = "linux", target_os = "android"),
+    case(
+        ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other),
+        PriorityRangeKind::Niceness
+    )
+)]
+#[case(
+    ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin),
+    PriorityRangeKind::StaticPriority
+)]
+fn get_priority_range(#[case] policy: ThreadSchedulePolicy, #[case] kind: PriorityRangeKind) {
+    let range = PriorityRange::for_policy(policy).unwrap();
+
+    assert_eq!(range.kind, kind);
+    assert_eq!(
+        range.min,
+        ThreadPriority::min_value_for_policy(policy).unwrap()
+    );
+    assert_eq!(
+        range.max,
+        ThreadPriority::max_value_for_policy(policy).unwrap()
+    );
+    assert!(range.contains(range.min) && range.contains(range.max));
+    assert_eq!(range.clamp(i32::MAX), *range.to_inclusive_range().end());
+    assert_eq!(range.clamp(i32::MIN), *range.to_inclusive_range().start());
+}
+
+#[test]
+fn check_priority_equivalence() {
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    let min = ThreadPriority::min_value_for_policy(realtime_policy).unwrap();
+    let max = ThreadPriority::max_value_for_policy(realtime_policy).unwrap();
+
+    assert_eq!(
+        ThreadPriority::Min.is_equivalent_for_policy(
+            ThreadPriority::Crossplatform((min as u8).try_into().unwrap()),
+            realtime_policy
+        ),
+        Ok(true)
+    );
+    assert_eq!(
+        ThreadPriority::Max.is_equivalent_for_policy(
+            ThreadPriority::Crossplatform((max as u8).try_into().unwrap()),
+            realtime_policy
+        ),
+        Ok(true)
+    );
+    assert_eq!(
+        ThreadPriority::Min.is_equivalent_for_policy(ThreadPriority::Max, realtime_policy),
+        Ok(false)
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn set_priority_with_fallbacks() {
+    let normal_policy = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let invalid_priority = ThreadPriority::Deadline {
+        runtime: std::time::Duration::from_millis(2),
+        deadline: std::time::Duration::from_millis(1),
+        period: std::time::Duration::from_millis(1),
+        flags: DeadlineFlags::empty(),
+    };
+
+    std::thread::spawn(move || {
+        assert!(set_current_thread_priority_with_fallbacks(&[]).is_err());
+        assert_eq!(
+            set_current_thread_priority_with_fallbacks(&[
+                (normal_policy, invalid_priority),
+                (normal_policy, ThreadPriority::Min),
+            ]),
+            Ok((normal_policy, ThreadPriority::Min))
+        );
+
+        let thread = ThreadBuilder::default()
+            .policy(normal_policy)
+            .priority(invalid_priority)
+            .fallback(normal_policy, ThreadPriority::Min)
+            .spawn(|result| result)
+            .unwrap();
+        assert_eq!(thread.join().unwrap(), Ok(()));
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn spawn_wait_returns_the_priority_error() {
+    let result = ThreadBuilder::default()
+        .policy(ThreadSchedulePolicy::Normal(
+            NormalThreadSchedulePolicy::Other,
+        ))
+        .priority(ThreadPriority::Deadline {
+            runtime: std::time::Duration::from_millis(2),
+            deadline: std::time::Duration::from_millis(1),
+            period: std::time::Duration::from_millis(1),
+            flags: DeadlineFlags::empty(),
+        })
+        .spawn_wait(|| panic!("The thread function must not be run."));
+    assert!(result.is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn spawn_process_with_scheduling_attributes_requires_capabilities() {
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    let mut spawn_attr = std::mem::MaybeUninit::<libc::posix_spawnattr_t>::uninit();
+    assert_eq!(
+        unsafe { libc::posix_spawnattr_init(spawn_attr.as_mut_ptr()) },
+        0
+    );
+    let mut spawn_attr = unsafe { spawn_attr.assume_init() };
+    assert_eq!(
+        unix::spawn::set_priority_and_policy(&mut spawn_attr, ThreadPriority::Min, realtime_policy),
+        Ok(())
+    );
+
+    // The child exits with its policy, read from the 41st field of its stat.
+    let path = std::ffi::CString::new("/bin/sh").unwrap();
+    let args = [
+        path.clone(),
+        std::ffi::CString::new("-c").unwrap(),
+        std::ffi::CString::new(
+            "read -r stat < /proc/self/stat; set -- ${stat##*) }; eval exit \\${39}",
+        )
+        .unwrap(),
+    ];
+    let argv: Vec<*mut libc::c_char> = args
+        .iter()
+        .map(|arg| arg.as_ptr() as *mut libc::c_char)
+        .chain(std::iter::once(std::ptr::null_mut()))
+        .collect();
+    let envp = [std::ptr::null_mut()];
+    let mut pid = 0;
+    assert_eq!(
+        unsafe {
+            libc::posix_spawn(
+                &mut pid,
+                path.as_ptr(),
+                std::ptr::null(),
+                &spawn_attr,
+                argv.as_ptr(),
+                envp.as_ptr(),
+            )
+        },
+        0
+    );
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(libc::WIFEXITED(status));
+    assert_eq!(libc::WEXITSTATUS(status), libc::SCHED_FIFO);
+
+    unsafe { libc::posix_spawnattr_destroy(&mut spawn_attr) };
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn parse_and_monitor_cpu_pressure() {
+    use unix::pressure::{parse_cpu_pressure, CpuPressureMonitor};
+
+    let pressure = parse_cpu_pressure(
+        "some avg10=7.01 avg60=5.06 avg300=4.68 total=68966522\n\
+         full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n",
+    )
+    .unwrap();
+    assert_eq!(pressure.some.avg10, 7.01);
+    assert_eq!(pressure.some.avg300, 4.68);
+    assert_eq!(
+        pressure.some.total,
+        std::time::Duration::from_micros(68966522)
+    );
+    assert_eq!(pressure.full.avg60, 0.0);
+    assert!(parse_cpu_pressure("some avg10=x").is_err());
+
+    if std::path::Path::new("/proc/pressure/cpu").exists() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let monitor = CpuPressureMonitor::start(
+            100.0,
+            std::time::Duration::from_millis(10),
+            move |high, _| sender.send(high).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(receiver.recv(), Ok(false));
+        drop(monitor);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn parse_and_pin_to_isolated_cpus() {
+    use unix::isolation::{isolated_cpus, pin_current_thread_to_isolated_core, IsolatedCpus};
+
+    let cpus = IsolatedCpus::from_cmdline(
+        "quiet isolcpus=nohz,domain,managed_irq,2-4,6 nohz_full=3-5 -- isolcpus=7",
+    );
+    assert_eq!(cpus.isolated, vec![2, 3, 4, 6]);
+    assert_eq!(cpus.nohz_full, vec![3, 4, 5]);
+    assert_eq!(cpus.all(), vec![3, 4, 2, 6, 5]);
+    assert_eq!(IsolatedCpus::from_cmdline("quiet"), IsolatedCpus::default());
+
+    if isolated_cpus().unwrap().all().is_empty() {
+        assert!(pin_current_thread_to_isolated_core(ThreadPriority::Min).is_err());
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "openbsd"
+))]
+#[test]
+fn thread_rusage_counts_the_context_switches() {
+    let usage = std::thread::spawn(|| {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let start = std::time::Instant::now();
+        while start.elapsed() < std::time::Duration::from_millis(10) {}
+        get_current_thread_rusage().unwrap()
+    })
+    .join()
+    .unwrap();
+
+    assert!(usage.cpu_time() > std::time::Duration::ZERO);
+    assert!(usage.voluntary_switches > 0);
+    assert!(usage.max_rss > 0);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn thread_sched_stats_are_read() {
+    let tid = KernelThreadId::current();
+    let before = get_thread_sched_stats(tid).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_millis(10) {}
+    let after = tid.sched_stats().unwrap();
+
+    assert!(after.runtime > before.runtime);
+    assert!(after.nr_voluntary_switches > before.nr_voluntary_switches);
+    assert_eq!(
+        after.nr_switches,
+        after.nr_voluntary_switches + after.nr_involuntary_switches
+    );
+    assert!(get_thread_sched_stats(KernelThreadId(-1)).is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn realtime_throttling_is_read() {
+    let throttling = realtime_throttling().unwrap();
+    assert!(!throttling.period.is_zero());
+    assert!(throttling.share() > 0.0 && throttling.share() <= 1.0);
+    assert_eq!(throttling.is_throttled(), throttling.runtime.is_some());
+
+    let unthrottled = RealtimeThrottling {
+        runtime: None,
+        ..throttling
+    };
+    assert_eq!(unthrottled.share(), 1.0);
+    let default = RealtimeThrottling {
+        runtime: Some(std::time::Duration::from_millis(950)),
+        period: std::time::Duration::from_secs(1),
+        cgroup_runtime: None,
+    };
+    assert!((default.share() - 0.95).abs() < 1e-9);
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "tvos",
+    target_os = "watchos"
+))]
+#[test]
+fn set_scheduling_attributes() {
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    let mut pthread_attr = std::mem::MaybeUninit::<libc::pthread_attr_t>::uninit();
+    assert_eq!(
+        unsafe { libc::pthread_attr_init(pthread_attr.as_mut_ptr()) },
+        0
+    );
+    let mut pthread_attr = unsafe { pthread_attr.assume_init() };
+
+    assert_eq!(
+        unix::attr::set_priority_and_policy(
+            &mut pthread_attr,
+            ThreadPriority::Crossplatform(23u8.try_into().unwrap()),
+            realtime_policy,
+        ),
+        Ok(())
+    );
+
+    let mut inherit = 0;
+    let mut policy = 0;
+    let mut param = unsafe { std::mem::zeroed::<libc::sched_param>() };
+    unsafe {
+        assert_eq!(
+            libc::pthread_attr_getinheritsched(&pthread_attr, &mut inherit),
+            0
+        );
+        assert_eq!(
+            libc::pthread_attr_getschedpolicy(&pthread_attr, &mut policy),
+            0
+        );
+        assert_eq!(
+            libc::pthread_attr_getschedparam(&pthread_attr, &mut param),
+            0
+        );
+        libc::pthread_attr_destroy(&mut pthread_attr);
+    }
+    assert_eq!(inherit, libc::PTHREAD_EXPLICIT_SCHED);
+    assert_eq!(policy, libc::SCHED_FIFO);
+    assert_eq!(param.sched_priority, 23);
+}
+
+#[cfg(target_os = "linux")]
+#[rstest]
+fn set_and_get_io_priority(
+    #[values(
+        IoPriority { class: IoPriorityClass::BestEffort, level: IoPriority::LEVEL_MIN },
+        IoPriority { class: IoPriorityClass::BestEffort, level: 4 },
+        IoPriority { class: IoPriorityClass::Idle, level: 0 }
+    )]
+    priority: IoPriority,
+) {
+    std::thread::spawn(move || {
+        let thread_id = thread_native_id();
+        assert_eq!(set_thread_io_priority(thread_id, priority), Ok(()));
+        assert_eq!(get_thread_io_priority(thread_id), Ok(priority));
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn set_io_priority_with_invalid_level() {
+    let priority = IoPriority {
+        class: IoPriorityClass::BestEffort,
+        level: 8,
+    };
+    assert_eq!(
+        set_thread_io_priority(thread_native_id(), priority),
+        Err(Error::PriorityNotInRange(0..=7))
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn round_robin_interval_follows_config_requires_capabilities() {
+    let config = RoundRobinConfig::current().unwrap();
+    assert_eq!(config.apply(), Ok(()));
+    assert_eq!(RoundRobinConfig::current(), Ok(config));
+
+    std::thread::spawn(move || {
+        let thread_id = thread_native_id();
+        set_thread_priority_and_policy(
+            thread_id,
+            ThreadPriority::Min,
+            ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin),
+        )
+        .unwrap();
+        assert_eq!(
+            get_thread_round_robin_interval(thread_id),
+            Ok(config.timeslice)
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn qos_class_can_be_restored() {
+    std::thread::spawn(|| {
+        assert_eq!(set_current_thread_qos_class(QosClass::Utility, -5), Ok(()));
+        let saved = get_current_thread_qos_class().unwrap();
+        assert_eq!(saved, (QosClass::Utility, -5));
+
+        assert_eq!(
+            set_current_thread_qos_class(QosClass::UserInitiated, 0),
+            Ok(())
+        );
+        assert_eq!(
+            get_thread_qos_class(thread_native_id()),
+            Ok((QosClass::UserInitiated, 0))
+        );
+        assert_eq!(set_current_thread_qos_class(saved.0, saved.1), Ok(()));
+        assert_eq!(get_current_thread_qos_class(), Ok(saved));
+        assert!(set_current_thread_qos_class(QosClass::Default, 1).is_err());
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn priority_reflects_the_qos_class() {
+    std::thread::spawn(|| {
+        set_current_thread_qos_class(QosClass::UserInitiated, -2).unwrap();
+        let priority = get_current_thread_priority().unwrap();
+        assert_eq!(
+            priority,
+            ThreadPriority::Crossplatform(35u8.try_into().unwrap())
+        );
+
+        set_current_thread_qos_class(QosClass::Background, 0).unwrap();
+        let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+        let min = ThreadPriority::min_value_for_policy(normal).unwrap();
+        assert_eq!(
+            get_current_thread_priority().unwrap().to_posix(normal),
+            Ok(min)
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn builder_sets_the_qos_class() {
+    let class = ThreadBuilder::default()
+        .qos_class(QosClass::Utility)
+        .spawn(|result| {
+            result.unwrap();
+            get_current_thread_qos_class().unwrap().0
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(class, QosClass::Utility);
+
+    let mut attr = std::mem::MaybeUninit::<libc::pthread_attr_t>::uninit();
+    assert_eq!(unsafe { libc::pthread_attr_init(attr.as_mut_ptr()) }, 0);
+    let mut attr = unsafe { attr.assume_init() };
+    assert_eq!(
+        thread_priority::unix::attr::set_qos_class(&mut attr, QosClass::Background, 0),
+        Ok(())
+    );
+    assert!(thread_priority::unix::attr::set_qos_class(&mut attr, QosClass::Default, 1).is_err());
+    unsafe { libc::pthread_attr_destroy(&mut attr) };
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn qos_override_raises_the_worker_class() {
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let worker = std::thread::spawn(move || {
+        set_current_thread_qos_class(QosClass::Background, 0).unwrap();
+        receiver.recv().unwrap();
+    });
+
+    let qos_override = QosOverride::start(worker.get_native_id(), QosClass::UserInitiated);
+    assert!(qos_override.is_ok());
+    drop(qos_override);
+
+    let qos_override = QosOverride::start(worker.get_native_id(), QosClass::Utility).unwrap();
+    assert_eq!(qos_override.end(), Ok(()));
+
+    sender.send(()).unwrap();
+    worker.join().unwrap();
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn set_time_constraint_policy() {
+    use std::time::Duration;
+
+    let period = Duration::from_millis(10);
+    let units = duration_to_mach_time(period).unwrap();
+    let restored = mach_time_to_duration(units);
+    assert!(period.saturating_sub(restored) < Duration::from_micros(1));
+
+    assert!(set_current_thread_time_constraint_policy(
+        period,
+        Duration::from_millis(5),
+        Duration::from_millis(2),
+        true,
+    )
+    .is_err());
+    assert_eq!(
+        set_current_thread_time_constraint_policy(
+            period,
+            Duration::from_millis(2),
+            Duration::from_millis(5),
+            true,
+        ),
+        Ok(())
+    );
+
+    let policy = get_thread_time_constraint_policy(thread_native_id()).unwrap();
+    assert!(!policy.default);
+    assert!(policy.preemptible);
+    assert!(period.saturating_sub(policy.period) < Duration::from_micros(1));
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn affinity_tag_is_set_where_supported() {
+    std::thread::spawn(|| {
+        // The Apple Silicon machines don't support the affinity policy.
+        if set_current_thread_affinity_tag(7).is_ok() {
+            assert_eq!(get_thread_affinity_tag(thread_native_id()), Ok(7));
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn mach_port_and_global_id_identify_the_thread() {
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let handle = std::thread::spawn(move || {
+        receiver.recv().unwrap();
+        (thread_native_id(), thread_global_id(thread_native_id()))
+    });
+    let native = handle.get_native_id();
+    let port = thread_mach_port(native);
+    assert_eq!(thread_from_mach_port(port), Some(native));
+    let global_id = thread_global_id(native).unwrap();
+    assert_ne!(global_id, thread_global_id(thread_native_id()).unwrap());
+
+    sender.send(()).unwrap();
+    assert_eq!(handle.join().unwrap(), (native, Ok(global_id)));
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn get_mach_policies() {
+    let native = thread_native_id();
+    assert!(get_thread_extended_policy(native).is_ok());
+    assert!(get_thread_precedence_policy(native).is_ok());
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn importance_is_set_through_precedence_policy() {
+    std::thread::spawn(|| {
+        assert_eq!(set_current_thread_importance(3), Ok(()));
+        let policy = get_thread_precedence_policy(thread_native_id()).unwrap();
+        assert_eq!(policy.importance, 3);
+        assert!(!policy.default);
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn kernel_thread_id_operations() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let (done_sender, done_receiver) = std::sync::mpsc::channel::<()>();
+    let thread = std::thread::spawn(move || {
+        sender
+            .send((KernelThreadId::current(), thread_native_id()))
+            .unwrap();
+        done_receiver.recv().unwrap();
+        unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) }
+    });
+    let (tid, native) = receiver.recv().unwrap();
+    assert_ne!(tid, KernelThreadId::current());
+
+    let niceness = tid.niceness().unwrap();
+    assert_eq!(tid.set_niceness(niceness), Ok(()));
+    assert_eq!(tid.niceness(), Ok(niceness));
+
+    let cores = tid.affinity().unwrap();
+    assert_eq!(tid.set_affinity(&cores), Ok(()));
+    assert_eq!(tid.affinity(), Ok(cores));
+
+    let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    assert_eq!(
+        tid.set_priority_and_policy(ThreadPriority::Min, normal),
+        Ok(())
+    );
+    assert!(tid.scheduling_attributes().is_ok());
+    let niceness = tid.niceness().unwrap();
+
+    // The pthread ids of the other threads aren't kernel thread ids.
+    assert!(get_thread_util_clamp(native).is_err());
+    let (min, max) = tid.util_clamp().unwrap();
+    assert!(set_thread_util_clamp(native, min, max).is_err());
+    match tid.set_util_clamp(min, max) {
+        // The kernel may be built without the utilization clamping support.
+        Err(Error::OS(libc::EOPNOTSUPP)) => {}
+        result => assert_eq!(result, Ok(())),
+    }
+    let io_priority = tid.io_priority().unwrap();
+    assert!(get_thread_io_priority(native).is_err());
+    assert!(set_thread_io_priority(native, io_priority).is_err());
+    assert_eq!(tid.set_io_priority(io_priority), Ok(()));
+    assert!(get_thread_round_robin_interval(native).is_err());
+    assert_eq!(tid.round_robin_interval(), Ok(std::time::Duration::ZERO));
+    let parameters = DeadlineParameters::new(
+        std::time::Duration::from_millis(1),
+        std::time::Duration::from_millis(10),
+        std::time::Duration::from_millis(100),
+    );
+    assert!(matches!(
+        set_thread_deadline_parameters(native, &parameters),
+        Err(Error::Priority(_))
+    ));
+
+    done_sender.send(()).unwrap();
+    assert_eq!(thread.join().unwrap(), niceness);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn run_with_priority_returns_the_result() {
+    let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let caller = thread_native_id();
+
+    assert_eq!(
+        run_with_priority(ThreadPriority::Min, normal, || {
+            assert_ne!(thread_native_id(), caller);
+            get_current_thread_priority()
+        }),
+        Ok(Ok(ThreadPriority::Crossplatform(0u8.try_into().unwrap())))
+    );
+
+    let deadline = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline);
+    assert!(run_with_priority(ThreadPriority::Min, deadline, || unreachable!()).is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[rstest]
+#[case(ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Idle), 0, 0)]
+#[case(ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other), 19, 1)]
+#[case(ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other), -20, 50)]
+#[case(
+    ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+    1,
+    51
+)]
+#[case(
+    ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+    99,
+    99
+)]
+#[case(
+    ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Deadline),
+    0,
+    100
+)]
+fn effective_score_of_the_interpreted_priority(
+    #[case] policy: ThreadSchedulePolicy,
+    #[case] raw: i32,
+    #[case] score: u8,
+) {
+    let priority = InterpretedPriority {
+        policy,
+        raw,
+        crossplatform: None,
+    };
+    assert_eq!(priority.effective_score(), score);
+    assert!(effective_priority_score(thread_native_id()).unwrap() <= 100);
+}
+
+// Running this test requires CAP_SYS_NICE.
+#[test]
+fn privileges_are_detected_requires_capabilities() {
+    assert_eq!(can_set_realtime_priority(), Ok(true));
+    assert_eq!(can_lower_niceness(), Ok(true));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn background_chunked_work_runs_with_the_idle_policy() {
+    let handle = run_background_chunked(
+        || Some(thread_schedule_policy()),
+        std::time::Duration::from_millis(1),
+    )
+    .unwrap();
+    assert_eq!(
+        handle.join().unwrap(),
+        Ok(ThreadSchedulePolicy::Normal(
+            NormalThreadSchedulePolicy::Idle
+        ))
+    );
+}
+
+#[test]
+fn current_thread_cpu_time_increases() {
+    let before = get_current_thread_cpu_time().unwrap();
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_millis(5) {}
+    assert!(get_current_thread_cpu_time().unwrap() > before);
+}
+
+#[rstest]
+#[cfg_attr(
+    not(target_os = "android"),
+    case(
+        ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other),
+        libc::SCHED_OTHER
+    )
+)]
+#[case(
+    ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo),
+    libc::SCHED_FIFO
+)]
+#[case(
+    ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin),
+    libc::SCHED_RR
+)]
+#[cfg_attr(
+    target_os = "linux",
+    case(
+        ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Idle),
+        libc::SCHED_IDLE
+    )
+)]
+#[cfg_attr(
+    target_os = "linux",
+    case(
+        ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Batch),
+        libc::SCHED_BATCH
+    )
+)]
+fn raw_policy_round_trip(#[case] policy: ThreadSchedulePolicy, #[case] raw: libc::c_int) {
+    assert_eq!(policy.to_raw(), raw);
+    assert_eq!(ThreadSchedulePolicy::from_raw(raw), Ok(policy));
+}
+
+#[test]
+fn unknown_raw_policy_is_rejected() {
+    assert!(matches!(
+        ThreadSchedulePolicy::from_raw(-1),
+        Err(Error::Ffi(_))
+    ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn schedule_plan_tries_the_fallbacks_after_a_failure() {
+    use thread_priority::plan::PlannedAction;
+
+    let normal_policy = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+    let realtime_policy = ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo);
+    let invalid: ThreadPriority = "os:1000".parse().unwrap();
+    std::thread::spawn(move || {
+        let builder = ThreadBuilder::default()
+            .affinity(&[0])
+            .priority(invalid)
+            .policy(realtime_policy)
+            .fallback(normal_policy, ThreadPriority::Min);
+        let plan = builder.plan_for(thread_native_id()).unwrap();
+        assert_eq!(
+            plan.actions(),
+            &[
+                PlannedAction::SetAffinity { cores: vec![0] },
+                PlannedAction::SetPriorityAndPolicy {
+                    priority: invalid,
+                    policy: realtime_policy,
+                },
+                PlannedAction::TryFallbacks {
+                    fallbacks: vec![(normal_policy, ThreadPriority::Min)],
+                },
+            ]
+        );
+        assert_eq!(
+            plan.actions()[2].to_string(),
+            "if that fails, try the priority min with other"
+        );
+        assert_eq!(plan.apply(), Ok(()));
+        assert_eq!(thread_schedule_policy(), Ok(normal_policy));
+
+        let builder = ThreadBuilder::default().policy(realtime_policy);
+        assert!(builder.plan_for(thread_native_id()).is_err());
+    })
+    .join()
+    .unwrap();
+}
+
+#[rstest]
+#[case(ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other))]
+#[case(ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo))]
+#[case(ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin))]
+fn fraction_priority_spans_the_allowed_range(#[case] policy: ThreadSchedulePolicy) {
+    let lowest = ThreadPriority::from_fraction(0.0).unwrap();
+    let highest = ThreadPriority::from_fraction(1.0).unwrap();
+    let (min, max) = match (
+        ThreadPriority::min_value_for_policy(policy),
+        ThreadPriority::max_value_for_policy(policy),
+    ) {
+        (Ok(min), Ok(max)) => (min, max),
+        // The policy doesn't support the priorities on this platform.
+        _ => return,
+    };
+    assert_eq!(lowest.to_posix(policy), Ok(min));
+    assert_eq!(highest.to_posix(policy), Ok(max));
+
+    let middle = ThreadPriority::from_fraction(0.5)
+        .unwrap()
+        .to_posix(policy)
+        .unwrap();
+    assert!(min.min(max) <= middle && middle <= min.max(max));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn managed_thread_controls_the_thread_from_outside() {
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let mut thread = ThreadBuilder::default()
+        .name("Managed")
+        .spawn_managed(move || {
+            receiver.recv().unwrap();
+            (KernelThreadId::current(), thread_schedule_policy())
+        })
+        .unwrap();
+    assert_eq!(thread.thread().name(), Some("Managed"));
+
+    let priority = ThreadPriority::Crossplatform(30u8.try_into().unwrap());
+    assert_eq!(
+        thread.retune(ThreadBuilder::default().priority(priority)),
+        Ok(())
+    );
+    assert_eq!(thread.pin(&[0]), Ok(()));
+    assert_eq!(
+        thread.config(),
+        &ThreadBuilder::default()
+            .name("Managed")
+            .priority(priority)
+            .affinity(&[0])
+    );
+
+    let snapshot = thread.snapshot().unwrap();
+    assert_eq!(
+        snapshot.policy,
+        ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other)
+    );
+    assert_eq!(snapshot.affinity, vec![0]);
+    assert!(thread.stats().is_ok());
+
+    let kernel_id = thread.kernel_id();
+    sender.send(()).unwrap();
+    let (current, policy) = thread.join().unwrap();
+    assert_eq!(current, kernel_id);
+    assert_eq!(policy, Ok(snapshot.policy));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn kernel_thread_cpu_time_matches_the_current_thread_one() {
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_millis(5) {}
+    let before = KernelThreadId::current().cpu_time().unwrap();
+    assert!(before > std::time::Duration::ZERO);
+    assert!(get_current_thread_cpu_time().unwrap() >= before);
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+#[test]
+fn rtprio_of_a_regular_thread_is_normal() {
+    std::thread::spawn(|| {
+        assert_eq!(
+            get_current_thread_rtprio().map(|rtprio| rtprio.class),
+            Ok(RtprioClass::Normal)
+        );
+        assert_eq!(
+            get_thread_rtprio(current_thread_lwpid()).map(|rtprio| rtprio.class),
+            Ok(RtprioClass::Normal)
+        );
+        assert_eq!(
+            set_current_thread_rtprio(Rtprio {
+                class: RtprioClass::Realtime,
+                level: 32,
+            }),
+            Err(Error::PriorityNotInRange(0..=31))
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "freebsd")]
+#[test]
+fn lwp_affinity_matches_the_thread_affinity() {
+    std::thread::spawn(|| {
+        let allowed = get_allowed_cpus().unwrap();
+        assert!(!allowed.is_empty());
+        assert_eq!(
+            set_lwp_affinity(current_thread_lwpid(), &allowed[..1]),
+            Ok(())
+        );
+        assert_eq!(
+            get_thread_affinity(thread_native_id()).unwrap(),
+            allowed[..1].to_vec()
+        );
+        assert_eq!(
+            get_lwp_affinity(current_thread_lwpid()).unwrap(),
+            allowed[..1].to_vec()
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "netbsd")]
+#[test]
+fn normal_priority_is_set_for_the_lwp() {
+    let main_lwpid = current_thread_lwpid();
+    std::thread::spawn(move || {
+        assert_ne!(current_thread_lwpid(), main_lwpid);
+        let policy = thread_schedule_policy().unwrap();
+        assert_eq!(
+            set_thread_priority_and_policy(thread_native_id(), ThreadPriority::Min, policy),
+            Ok(())
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+#[test]
+fn fixed_priority_class_is_set_for_the_lwp() {
+    use thread_priority::unix::priocntl::*;
+
+    std::thread::spawn(|| {
+        let range = SchedulingClass::FixedPriority.priority_range().unwrap();
+        assert_eq!(
+            set_current_lwp_scheduling_class(SchedulingClass::FixedPriority, *range.start()),
+            Ok(())
+        );
+        assert_eq!(
+            get_lwp_scheduling_class(thread_native_id() as libc::id_t),
+            Ok((SchedulingClass::FixedPriority, *range.start()))
+        );
+        assert_eq!(
+            thread_schedule_policy(),
+            Ok(ThreadSchedulePolicy::Normal(
+                NormalThreadSchedulePolicy::Fixed
+            ))
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "android")]
+#[test]
+fn android_priority_is_applied_as_niceness() {
+    std::thread::spawn(|| {
+        assert_eq!(
+            set_current_thread_android_priority(AndroidThreadPriority::Background),
+            Ok(())
+        );
+        let tid = KernelThreadId::current();
+        assert_eq!(tid.niceness(), Ok(10));
+        assert_eq!(
+            get_android_thread_priority(tid),
+            Ok(AndroidThreadPriority::Background)
+        );
+        assert!(AndroidThreadPriority::try_from(5).is_err());
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "android")]
+#[test]
+fn audio_thread_is_promoted_to_fifo_or_urgent_audio() {
+    std::thread::spawn(|| {
+        let policy = promote_to_audio_thread().unwrap();
+        if policy == ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::Fifo) {
+            assert_eq!(
+                thread_schedule_policy_param(thread_native_id()).map(|(_, params)| params),
+                Ok(ScheduleParams { sched_priority: 2 })
+            );
+        } else {
+            assert_eq!(
+                get_android_thread_priority(KernelThreadId::current()),
+                Ok(AndroidThreadPriority::UrgentAudio)
+            );
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn realtime_policies_are_available() {
+    assert!(RealtimeThreadSchedulePolicy::Fifo.is_available());
+    assert!(RealtimeThreadSchedulePolicy::RoundRobin.is_available());
+    #[cfg(target_os = "linux")]
+    assert!(RealtimeThreadSchedulePolicy::Deadline.is_available());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn child_process_is_started_with_niceness() {
+    let output = std::process::Command::new("nice")
+        .niceness(7)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "7");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn panicking_thread_is_demoted_requires_capabilities() {
+    struct ReportPolicy(std::sync::mpsc::Sender<ThreadSchedulePolicy>);
+
+    impl Drop for ReportPolicy {
+        fn drop(&mut self) {
+            self.0.send(thread_schedule_policy().unwrap()).unwrap();
+        }
+    }
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let handle = ThreadBuilder::default()
+        .policy(ThreadSchedulePolicy::Realtime(
+            RealtimeThreadSchedulePolicy::Fifo,
+        ))
+        .priority(ThreadPriority::Min)
+        .demote_on_panic(true)
+        .spawn(move |result| {
+            result.unwrap();
+            // Dropped by the unwinding, which starts once the thread is demoted.
+            let _report = ReportPolicy(sender);
+            panic!("The thread must be demoted.");
+        })
+        .unwrap();
+    assert!(handle.join().is_err());
+    assert_eq!(
+        receiver.recv(),
+        Ok(ThreadSchedulePolicy::Normal(
+            NormalThreadSchedulePolicy::Other
+        ))
+    );
+}
+
+#[cfg(any(target_os = "openbsd", target_os = "dragonfly"))]
+#[test]
+fn normal_priority_changes_the_process_niceness_unless_strict() {
+    std::thread::spawn(|| {
+        let normal = ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Other);
+        let native = thread_native_id();
+        assert!(
+            set_thread_priority_and_policy_strict(native, ThreadPriority::Min, normal).is_err()
+        );
+        assert_eq!(
+            set_thread_priority_and_policy(native, ThreadPriority::Min, normal),
+            Ok(())
+        );
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,21 @@
+#![cfg(not(any(unix, windows)))]
+use rstest::rstest;
+use thread_priority::*;
+
+#[rstest]
+fn scheduling_is_reported_as_unsupported() {
+    assert_eq!(capabilities(), Capabilities::default());
+    assert_eq!(
+        set_current_thread_priority(ThreadPriority::Min),
+        Err(Error::UnsupportedPlatform)
+    );
+    assert_eq!(
+        get_current_thread_priority(),
+        Err(Error::UnsupportedPlatform)
+    );
+    assert_eq!(
+        Error::UnsupportedPlatform.kind(),
+        ErrorKind::UnsupportedOnPlatform
+    );
+    assert_eq!(thread_native_id(), thread_native_id());
+}
@@ -18,3 +18,473 @@ fn get_and_set_priority_requires_capabilities(
     assert_eq!(set_result, Ok(()));
     assert_eq!(get_result, Ok(expected_priority),);
 }
+
+#[test]
+fn processor_topology_is_consistent() {
+    let topology = processor_topology().unwrap();
+
+    assert!(!topology.groups.is_empty());
+    assert!(!topology.cores.is_empty());
+    assert_eq!(
+        topology.active_processor_count() as usize,
+        topology
+            .cores
+            .iter()
+            .map(|core| core.processors.len())
+            .sum::<usize>()
+    );
+}
+
+#[test]
+fn should_be_possible_to_reset_the_same_process_priority_class() {
+    let priority_class = get_process_priority_class().unwrap();
+
+    assert_eq!(set_process_priority_class(priority_class), Ok(()));
+    assert_eq!(get_process_priority_class(), Ok(priority_class));
+}
+
+#[rstest]
+fn get_and_set_memory_priority(
+    #[values(
+        MemoryPriority::VeryLow,
+        MemoryPriority::Medium,
+        MemoryPriority::Normal
+    )]
+    priority: MemoryPriority,
+) {
+    let thread_id = thread_native_id();
+
+    assert_eq!(set_thread_memory_priority(thread_id, priority), Ok(()));
+    assert_eq!(get_thread_memory_priority(thread_id), Ok(priority));
+}
+
+#[rstest]
+#[case(23, 30, true)]
+#[case(23, 50, false)]
+fn check_priority_equivalence(#[case] first: u8, #[case] second: u8, #[case] equivalent: bool) {
+    let first = ThreadPriority::Crossplatform(first.try_into().unwrap());
+    let second = ThreadPriority::Crossplatform(second.try_into().unwrap());
+
+    assert_eq!(first.is_equivalent(second), Ok(equivalent));
+}
+
+#[test]
+fn should_disable_boost_only_when_requested() {
+    let thread = ThreadBuilder::default()
+        .winapi_priority(WinAPIThreadPriority::TimeCritical)
+        .spawn(|result| {
+            result.unwrap();
+            get_current_thread_priority_boost()
+        })
+        .unwrap();
+    assert_eq!(thread.join().unwrap(), Ok(true));
+
+    let thread = ThreadBuilder::default()
+        .realtime_preset(WinAPIThreadPriority::TimeCritical)
+        .spawn(|result| {
+            result.unwrap();
+            get_current_thread_priority_boost()
+        })
+        .unwrap();
+    assert_eq!(thread.join().unwrap(), Ok(false));
+}
+
+#[test]
+fn should_apply_power_aware_priorities() {
+    use std::os::windows::io::AsRawHandle;
+    use thread_priority::windows::power::PowerAwareThreads;
+
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let thread = std::thread::spawn(move || receiver.recv().unwrap());
+    let native = thread.as_raw_handle() as ThreadId;
+
+    let threads = PowerAwareThreads::default();
+    threads.register(
+        native,
+        ThreadPriority::Os(WinAPIThreadPriority::Normal.into()),
+        ThreadPriority::Os(WinAPIThreadPriority::Lowest.into()),
+    );
+
+    assert_eq!(threads.apply(true), Ok(()));
+    assert_eq!(
+        get_thread_priority(native),
+        Ok(ThreadPriority::Os(WinAPIThreadPriority::Lowest.into()))
+    );
+    assert_eq!(threads.apply(false), Ok(()));
+    assert_eq!(
+        get_thread_priority(native),
+        Ok(ThreadPriority::Os(WinAPIThreadPriority::Normal.into()))
+    );
+
+    sender.send(()).unwrap();
+    thread.join().unwrap();
+}
+
+#[test]
+fn dynamic_priority_is_not_below_the_base_one() {
+    set_current_thread_priority_boost(false).unwrap();
+    let priority = get_current_thread_dynamic_priority().unwrap();
+
+    assert!((0..=31).contains(&priority.base));
+    assert!(priority.current >= priority.base);
+    assert_eq!(priority.boost(), priority.current - priority.base);
+}
+
+#[test]
+fn should_set_the_builder_name_as_the_thread_description() {
+    let thread = ThreadBuilder::default()
+        .name("MyThread")
+        .spawn(|result| {
+            result.unwrap();
+            get_thread_description(thread_native_id())
+        })
+        .unwrap();
+    assert_eq!(thread.join().unwrap(), Ok("MyThread".to_owned()));
+
+    assert!(set_thread_description(thread_native_id(), "My\0Thread").is_err());
+}
+
+#[test]
+fn should_list_the_process_threads() {
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let (started_sender, started) = std::sync::mpsc::channel::<()>();
+    let thread = std::thread::Builder::new()
+        .name("ListedThread".to_owned())
+        .spawn(move || {
+            started_sender.send(()).unwrap();
+            receiver.recv().unwrap()
+        })
+        .unwrap();
+    // The name is set by the thread itself once it starts.
+    started.recv().unwrap();
+
+    let threads = Process::current().threads().unwrap();
+    assert!(threads.len() >= 2);
+    assert!(threads
+        .iter()
+        .any(|thread| thread.name.as_deref() == Some("ListedThread")));
+
+    sender.send(()).unwrap();
+    thread.join().unwrap();
+}
+
+#[test]
+fn cpu_sets_are_consistent_with_the_topology() {
+    let topology = processor_topology().unwrap();
+    let cpu_sets = system_cpu_sets().unwrap();
+
+    assert_eq!(cpu_sets.len(), topology.active_processor_count() as usize);
+    assert!(cpu_sets
+        .iter()
+        .all(|cpu_set| topology.contains(cpu_set.group, cpu_set.logical_processor_index as usize)));
+    assert_eq!(get_thread_selected_cpu_sets(thread_native_id()), Ok(vec![]));
+}
+
+#[rstest]
+#[case(0, 0)]
+#[case(8, 26)]
+#[case(15, 50)]
+#[case(16, 53)]
+#[case(31, 100)]
+fn effective_score_of_the_dynamic_priority(#[case] current: i32, #[case] score: u8) {
+    let priority = ThreadDynamicPriority {
+        base: current,
+        current,
+    };
+    assert_eq!(priority.effective_score(), score);
+}
+
+#[test]
+fn should_detect_the_realtime_privilege() {
+    assert!(can_set_realtime_priority().is_ok());
+}
+
+#[test]
+fn should_configure_the_main_thread() {
+    let main = Process::current().main_thread_id().unwrap();
+    assert!(Process::current().thread_ids().unwrap().contains(&main));
+
+    let config = ThreadBuilder::default().boost(true);
+    assert_eq!(configure_main_thread(&config), Ok(()));
+}
+
+#[test]
+fn realtime_relinquish_guard_restores_the_priority() {
+    std::thread::spawn(|| {
+        let guard = RealtimeRelinquishGuard::relinquish().unwrap();
+        assert!(!guard.is_relinquished());
+        drop(guard);
+
+        let lowest = ThreadPriority::Os(WinAPIThreadPriority::Lowest.into());
+        set_current_thread_priority(lowest).unwrap();
+        let guard = RealtimeRelinquishGuard::relinquish().unwrap();
+        assert!(!guard.is_relinquished());
+        assert_eq!(get_current_thread_priority(), Ok(lowest));
+        drop(guard);
+
+        let highest = ThreadPriority::Os(WinAPIThreadPriority::Highest.into());
+        set_current_thread_priority(highest).unwrap();
+        let guard = RealtimeRelinquishGuard::relinquish().unwrap();
+        assert!(guard.is_relinquished());
+        assert_eq!(
+            get_current_thread_priority(),
+            Ok(ThreadPriority::Os(WinAPIThreadPriority::Normal.into()))
+        );
+        assert_eq!(guard.restore(), Ok(()));
+        assert_eq!(get_current_thread_priority(), Ok(highest));
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn should_set_the_priority_of_another_thread_by_its_id() {
+    let main = Process::current().main_thread_id().unwrap();
+    std::thread::spawn(move || {
+        let thread = OwnedThreadId::from_os_id(main).unwrap();
+        let priority = get_thread_priority(thread.as_raw()).unwrap();
+        assert_eq!(set_thread_priority(thread.as_raw(), priority), Ok(()));
+        assert_eq!(set_thread_priority_boost(thread.as_raw(), true), Ok(()));
+    })
+    .join()
+    .unwrap();
+
+    assert!(OwnedThreadId::from_os_id(0).is_err());
+}
+
+#[test]
+fn owned_current_thread_id_refers_to_its_thread_from_other_threads() {
+    use std::os::windows::io::AsRawHandle;
+
+    let highest = ThreadPriority::Os(WinAPIThreadPriority::Highest.into());
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let (id_sender, id_receiver) = std::sync::mpsc::channel();
+    let thread = std::thread::spawn(move || {
+        id_sender.send(OwnedThreadId::current().unwrap()).unwrap();
+        receiver.recv().unwrap();
+        get_current_thread_priority()
+    });
+
+    let id = id_receiver.recv().unwrap();
+    assert_eq!(set_thread_priority(id.as_raw(), highest), Ok(()));
+    sender.send(()).unwrap();
+    assert_eq!(thread.join().unwrap(), Ok(highest));
+
+    // The handle is closed by the owned handle it was converted into.
+    let handle = std::os::windows::io::OwnedHandle::from(id);
+    assert!(get_thread_priority(handle.as_raw_handle() as ThreadId).is_ok());
+}
+
+#[test]
+fn current_thread_cpu_time_increases() {
+    let before = get_current_thread_cpu_time().unwrap();
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_millis(50) {}
+    assert!(get_current_thread_cpu_time().unwrap() > before);
+}
+
+#[test]
+fn should_set_the_raw_affinity_mask() {
+    std::thread::spawn(|| {
+        let native = thread_native_id();
+        let mask = get_thread_affinity_mask(native).unwrap();
+        let cores = get_thread_affinity(native).unwrap();
+        assert_eq!(mask.count_ones() as usize, cores.len());
+
+        let lowest = mask & mask.wrapping_neg();
+        assert_eq!(set_thread_affinity_mask(native, lowest), Ok(mask));
+        assert_eq!(get_thread_affinity_mask(native), Ok(lowest));
+        assert_eq!(set_thread_affinity_mask(native, mask), Ok(lowest));
+        assert!(set_thread_affinity_mask(native, 0).is_err());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn should_set_the_group_affinity_mask() {
+    std::thread::spawn(|| {
+        let native = thread_native_id();
+        let (group, mask) = get_thread_group_affinity(native).unwrap();
+        let lowest = mask & mask.wrapping_neg();
+        assert_eq!(
+            set_thread_group_affinity_mask(native, group, lowest),
+            Ok((group, mask))
+        );
+        assert_eq!(get_thread_group_affinity(native), Ok((group, lowest)));
+
+        let groups = processor_topology().unwrap().groups.len() as u16;
+        assert!(set_thread_group_affinity_mask(native, groups, lowest).is_err());
+    })
+    .join()
+    .unwrap();
+}
+
+#[rstest]
+#[case(0.0, WinAPIThreadPriority::Lowest)]
+#[case(0.5, WinAPIThreadPriority::Normal)]
+#[case(1.0, WinAPIThreadPriority::Highest)]
+fn fraction_priority_maps_onto_the_winapi_priorities(
+    #[case] fraction: f64,
+    #[case] expected: WinAPIThreadPriority,
+) {
+    let priority = ThreadPriority::from_fraction(fraction).unwrap();
+    assert_eq!(WinAPIThreadPriority::try_from(priority), Ok(expected));
+}
+
+#[test]
+fn thread_times_account_for_the_consumed_cpu_time() {
+    let before = std::time::SystemTime::now();
+    let times = std::thread::spawn(|| {
+        let start = std::time::Instant::now();
+        while start.elapsed() < std::time::Duration::from_millis(50) {}
+        get_thread_times(thread_native_id()).unwrap()
+    })
+    .join()
+    .unwrap();
+
+    assert!(times.cpu_time() > std::time::Duration::ZERO);
+    assert_eq!(times.cpu_time(), times.kernel + times.user);
+    // The creation time is only as precise as the system clock.
+    assert!(times.creation + std::time::Duration::from_millis(100) >= before);
+    assert!(times.creation <= std::time::SystemTime::now());
+}
+
+#[test]
+fn thread_rusage_accounts_for_the_consumed_cpu_time() {
+    let usage = std::thread::spawn(|| {
+        let start = std::time::Instant::now();
+        while start.elapsed() < std::time::Duration::from_millis(50) {}
+        get_current_thread_rusage().unwrap()
+    })
+    .join()
+    .unwrap();
+
+    assert!(usage.cpu_time() > std::time::Duration::ZERO);
+    assert_eq!(usage.cpu_time(), usage.user_time + usage.system_time);
+}
+
+#[test]
+fn thread_cycle_time_increases() {
+    let native = thread_native_id();
+    let before = get_thread_cycle_time(native).unwrap();
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_millis(5) {}
+    assert!(get_thread_cycle_time(native).unwrap() > before);
+}
+
+#[test]
+fn managed_thread_controls_the_thread_from_outside() {
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let mut thread = ThreadBuilder::default()
+        .spawn_managed(move || receiver.recv().unwrap())
+        .unwrap();
+
+    let lowest = ThreadPriority::Os(WinAPIThreadPriority::Lowest.into());
+    assert_eq!(
+        thread.retune(ThreadBuilder::default().priority(lowest)),
+        Ok(())
+    );
+    let cores = get_thread_affinity(thread.native_id()).unwrap();
+    assert_eq!(thread.pin(&cores[..1]), Ok(()));
+    assert_eq!(
+        thread.config(),
+        &ThreadBuilder::default()
+            .priority(lowest)
+            .affinity(&cores[..1])
+    );
+
+    let snapshot = thread.snapshot().unwrap();
+    assert_eq!(snapshot.priority, lowest);
+    assert_eq!(snapshot.affinity, cores[..1].to_vec());
+    assert!(thread.stats().is_ok());
+
+    sender.send(()).unwrap();
+    thread.join().unwrap();
+}
+
+#[test]
+fn priority_boost_can_be_saved_and_restored() {
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let handle = std::thread::spawn(move || receiver.recv().unwrap());
+
+    let saved = handle.get_priority_boost().unwrap();
+    assert_eq!(handle.set_priority_boost(!saved), Ok(()));
+    assert_eq!(handle.get_priority_boost(), Ok(!saved));
+    assert_eq!(handle.set_priority_boost(saved), Ok(()));
+    assert_eq!(get_thread_priority_boost(handle.get_native_id()), Ok(saved));
+
+    sender.send(()).unwrap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn ideal_processor_ex_is_restored() {
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let handle = std::thread::spawn(move || receiver.recv().unwrap());
+    let native = handle.get_native_id();
+
+    let (group, mask) = get_thread_group_affinity(native).unwrap();
+    let first = ProcessorNumber {
+        group,
+        number: mask.trailing_zeros() as u8,
+    };
+    let previous = set_thread_ideal_processor_ex(native, first).unwrap();
+    assert_eq!(get_thread_ideal_processor_ex(native), Ok(first));
+    assert_eq!(set_thread_ideal_processor_ex(native, previous), Ok(first));
+    assert_eq!(get_thread_ideal_processor_ex(native), Ok(previous));
+
+    sender.send(()).unwrap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn ideal_processor_is_within_numa_node() {
+    let (group, mask) = get_numa_node_group_affinity(0).unwrap();
+    let processor = ThreadBuilder::default()
+        .prefer_numa_node(0)
+        .spawn_careless(|| get_current_thread_ideal_processor_ex().unwrap())
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(processor.group, group);
+    assert_ne!(mask & (1 << processor.number), 0);
+
+    let native = thread_native_id();
+    let processor = set_thread_ideal_numa_node(native, 0).unwrap();
+    assert_eq!(set_thread_ideal_numa_node(native, 0), Ok(processor));
+    assert!(get_numa_node_group_affinity(u16::MAX).is_err());
+}
+
+#[test]
+fn child_process_is_started_in_priority_class() {
+    let status = std::process::Command::new("cmd")
+        .args(["/C", "exit"])
+        .priority_class(ProcessPriorityClass::BelowNormal)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn mmcss_task_is_left_when_demoted() {
+    std::thread::spawn(|| {
+        let task = MmcssTask::join("Pro Audio").unwrap();
+        assert_eq!(demote(task), Ok(()));
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn get_thread_priority_boost_returns_what_was_set() {
+    std::thread::spawn(|| {
+        let native = thread_native_id();
+        for enabled in [false, true] {
+            assert_eq!(set_thread_priority_boost(native, enabled), Ok(()));
+            assert_eq!(get_thread_priority_boost(native), Ok(enabled));
+        }
+    })
+    .join()
+    .unwrap();
+}